@@ -243,6 +243,97 @@ impl Evaluator for BFVEvaluator {
             )
         })?;
 
+        Ok(())
+    }
+    fn negate_into(&self, a: &Ciphertext, out: &mut Ciphertext) -> Result<()> {
+        self.0.negate_into(a, out)
+    }
+
+    fn add_into(&self, a: &Ciphertext, b: &Ciphertext, out: &mut Ciphertext) -> Result<()> {
+        self.0.add_into(a, b, out)
+    }
+
+    fn sub_into(&self, a: &Ciphertext, b: &Ciphertext, out: &mut Ciphertext) -> Result<()> {
+        self.0.sub_into(a, b, out)
+    }
+
+    fn multiply_into(&self, a: &Ciphertext, b: &Ciphertext, out: &mut Ciphertext) -> Result<()> {
+        self.0.multiply_into(a, b, out)
+    }
+
+    fn add_plain_into(&self, a: &Ciphertext, b: &Plaintext, out: &mut Ciphertext) -> Result<()> {
+        self.0.add_plain_into(a, b, out)
+    }
+
+    fn sub_plain_into(&self, a: &Ciphertext, b: &Plaintext, out: &mut Ciphertext) -> Result<()> {
+        self.0.sub_plain_into(a, b, out)
+    }
+
+    fn multiply_plain_into(
+        &self,
+        a: &Ciphertext,
+        b: &Plaintext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        self.0.multiply_plain_into(a, b, out)
+    }
+
+    fn relinearize_into(
+        &self,
+        a: &Ciphertext,
+        relin_keys: &RelinearizationKeys,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Relinearize(
+                self.get_handle(),
+                a.get_handle(),
+                relin_keys.get_handle(),
+                out.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn rotate_rows_into(
+        &self,
+        a: &Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_RotateRows(
+                self.get_handle(),
+                a.get_handle(),
+                steps,
+                galois_keys.get_handle(),
+                out.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn rotate_columns_into(
+        &self,
+        a: &Ciphertext,
+        galois_keys: &GaloisKeys,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_RotateColumns(
+                self.get_handle(),
+                a.get_handle(),
+                galois_keys.get_handle(),
+                out.get_handle(),
+                null_mut(),
+            )
+        })?;
+
         Ok(())
     }
 }
@@ -383,6 +474,30 @@ mod tests {
         });
     }
 
+    #[test]
+    fn can_add_into_reused_ciphertext() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
+            let a = make_vec(&encoder);
+            let b = make_vec(&encoder);
+            let a_p = encoder.encode_signed(&a).unwrap();
+            let b_p = encoder.encode_signed(&b).unwrap();
+            let a_c = encryptor.encrypt(&a_p).unwrap();
+            let b_c = encryptor.encrypt(&b_p).unwrap();
+
+            // Start from a ciphertext holding an unrelated value to check
+            // the old contents get overwritten.
+            let mut c_c = evaluator.negate(&a_c).unwrap();
+            evaluator.add_into(&a_c, &b_c, &mut c_c).unwrap();
+
+            let c_p = decryptor.decrypt(&c_c).unwrap();
+            let c = encoder.decode_signed(&c_p).unwrap();
+
+            for i in 0..a.len() {
+                assert_eq!(c[i], a[i] + b[i]);
+            }
+        });
+    }
+
     #[test]
     fn can_add_inplace() {
         run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
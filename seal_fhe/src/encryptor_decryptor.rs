@@ -88,6 +88,26 @@ impl Encryptor {
         Ok(Encryptor { handle })
     }
 
+    /**
+     * Creates an Encryptor instance initialized with the specified SEALContext
+     * and secret key. Such an Encryptor only supports
+     * [`encrypt_symmetric`](Self::encrypt_symmetric).
+     */
+    pub fn with_secret_key(ctx: &Context, secret_key: &SecretKey) -> Result<Encryptor> {
+        let mut handle: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::Encryptor_Create(
+                ctx.get_handle(),
+                null_mut(),
+                secret_key.get_handle(),
+                &mut handle,
+            )
+        })?;
+
+        Ok(Encryptor { handle })
+    }
+
     /**
      *
      * Encrypts a plaintext with the public key and returns the ciphertext as
@@ -115,6 +135,34 @@ impl Encryptor {
 
         Ok(ciphertext)
     }
+
+    /**
+     * Encrypts a plaintext with the secret key and returns the ciphertext.
+     *
+     * # Remarks
+     * The returned ciphertext stores the seed that generates its second
+     * polynomial in place of that polynomial, so it serializes to about
+     * half the size of a public key encryption. Until it's serialized and
+     * deserialized, it isn't valid for evaluation or decryption; see
+     * [`Ciphertext::is_valid_for`].
+     *
+     * * `plainext` - The plaintext to encrypt.
+     */
+    pub fn encrypt_symmetric(&self, plaintext: &Plaintext) -> Result<Ciphertext> {
+        let ciphertext = Ciphertext::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::Encryptor_EncryptSymmetric(
+                self.handle,
+                plaintext.get_handle(),
+                true,
+                ciphertext.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(ciphertext)
+    }
 }
 
 impl Drop for Encryptor {
@@ -386,4 +434,52 @@ mod tests {
 
         assert_eq!(data, data_2);
     }
+
+    #[test]
+    fn can_encrypt_symmetric_and_decrypt_after_serialization() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let data = (0..encoder.get_slot_count())
+            .map(|i| i as u64)
+            .collect::<Vec<_>>();
+
+        let plaintext = encoder.encode_unsigned(&data).unwrap();
+
+        let public_key = gen.create_public_key();
+        let secret_key = gen.secret_key();
+
+        let encryptor = Encryptor::with_secret_key(&ctx, &secret_key).unwrap();
+        let public_encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+
+        let seeded = encryptor.encrypt_symmetric(&plaintext).unwrap();
+        let full = public_encryptor.encrypt(&plaintext).unwrap();
+
+        assert!(!seeded.is_valid_for(&ctx));
+        assert!(full.is_valid_for(&ctx));
+
+        let bytes = seeded.as_bytes().unwrap();
+
+        assert!(bytes.len() < full.as_bytes().unwrap().len());
+
+        let ciphertext = Ciphertext::from_bytes(&ctx, &bytes).unwrap();
+
+        assert!(ciphertext.is_valid_for(&ctx));
+
+        let decrypted = decryptor.decrypt(&ciphertext).unwrap();
+
+        assert_eq!(encoder.decode_unsigned(&decrypted).unwrap(), data);
+    }
 }
@@ -277,4 +277,101 @@ pub trait Evaluator {
      * * `galoisKeys` - The Galois keys
      */
     fn rotate_columns_inplace(&self, a: &Ciphertext, galois_keys: &GaloisKeys) -> Result<()>;
+
+    /**
+     * Negates `a` and stores the result in `out`, reusing `out`'s memory when it's
+     * large enough.
+     * * `a` - the value to negate
+     * * `out` - the destination
+     */
+    fn negate_into(&self, a: &Ciphertext, out: &mut Ciphertext) -> Result<()>;
+
+    /**
+     * Adds `a` and `b` and stores the result in `out`, reusing `out`'s memory when
+     * it's large enough.
+     * * `a` - first operand
+     * * `b` - second operand
+     * * `out` - the destination
+     */
+    fn add_into(&self, a: &Ciphertext, b: &Ciphertext, out: &mut Ciphertext) -> Result<()>;
+
+    /**
+     * Subtracts `b` from `a` and stores the result in `out`, reusing `out`'s memory
+     * when it's large enough.
+     * * `a` - the left operand
+     * * `b` - the right operand
+     * * `out` - the destination
+     */
+    fn sub_into(&self, a: &Ciphertext, b: &Ciphertext, out: &mut Ciphertext) -> Result<()>;
+
+    /**
+     * Multiplies `a` and `b` and stores the result in `out`, reusing `out`'s memory
+     * when it's large enough.
+     * * `a` - the left operand
+     * * `b` - the right operand
+     * * `out` - the destination
+     */
+    fn multiply_into(&self, a: &Ciphertext, b: &Ciphertext, out: &mut Ciphertext) -> Result<()>;
+
+    /**
+     * Adds a ciphertext and a plaintext and stores the result in `out`.
+     * * `a` - the ciphertext
+     * * `b` - the plaintext
+     * * `out` - the destination
+     */
+    fn add_plain_into(&self, a: &Ciphertext, b: &Plaintext, out: &mut Ciphertext) -> Result<()>;
+
+    /**
+     * Subtracts a plaintext from a ciphertext and stores the result in `out`.
+     * * `a` - the ciphertext
+     * * `b` - the plaintext
+     * * `out` - the destination
+     */
+    fn sub_plain_into(&self, a: &Ciphertext, b: &Plaintext, out: &mut Ciphertext) -> Result<()>;
+
+    /**
+     * Multiplies a ciphertext by a plaintext and stores the result in `out`.
+     * * `a` - the ciphertext
+     * * `b` - the plaintext
+     * * `out` - the destination
+     */
+    fn multiply_plain_into(
+        &self,
+        a: &Ciphertext,
+        b: &Plaintext,
+        out: &mut Ciphertext,
+    ) -> Result<()>;
+
+    /**
+     * Relinearizes `a` and stores the result in `out`.
+     */
+    fn relinearize_into(
+        &self,
+        a: &Ciphertext,
+        relin_keys: &RelinearizationKeys,
+        out: &mut Ciphertext,
+    ) -> Result<()>;
+
+    /**
+     * Rotates plaintext matrix rows cyclically and stores the result in `out`. See
+     * [`rotate_rows`](Self::rotate_rows).
+     */
+    fn rotate_rows_into(
+        &self,
+        a: &Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+        out: &mut Ciphertext,
+    ) -> Result<()>;
+
+    /**
+     * Rotates plaintext matrix columns cyclically and stores the result in `out`. See
+     * [`rotate_columns`](Self::rotate_columns).
+     */
+    fn rotate_columns_into(
+        &self,
+        a: &Ciphertext,
+        galois_keys: &GaloisKeys,
+        out: &mut Ciphertext,
+    ) -> Result<()>;
 }
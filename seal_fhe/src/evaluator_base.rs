@@ -403,5 +403,123 @@ impl EvaluatorBase {
         Ok(())
     }
 
+    pub(crate) fn negate_into(&self, a: &Ciphertext, out: &mut Ciphertext) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Negate(self.handle, a.get_handle(), out.get_handle())
+        })?;
+
+        Ok(())
+    }
+
+    pub(crate) fn add_into(
+        &self,
+        a: &Ciphertext,
+        b: &Ciphertext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Add(
+                self.handle,
+                a.get_handle(),
+                b.get_handle(),
+                out.get_handle(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub(crate) fn sub_into(
+        &self,
+        a: &Ciphertext,
+        b: &Ciphertext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Sub(
+                self.handle,
+                a.get_handle(),
+                b.get_handle(),
+                out.get_handle(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub(crate) fn multiply_into(
+        &self,
+        a: &Ciphertext,
+        b: &Ciphertext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Multiply(
+                self.handle,
+                a.get_handle(),
+                b.get_handle(),
+                out.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub(crate) fn add_plain_into(
+        &self,
+        a: &Ciphertext,
+        b: &Plaintext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_AddPlain(
+                self.get_handle(),
+                a.get_handle(),
+                b.get_handle(),
+                out.get_handle(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub(crate) fn sub_plain_into(
+        &self,
+        a: &Ciphertext,
+        b: &Plaintext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_SubPlain(
+                self.get_handle(),
+                a.get_handle(),
+                b.get_handle(),
+                out.get_handle(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub(crate) fn multiply_plain_into(
+        &self,
+        a: &Ciphertext,
+        b: &Plaintext,
+        out: &mut Ciphertext,
+    ) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_MultiplyPlain(
+                self.get_handle(),
+                a.get_handle(),
+                b.get_handle(),
+                out.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(())
+    }
+
     // TODO: NTT transform.
 }
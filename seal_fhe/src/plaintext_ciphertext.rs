@@ -252,14 +252,42 @@ impl Plaintext {
         .expect("Fatal error in Plaintext::index().");
     }
 
+    /**
+     * Sets the coefficient at the given location. Unlike
+     * [`set_coefficient`](Plaintext::set_coefficient), this returns
+     * [`Error::InvalidArgument`] rather than panicking when `index` is out
+     * of bounds and surfaces any error SEAL reports.
+     */
+    pub fn try_set_coefficient(&mut self, index: usize, value: u64) -> Result<()> {
+        if index >= self.len() {
+            return Err(Error::InvalidArgument);
+        }
+
+        convert_seal_error(unsafe {
+            bindgen::Plaintext_SetCoeffAt(self.handle, index as u64, value)
+        })
+    }
+
     /**
      * Sets the number of coefficients this plaintext can hold.
+     *
+     * # Panics
+     * Panics if SEAL fails to resize the plaintext. See
+     * [`try_resize`](Plaintext::try_resize) for a fallible variant.
      */
     pub fn resize(&mut self, count: usize) {
-        convert_seal_error(unsafe { bindgen::Plaintext_Resize(self.handle, count as u64) })
+        self.try_resize(count)
             .expect("Fatal error in Plaintext::resize().");
     }
 
+    /**
+     * Sets the number of coefficients this plaintext can hold, returning
+     * an error if SEAL fails to do so (e.g. the allocation would overflow).
+     */
+    pub fn try_resize(&mut self, count: usize) -> Result<()> {
+        convert_seal_error(unsafe { bindgen::Plaintext_Resize(self.handle, count as u64) })
+    }
+
     /**
      * Returns the number of coefficients this plaintext can hold.
      */
@@ -351,6 +379,54 @@ impl Ciphertext {
 
         size
     }
+
+    /**
+     * Returns the number of primes in the coefficient modulus this ciphertext
+     * is currently encrypted under. Each modulus switch drops one prime, so
+     * ciphertexts at a lower level in the modulus switching chain return a smaller value.
+     */
+    pub fn coeff_modulus_size(&self) -> u64 {
+        let mut size: u64 = 0;
+
+        convert_seal_error(unsafe { bindgen::Ciphertext_CoeffModulusSize(self.handle, &mut size) })
+            .unwrap();
+
+        size
+    }
+
+    /**
+     * Returns whether this ciphertext is transparent, i.e. its secret-key
+     * dependent polynomials are all zero so it decrypts without the key.
+     */
+    pub fn is_transparent(&self) -> bool {
+        let mut result = false;
+
+        convert_seal_error(unsafe { bindgen::Ciphertext_IsTransparent(self.handle, &mut result) })
+            .unwrap();
+
+        result
+    }
+
+    /**
+     * Returns whether this ciphertext's metadata and data are valid for
+     * the given context.
+     *
+     * # Remarks
+     * Seeded ciphertexts from
+     * [`Encryptor::encrypt_symmetric`](crate::Encryptor::encrypt_symmetric)
+     * aren't valid until serialized and deserialized, which expands the
+     * seed.
+     */
+    pub fn is_valid_for(&self, context: &Context) -> bool {
+        let mut result = false;
+
+        convert_seal_error(unsafe {
+            bindgen::ValCheck_Ciphertext_IsValidFor(self.handle, context.handle, &mut result)
+        })
+        .unwrap();
+
+        result
+    }
 }
 
 impl PartialEq for Ciphertext {
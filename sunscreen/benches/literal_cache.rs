@@ -0,0 +1,55 @@
+use std::time::Instant;
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Fractional, Cipher},
+    Compiler, RunContext, Runtime,
+};
+
+// Compares repeatedly running an FHE program with several plaintext
+// literals when the runtime's decoded literals are reused and when each
+// run decodes them afresh.
+
+#[fhe_program(scheme = "bfv")]
+fn affine(a: Cipher<Fractional<64>>) -> Cipher<Fractional<64>> {
+    let b = a * 1.5 + 2.25;
+    let c = b * -0.75 - 3.125;
+
+    c * 0.5 + 4.0
+}
+
+fn main() {
+    const RUNS: u32 = 50;
+
+    let app = Compiler::new().fhe_program(affine).compile().unwrap();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(affine).unwrap();
+
+    let a = runtime
+        .encrypt(Fractional::<64>::from(3.0), &public_key)
+        .unwrap();
+
+    let now = Instant::now();
+
+    for _ in 0..RUNS {
+        // A fresh context has an empty literal cache.
+        runtime
+            .run_with_context(program, vec![a.clone()], &public_key, &RunContext::new())
+            .unwrap();
+    }
+
+    let uncached = now.elapsed().as_secs_f64() / RUNS as f64;
+
+    let now = Instant::now();
+
+    for _ in 0..RUNS {
+        runtime.run(program, vec![a.clone()], &public_key).unwrap();
+    }
+
+    let cached = now.elapsed().as_secs_f64() / RUNS as f64;
+
+    println!("Decoding literals every run: {uncached}s");
+    println!("Reusing decoded literals:    {cached}s");
+}
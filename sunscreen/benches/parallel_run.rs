@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, RunContext, Runtime,
+};
+
+// Compares running a wide FHE program, whose branches are independent,
+// in parallel (the default) and one operation at a time.
+
+#[fhe_program(scheme = "bfv")]
+fn wide(
+    a: Cipher<Signed>,
+    b: Cipher<Signed>,
+    c: Cipher<Signed>,
+    d: Cipher<Signed>,
+) -> Cipher<Signed> {
+    let w = a * b;
+    let x = c * d;
+    let y = a * c;
+    let z = b * d;
+
+    (w + x) * (y + z)
+}
+
+fn main() {
+    const RUNS: u32 = 20;
+
+    let app = Compiler::new().fhe_program(wide).compile().unwrap();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(wide).unwrap();
+
+    let args = [3, -2, 5, 7]
+        .iter()
+        .map(|x| runtime.encrypt(Signed::from(*x), &public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let time = |run_context: &RunContext| {
+        let now = Instant::now();
+
+        for _ in 0..RUNS {
+            runtime
+                .run_with_context(program, args.clone(), &public_key, run_context)
+                .unwrap();
+        }
+
+        now.elapsed().as_secs_f64() / RUNS as f64
+    };
+
+    let parallel = time(&RunContext::new());
+    let sequential = time(&RunContext::new().sequential());
+
+    println!("Parallel run:   {parallel}s");
+    println!("Sequential run: {sequential}s");
+    println!("Speedup:        {:.2}x", sequential / parallel);
+}
@@ -0,0 +1,64 @@
+use std::time::Instant;
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, RunContext, Runtime,
+};
+
+// Compares repeatedly running an FHE program with and without reusing a
+// [`RunContext`] for intermediate ciphertexts.
+
+#[fhe_program(scheme = "bfv")]
+fn poly(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    let c = a * b + a;
+    let d = c * c - b;
+
+    -(d + a * 3)
+}
+
+fn main() {
+    const RUNS: u32 = 50;
+
+    let app = Compiler::new().fhe_program(poly).compile().unwrap();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(poly).unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(-2), &public_key).unwrap();
+
+    let now = Instant::now();
+
+    for _ in 0..RUNS {
+        runtime
+            .run(program, vec![a.clone(), b.clone()], &public_key)
+            .unwrap();
+    }
+
+    let unpooled = now.elapsed().as_secs_f64() / RUNS as f64;
+
+    let run_context = RunContext::new();
+    let now = Instant::now();
+
+    for _ in 0..RUNS {
+        runtime
+            .run_with_context(
+                program,
+                vec![a.clone(), b.clone()],
+                &public_key,
+                &run_context,
+            )
+            .unwrap();
+    }
+
+    let pooled = now.elapsed().as_secs_f64() / RUNS as f64;
+
+    println!("Unpooled run: {unpooled}s");
+    println!("Pooled run:   {pooled}s");
+    println!(
+        "Ciphertext buffers retained: {}",
+        run_context.pooled_ciphertexts()
+    );
+}
@@ -1,10 +1,16 @@
-use crate::fhe::{FheCompile, FheFrontendCompilation};
-use crate::params::{determine_params, PlainModulusConstraint};
+use crate::fhe::{
+    find_unused_inputs, simd_diagnostic, FheCompile, FheFrontendCompilation, PrebuiltFheProgram,
+};
+use crate::params::{
+    determine_params, fresh_noise_budget, BindingConstraint, CandidateOutcome, ParamsCandidate,
+    PlainModulusConstraint,
+};
 use crate::{
-    zkp, Application, CallSignature, Error, FheProgramMetadata, Params, RequiredKeys, Result,
-    SchemeType, SecurityLevel, ZkpProgramFn,
+    zkp, Application, CallSignature, Error, FheProgramMetadata, Params, RelinearizationStrategy,
+    RequiredKeys, Result, SchemeType, SecurityLevel, ZkpProgramFn,
 };
-use std::collections::{HashMap, HashSet};
+use log::warn;
+use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 use sunscreen_fhe_program::FheProgramTrait;
 use sunscreen_runtime::{marker, CompiledFheProgram, Fhe, FheZkp, Zkp};
@@ -28,7 +34,20 @@ pub trait FheProgramFn {
     /**
      * Compile the `#[fhe_program]`.
      */
-    fn build(&self, params: &Params) -> Result<FheFrontendCompilation>;
+    fn build(&self, params: &Params) -> Result<FheFrontendCompilation> {
+        self.build_with_max_nodes(params, None)
+    }
+
+    /**
+     * Compile the `#[fhe_program]`, failing with
+     * [`Error::FheProgramTooLarge`] as soon as its graph holds more than
+     * `max_nodes` nodes.
+     */
+    fn build_with_max_nodes(
+        &self,
+        params: &Params,
+        max_nodes: Option<usize>,
+    ) -> Result<FheFrontendCompilation>;
 
     /**
      * Get the scheme type.
@@ -52,6 +71,11 @@ struct FheCompilerData {
     plain_modulus_constraint: PlainModulusConstraint,
     security_level: SecurityLevel,
     noise_margin: u32,
+    max_nodes: Option<usize>,
+    max_inputs: Option<usize>,
+    lattice_dimension: Option<u64>,
+    relinearization_strategy: RelinearizationStrategy,
+    simd_diagnostics: bool,
 }
 
 impl Default for FheCompilerData {
@@ -64,6 +88,11 @@ impl Default for FheCompilerData {
             plain_modulus_constraint: PlainModulusConstraint::Raw(262_144),
             security_level: SecurityLevel::TC128,
             noise_margin: 20,
+            max_nodes: None,
+            max_inputs: None,
+            lattice_dimension: None,
+            relinearization_strategy: RelinearizationStrategy::default(),
+            simd_diagnostics: false,
         }
     }
 }
@@ -153,6 +182,102 @@ impl<B> CompilerData<B> {
 
 type BoxZkpFn<F> = Box<dyn ZkpProgramFn<F>>;
 
+#[derive(Debug, Clone, PartialEq)]
+/**
+ * Explains how [`FheCompiler::compile_with_report`] chose the
+ * application's [`Params`].
+ *
+ * # Remarks
+ * When parameters are given with
+ * [`with_params`](GenericCompiler::with_params), no search runs, so
+ * `candidates` is empty and the noise fields are `None`.
+ *
+ * This type's [`Display`](std::fmt::Display) implementation prints a
+ * human-readable summary.
+ */
+pub struct CompileReport {
+    /**
+     * The parameters every FHE program was compiled under.
+     */
+    pub params: Params,
+
+    /**
+     * The multiplicative depth of each FHE program, by name.
+     */
+    pub multiplicative_depth: BTreeMap<String, usize>,
+
+    /**
+     * Each lattice dimension the parameter search tried, in increasing
+     * order. The last is the one chosen.
+     */
+    pub candidates: Vec<ParamsCandidate>,
+
+    /**
+     * What kept the search from choosing a smaller lattice dimension, or
+     * `None` if it chose the first dimension it tried.
+     */
+    pub binding_constraint: Option<BindingConstraint>,
+
+    /**
+     * The smallest predicted noise budget, in bits, left in any output.
+     */
+    pub noise_budget: Option<f64>,
+
+    /**
+     * The predicted noise budget, in bits, the noisiest output consumes
+     * relative to a freshly encrypted ciphertext.
+     */
+    pub noise_budget_consumed: Option<f64>,
+}
+
+impl std::fmt::Display for CompileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Chose lattice_dimension={} plain_modulus={} security_level={:?}",
+            self.params.lattice_dimension, self.params.plain_modulus, self.params.security_level
+        )?;
+
+        for (name, depth) in &self.multiplicative_depth {
+            writeln!(f, "  {}: multiplicative depth {}", name, depth)?;
+        }
+
+        for candidate in &self.candidates {
+            write!(f, "  n={}: ", candidate.lattice_dimension)?;
+
+            match candidate.outcome {
+                CandidateOutcome::UnsatisfiablePlainModulus => {
+                    writeln!(f, "rejected, plaintext modulus constraint unsatisfiable")?
+                }
+                CandidateOutcome::CannotRun => writeln!(f, "rejected, programs can't run")?,
+                CandidateOutcome::BelowNoiseMargin { noise_budget } => writeln!(
+                    f,
+                    "rejected, {:.1} bits of noise budget is below the margin",
+                    noise_budget
+                )?,
+                CandidateOutcome::Selected { noise_budget } => {
+                    writeln!(f, "selected, {:.1} bits of noise budget", noise_budget)?
+                }
+            }
+        }
+
+        if let Some(consumed) = self.noise_budget_consumed {
+            writeln!(f, "  Programs consume {:.1} bits of noise budget", consumed)?;
+        }
+
+        match self.binding_constraint {
+            Some(BindingConstraint::NoiseMargin) => write!(f, "  Binding constraint: noise margin"),
+            Some(BindingConstraint::SecurityLevel) => {
+                write!(f, "  Binding constraint: security level")
+            }
+            Some(BindingConstraint::PlainModulus) => {
+                write!(f, "  Binding constraint: plaintext modulus")
+            }
+            None => write!(f, "  Binding constraint: none"),
+        }
+    }
+}
+
 /**
  * A frontend compiler for Sunscreen FHE programs.
  */
@@ -196,6 +321,28 @@ impl Compiler {
         }
     }
 
+    /**
+     * Add an FHE program given directly as frontend IR rather than as an
+     * `#[fhe_program]` function.
+     *
+     * # Remarks
+     * This lets a separate process (or a non-Rust tool) generate and
+     * serialize the IR, leaving only parameter selection and backend
+     * compilation to this process. `signature` describes the program's
+     * arguments and return values, as
+     * [`FheProgramFn::signature`] would for a macro-built program.
+     *
+     * See [`PrebuiltFheProgram`] for caveats around plaintext literals.
+     */
+    pub fn with_frontend(
+        self,
+        name: &str,
+        signature: CallSignature,
+        frontend: FheFrontendCompilation,
+    ) -> FheCompiler {
+        self.fhe_program(PrebuiltFheProgram::new(name, signature, frontend))
+    }
+
     /**
      * Sets the ZKP backend target.
      */
@@ -210,11 +357,11 @@ impl Compiler {
 }
 
 impl<T, B> GenericCompiler<T, B> {
-    fn compile_fhe(&self) -> Result<HashMap<String, CompiledFheProgram>> {
+    fn compile_fhe(&self) -> Result<(BTreeMap<String, CompiledFheProgram>, CompileReport)> {
         let fhe_data: &FheCompilerData = self.data.fhe_data();
 
         if fhe_data.fhe_program_fns.is_empty() {
-            return Ok(HashMap::new());
+            return Err(Error::NoPrograms);
         }
 
         // Check that all programs use the same scheme type.
@@ -233,7 +380,7 @@ impl<T, B> GenericCompiler<T, B> {
             .fhe_program_fns
             .iter()
             .map(|f| f.name().to_owned())
-            .collect::<HashSet<String>>()
+            .collect::<BTreeSet<String>>()
             .len()
             != fhe_data.fhe_program_fns.len()
         {
@@ -263,26 +410,98 @@ impl<T, B> GenericCompiler<T, B> {
             ));
         }
 
+        if let Some(max) = fhe_data.max_inputs {
+            let inputs = fhe_data
+                .fhe_program_fns
+                .iter()
+                .map(|p| p.signature().arguments.len())
+                .max()
+                .unwrap();
+
+            if inputs > max {
+                return Err(Error::TooManyInputs { inputs, max });
+            }
+        }
+
         let scheme = fhe_data.fhe_program_fns.first().unwrap().scheme_type();
 
-        let params = match &fhe_data.params_mode {
-            ParamsMode::Manual(p) => p.clone(),
-            ParamsMode::Search => determine_params(
-                &fhe_data.fhe_program_fns,
-                fhe_data.plain_modulus_constraint,
-                fhe_data.security_level,
-                fhe_data.noise_margin,
-                scheme,
-            )?,
+        let mut report = match &fhe_data.params_mode {
+            ParamsMode::Manual(p) => {
+                p.validate()?;
+
+                CompileReport {
+                    params: p.clone(),
+                    multiplicative_depth: BTreeMap::new(),
+                    candidates: vec![],
+                    binding_constraint: None,
+                    noise_budget: None,
+                    noise_budget_consumed: None,
+                }
+            }
+            ParamsMode::Search => {
+                let search = determine_params(
+                    &fhe_data.fhe_program_fns,
+                    fhe_data.plain_modulus_constraint,
+                    fhe_data.security_level,
+                    fhe_data.noise_margin,
+                    scheme,
+                    fhe_data.max_nodes,
+                    fhe_data.lattice_dimension,
+                    fhe_data.relinearization_strategy,
+                )?;
+
+                if let Some(advice) = search.advice {
+                    warn!("{}", advice);
+                }
+
+                let binding_constraint = search.binding_constraint();
+                let noise_budget_consumed = fresh_noise_budget(&search.params)
+                    .map(|fresh| f64::max(fresh - search.noise_budget, 0.));
+
+                CompileReport {
+                    params: search.params,
+                    multiplicative_depth: BTreeMap::new(),
+                    candidates: search.candidates,
+                    binding_constraint,
+                    noise_budget: Some(search.noise_budget),
+                    noise_budget_consumed,
+                }
+            }
         };
 
+        let params = report.params.clone();
+
         let fhe_programs = fhe_data
             .fhe_program_fns
             .iter()
             .map(|prog| {
-                let execution_graph = prog.build(&params);
+                let execution_graph = prog.build_with_max_nodes(&params, fhe_data.max_nodes)?;
+
+                report.multiplicative_depth.insert(
+                    prog.name().to_owned(),
+                    execution_graph.multiplicative_depth(),
+                );
+
+                if fhe_data.simd_diagnostics {
+                    if let Some(warning) = simd_diagnostic(prog.name(), &execution_graph) {
+                        warn!("{}", warning);
+                    }
+                }
+
+                let unused_inputs = find_unused_inputs(&execution_graph);
+
+                for i in &unused_inputs {
+                    warn!(
+                        "FHE program {} never uses input {}; its argument is still required but \
+                         can hold any value.",
+                        prog.name(),
+                        i
+                    );
+                }
+
                 let mut required_keys = vec![];
-                let fhe_program_fn = execution_graph?.compile();
+                let fhe_program_fn =
+                    execution_graph.compile(&params, fhe_data.relinearization_strategy)?;
 
                 if fhe_program_fn.requires_relin_keys() {
                     required_keys.push(RequiredKeys::Relin);
@@ -296,6 +515,7 @@ impl<T, B> GenericCompiler<T, B> {
                     params: params.clone(),
                     required_keys,
                     signature: prog.signature(),
+                    unused_inputs,
                 };
 
                 let compiled_program = CompiledFheProgram {
@@ -305,9 +525,9 @@ impl<T, B> GenericCompiler<T, B> {
 
                 Ok((prog.name().to_owned(), compiled_program))
             })
-            .collect::<Result<HashMap<_, _>>>()?;
+            .collect::<Result<BTreeMap<_, _>>>()?;
 
-        Ok(fhe_programs)
+        Ok((fhe_programs, report))
     }
 }
 
@@ -315,7 +535,7 @@ impl<T, B> GenericCompiler<T, BoxZkpFn<B>>
 where
     B: BackendField,
 {
-    fn compile_zkp(&self) -> Result<HashMap<String, CompiledZkpProgram>> {
+    fn compile_zkp(&self) -> Result<BTreeMap<String, CompiledZkpProgram>> {
         let zkp_data = self.data.zkp_data();
 
         let zkp_programs = zkp_data
@@ -327,13 +547,13 @@ where
 
                 Ok((prog.name().to_owned(), result))
             })
-            .collect::<Result<HashMap<_, _>>>()?;
+            .collect::<Result<BTreeMap<_, _>>>()?;
 
         Ok(zkp_programs)
     }
 
     fn compile_internal(self) -> Result<Application<T>> {
-        Application::new(HashMap::new(), self.compile_zkp()?)
+        Application::new(BTreeMap::new(), self.compile_zkp()?)
     }
 }
 
@@ -383,7 +603,20 @@ impl FheCompiler {
      * will return a [`Error::NameCollision`] error.
      */
     pub fn compile(self) -> Result<Application<Fhe>> {
-        Application::new(self.compile_fhe()?, HashMap::new())
+        self.compile_with_report().map(|(app, _)| app)
+    }
+
+    /**
+     * Like [`compile`](Self::compile), but also returns a
+     * [`CompileReport`] explaining the chosen [`Params`]: each candidate
+     * the parameter search tried and why it was rejected, the constraint
+     * that kept the search from a smaller lattice dimension, each
+     * program's multiplicative depth, and the predicted noise budget.
+     */
+    pub fn compile_with_report(self) -> Result<(Application<Fhe>, CompileReport)> {
+        let (fhe_programs, report) = self.compile_fhe()?;
+
+        Ok((Application::new(fhe_programs, BTreeMap::new())?, report))
     }
 }
 
@@ -538,6 +771,88 @@ where
         self.data.fhe_data_mut().noise_margin = noise_margin;
         self
     }
+
+    /**
+     * Fail compilation with [`Error::FheProgramTooLarge`] if any FHE
+     * program's frontend graph has more than `n` nodes.
+     *
+     * # Remarks
+     * Unrolled loops and other generated code can produce far larger
+     * graphs than intended with an innocent-looking bound. The frontend
+     * checks this cap as it adds each node, so compilation stops as soon
+     * as a program grows past it, before building the rest of its graph
+     * or running the backend. By default, there is no limit.
+     */
+    pub fn max_nodes(mut self, n: usize) -> Self {
+        self.data.fhe_data_mut().max_nodes = Some(n);
+        self
+    }
+
+    /**
+     * Fail compilation with [`Error::TooManyInputs`] if any FHE program
+     * declares more than `n` arguments.
+     *
+     * # Remarks
+     * Each argument is a separate ciphertext (or set of ciphertexts) to
+     * encrypt, transmit, and type check, so programs taking hundreds of
+     * scalars are slow to use. An array or [`Batched`](crate::types::bfv::Batched)
+     * argument carries the same data far more cheaply. By default, there
+     * is no limit.
+     */
+    pub fn max_inputs(mut self, n: usize) -> Self {
+        self.data.fhe_data_mut().max_inputs = Some(n);
+        self
+    }
+
+    /**
+     * Use lattice dimension `n` rather than searching for the smallest
+     * one that works. The remaining parameters are still chosen by the
+     * search, so compilation fails with [`Error::NoParametersFound`] if `n`
+     * can't meet the noise budget, or [`Error::UnsatisfiableConstraint`]
+     * if it can't meet the plaintext modulus constraint.
+     *
+     * # Remarks
+     * Useful for benchmarking a program across dimensions. `n` must be a
+     * power of two from 1024 through 32768, else compilation fails with
+     * [`Error::Unsupported`]. Has no effect with
+     * [`with_params`](Self::with_params).
+     */
+    pub fn lattice_dimension(mut self, n: u64) -> Self {
+        self.data.fhe_data_mut().lattice_dimension = Some(n);
+        self
+    }
+
+    /**
+     * Choose where the backend relinearizes ciphertext products. Defaults
+     * to [`RelinearizationStrategy::EveryMultiply`].
+     *
+     * # Remarks
+     * Parameter search runs with the same strategy, so the chosen
+     * parameters account for the noise and ciphertext growth it causes.
+     * [`RelinearizationStrategy::None`] can't run programs that rotate or
+     * swap rows of a product.
+     */
+    pub fn relinearization_strategy(mut self, strategy: RelinearizationStrategy) -> Self {
+        self.data.fhe_data_mut().relinearization_strategy = strategy;
+        self
+    }
+
+    /**
+     * Log a warning for each FHE program that repeats the same computation
+     * on many independent inputs.
+     *
+     * # Remarks
+     * Such programs (e.g. one squaring 8 separate [`Signed`](crate::types::bfv::Signed)
+     * arguments) can usually put those inputs in the lanes of a
+     * [`Batched`](crate::types::bfv::Batched) value and do the work once.
+     * This only advises; compilation output is unchanged. See
+     * [`find_parallel_subgraphs`](crate::fhe::find_parallel_subgraphs).
+     * Disabled by default.
+     */
+    pub fn simd_diagnostics(mut self, enabled: bool) -> Self {
+        self.data.fhe_data_mut().simd_diagnostics = enabled;
+        self
+    }
 }
 
 /**
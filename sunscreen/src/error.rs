@@ -13,10 +13,28 @@ pub enum Error {
     MissingPlainModulusConstraint,
 
     /**
-     * No parameters were found that satisfy the given FHE program.
+     * No parameters meeting the security level leave the requested noise
+     * margin after running the given FHE programs.
+     *
+     * # Remarks
+     * Lower the margin with
+     * [`additional_noise_budget`](crate::GenericCompiler::additional_noise_budget)
+     * if it's more than the application needs, and otherwise reduce the
+     * programs' multiplicative depth (e.g. by balancing chains of
+     * multiplications).
      */
-    #[error("Failed to find satisfying parameters")]
-    NoParams,
+    #[error("No parameters leave a {requested_margin} bit noise margin for an FHE program with multiplicative depth {depth}")]
+    NoParametersFound {
+        /**
+         * The largest multiplicative depth among the FHE programs.
+         */
+        depth: usize,
+
+        /**
+         * The noise margin, in bits, that couldn't be met.
+         */
+        requested_margin: u32,
+    },
 
     /**
      * Attempted to compile the given FHE program with the wrong scheme.
@@ -77,6 +95,46 @@ pub enum Error {
      */
     #[error("Unsupported: {0}")]
     Unsupported(Box<String>),
+
+    /**
+     * An [`FheProgramBuilder`](crate::FheProgramBuilder) produced an invalid program.
+     */
+    #[error("Invalid FHE program builder: {0}")]
+    BuilderError(Box<String>),
+
+    /**
+     * An FHE program's graph exceeded the limit set with
+     * [`max_nodes`](crate::GenericCompiler::max_nodes).
+     */
+    #[error("FHE program has {nodes} nodes, exceeding the maximum of {max}")]
+    FheProgramTooLarge {
+        /**
+         * The number of nodes in the program's graph.
+         */
+        nodes: usize,
+
+        /**
+         * The configured limit.
+         */
+        max: usize,
+    },
+
+    /**
+     * An FHE program declared more arguments than the limit set with
+     * [`max_inputs`](crate::GenericCompiler::max_inputs).
+     */
+    #[error("FHE program takes {inputs} arguments, exceeding the maximum of {max}; consider passing arrays or Batched values instead")]
+    TooManyInputs {
+        /**
+         * The number of arguments the program declares.
+         */
+        inputs: usize,
+
+        /**
+         * The configured limit.
+         */
+        max: usize,
+    },
 }
 
 const_assert!(std::mem::size_of::<Error>() <= 24);
@@ -88,6 +146,13 @@ impl Error {
     pub fn unsupported(msg: &str) -> Self {
         Self::Unsupported(Box::new(msg.to_owned()))
     }
+
+    /**
+     * Create an [`Error::BuilderError`]
+     */
+    pub fn builder_error(msg: &str) -> Self {
+        Self::BuilderError(Box::new(msg.to_owned()))
+    }
 }
 
 /**
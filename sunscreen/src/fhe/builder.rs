@@ -0,0 +1,307 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::rc::Rc;
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::{
+    fhe::{check_node_limit, FheContext, FheContextOps, FheData, FheFrontendCompilation},
+    types::{BfvType, Cipher, TypeName},
+    CallSignature, Error, FheProgramFn, Params, Result, SchemeType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Input,
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Neg(usize),
+}
+
+#[derive(Debug, Default)]
+struct Recipe {
+    steps: Vec<Step>,
+    outputs: Vec<usize>,
+}
+
+/**
+ * Constructs an FHE program without the [`fhe_program`](crate::fhe_program)
+ * macro.
+ *
+ * # Remarks
+ * This is useful when a program's shape isn't known until runtime (e.g.
+ * it's generated from a config file). Every input and output is a
+ * [`Cipher<T>`], and intermediate values are [`BuilderValue`] handles
+ * supporting `+`, `-`, `*`, and unary `-`.
+ *
+ * Only types that encode into a single ciphertext are supported.
+ *
+ * ```rust
+ * # use sunscreen::{types::bfv::Signed, Compiler, FheProgramBuilder};
+ * let mut builder = FheProgramBuilder::<Signed>::new("mad");
+ *
+ * let a = builder.input();
+ * let b = builder.input();
+ * let c = builder.input();
+ *
+ * builder.output(&(a * b + c));
+ *
+ * let mad = builder.build().unwrap();
+ *
+ * let app = Compiler::new().fhe_program(mad.clone()).compile().unwrap();
+ * assert!(app.get_fhe_program(mad).is_some());
+ * ```
+ */
+pub struct FheProgramBuilder<T> {
+    name: String,
+    recipe: Rc<RefCell<Recipe>>,
+    _phantom: PhantomData<T>,
+}
+
+/**
+ * A handle to a value in an [`FheProgramBuilder`].
+ */
+pub struct BuilderValue<T> {
+    id: usize,
+    recipe: Rc<RefCell<Recipe>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Clone for BuilderValue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            recipe: self.recipe.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> BuilderValue<T> {
+    fn push(&self, step: Step) -> Self {
+        let mut recipe = self.recipe.borrow_mut();
+        recipe.steps.push(step);
+
+        Self {
+            id: recipe.steps.len() - 1,
+            recipe: self.recipe.clone(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn binary(&self, rhs: &Self, step: fn(usize, usize) -> Step) -> Self {
+        assert!(
+            Rc::ptr_eq(&self.recipe, &rhs.recipe),
+            "Operands belong to different FheProgramBuilders."
+        );
+
+        self.push(step(self.id, rhs.id))
+    }
+}
+
+impl<T> Add for BuilderValue<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.binary(&rhs, Step::Add)
+    }
+}
+
+impl<T> Sub for BuilderValue<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.binary(&rhs, Step::Sub)
+    }
+}
+
+impl<T> Mul for BuilderValue<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.binary(&rhs, Step::Mul)
+    }
+}
+
+impl<T> Neg for BuilderValue<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.push(Step::Neg(self.id))
+    }
+}
+
+impl<T> FheProgramBuilder<T>
+where
+    T: BfvType + TypeName + 'static,
+{
+    /**
+     * Creates a builder for an FHE program with the given name.
+     */
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            recipe: Rc::new(RefCell::new(Recipe::default())),
+            _phantom: PhantomData,
+        }
+    }
+
+    /**
+     * Appends a [`Cipher<T>`] argument to the program and returns its handle.
+     */
+    pub fn input(&mut self) -> BuilderValue<T> {
+        let mut recipe = self.recipe.borrow_mut();
+        recipe.steps.push(Step::Input);
+
+        BuilderValue {
+            id: recipe.steps.len() - 1,
+            recipe: self.recipe.clone(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /**
+     * Appends `value` to the program's return values.
+     *
+     * # Panics
+     * Panics if `value` came from a different builder.
+     */
+    pub fn output(&mut self, value: &BuilderValue<T>) {
+        assert!(
+            Rc::ptr_eq(&self.recipe, &value.recipe),
+            "Output belongs to a different FheProgramBuilder."
+        );
+
+        self.recipe.borrow_mut().outputs.push(value.id);
+    }
+
+    /**
+     * Validates the program and returns it in a form you can pass to
+     * [`Compiler::fhe_program`](crate::GenericCompiler::fhe_program).
+     *
+     * # Remarks
+     * Returns [`Error::BuilderError`] if `T` spans more than one ciphertext
+     * or the program has no outputs.
+     */
+    pub fn build(self) -> Result<BuiltFheProgram<T>> {
+        if T::NUM_CIPHERTEXTS != 1 {
+            return Err(Error::builder_error(
+                "FheProgramBuilder only supports types encoded in a single ciphertext",
+            ));
+        }
+
+        let recipe = self.recipe.borrow();
+
+        if recipe.outputs.is_empty() {
+            return Err(Error::builder_error("FHE program has no outputs"));
+        }
+
+        Ok(BuiltFheProgram {
+            name: self.name.clone(),
+            steps: recipe.steps.clone(),
+            outputs: recipe.outputs.clone(),
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/**
+ * An FHE program produced by an [`FheProgramBuilder`].
+ */
+pub struct BuiltFheProgram<T> {
+    name: String,
+    steps: Vec<Step>,
+    outputs: Vec<usize>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Clone for BuiltFheProgram<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            steps: self.steps.clone(),
+            outputs: self.outputs.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> FheProgramFn for BuiltFheProgram<T>
+where
+    T: BfvType + TypeName,
+{
+    fn signature(&self) -> CallSignature {
+        let num_inputs = self.steps.iter().filter(|s| **s == Step::Input).count();
+
+        CallSignature {
+            arguments: vec![Cipher::<T>::type_name(); num_inputs],
+            returns: vec![Cipher::<T>::type_name(); self.outputs.len()],
+            num_ciphertexts: vec![T::NUM_CIPHERTEXTS; self.outputs.len()],
+        }
+    }
+
+    fn build_with_max_nodes(
+        &self,
+        params: &Params,
+        max_nodes: Option<usize>,
+    ) -> Result<FheFrontendCompilation> {
+        if SchemeType::Bfv != params.scheme_type {
+            return Err(Error::IncorrectScheme);
+        }
+
+        let mut context = FheContext::new(FheData::new(params.clone()).with_max_nodes(max_nodes));
+        let mut nodes: Vec<Option<NodeIndex>> = vec![None; self.steps.len()];
+
+        // Each input takes its argument position from the order it's added
+        // to the context's input list, so add them in step order.
+        for (i, step) in self.steps.iter().enumerate() {
+            if let Step::Input = step {
+                nodes[i] = Some(context.add_ciphertext_input());
+                check_node_limit(&context)?;
+            }
+        }
+
+        // Steps only ever reference earlier steps, so every operand exists.
+        let node = |nodes: &[Option<NodeIndex>], i: usize| nodes[i].unwrap();
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let n = match *step {
+                Step::Input => continue,
+                Step::Add(a, b) => context.add_addition(node(&nodes, a), node(&nodes, b)),
+                Step::Sub(a, b) => context.add_subtraction(node(&nodes, a), node(&nodes, b)),
+                Step::Mul(a, b) => context.add_multiplication(node(&nodes, a), node(&nodes, b)),
+                Step::Neg(a) => context.add_negate(node(&nodes, a)),
+            };
+
+            nodes[i] = Some(n);
+            check_node_limit(&context)?;
+        }
+
+        for o in &self.outputs {
+            context.add_output(node(&nodes, *o));
+            check_node_limit(&context)?;
+        }
+
+        Ok(context.graph)
+    }
+
+    fn scheme_type(&self) -> SchemeType {
+        SchemeType::Bfv
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn chain_count(&self) -> usize {
+        1
+    }
+}
+
+impl<T> AsRef<str> for BuiltFheProgram<T> {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
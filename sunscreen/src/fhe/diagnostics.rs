@@ -0,0 +1,276 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use sunscreen_compiler_common::{EdgeInfo, Operation as OperationTrait};
+
+use crate::fhe::{FheFrontendCompilation, FheOperation};
+
+/**
+ * The number of independent, identical subgraphs at which
+ * [`simd_diagnostics`](crate::GenericCompiler::simd_diagnostics) suggests
+ * batching.
+ */
+const SIMD_DIAGNOSTIC_THRESHOLD: usize = 4;
+
+/**
+ * Finds the largest set of structurally identical, independent
+ * subgraphs in `program` and returns their root nodes.
+ *
+ * # Remarks
+ * Two subgraphs are structurally identical when they apply the same
+ * operations in the same shape, regardless of which inputs they read,
+ * and independent when they share no inputs. A program that does the same
+ * work on many independent inputs (e.g. squaring 8 separate scalars) can
+ * usually do it once on a [`Batched`](crate::types::bfv::Batched) value
+ * instead, so a large result suggests switching to a batched type. See
+ * [`simd_diagnostics`](crate::GenericCompiler::simd_diagnostics).
+ *
+ * Returns an empty vector if no subgraph appears more than once.
+ */
+pub fn find_parallel_subgraphs(program: &FheFrontendCompilation) -> Vec<NodeIndex> {
+    let graph = &program.0;
+
+    // Frontend programs are DAGs, so this can't fail.
+    let order = toposort(graph, None).unwrap();
+
+    let mut shapes = vec![0u64; graph.node_bound()];
+    let mut inputs = vec![BTreeSet::new(); graph.node_bound()];
+
+    // Candidate roots grouped by shape, with groups ordered by when their
+    // shape first appears.
+    let mut groups: Vec<Vec<NodeIndex>> = vec![];
+    let mut group_of_shape = BTreeMap::new();
+
+    for id in order {
+        let operation = &graph[id].operation;
+
+        let mut operands = graph
+            .edges_directed(id, Direction::Incoming)
+            .map(|e| {
+                let position = match e.weight() {
+                    EdgeInfo::Left => 0,
+                    EdgeInfo::Right => 1,
+                    _ => 2,
+                };
+
+                (position, e.source())
+            })
+            .collect::<Vec<_>>();
+
+        operands.sort();
+
+        let mut operand_shapes = operands
+            .iter()
+            .map(|(_, o)| shapes[o.index()])
+            .collect::<Vec<_>>();
+
+        if operation.is_commutative() {
+            operand_shapes.sort_unstable();
+        }
+
+        let mut hasher = DefaultHasher::new();
+
+        match operation {
+            FheOperation::InputCiphertext(_) | FheOperation::InputPlaintext(_) => {
+                inputs[id.index()].insert(id);
+            }
+            _ => {
+                for (_, o) in &operands {
+                    let o_inputs = inputs[o.index()].clone();
+                    inputs[id.index()].extend(o_inputs);
+                }
+            }
+        }
+
+        // Inputs of the same kind have the same shape whichever argument
+        // they load.
+        match operation {
+            FheOperation::InputCiphertext(_) | FheOperation::InputPlaintext(_) => {
+                std::mem::discriminant(operation).hash(&mut hasher)
+            }
+            _ => operation.hash(&mut hasher),
+        }
+
+        operand_shapes.hash(&mut hasher);
+        shapes[id.index()] = hasher.finish();
+
+        // Inputs, literals, and outputs do no work themselves.
+        if matches!(
+            operation,
+            FheOperation::InputCiphertext(_)
+                | FheOperation::InputPlaintext(_)
+                | FheOperation::Literal(_)
+                | FheOperation::Output
+        ) {
+            continue;
+        }
+
+        let group = *group_of_shape.entry(shapes[id.index()]).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+
+        groups[group].push(id);
+    }
+
+    let mut best = vec![];
+
+    for group in groups {
+        // Greedily keep the subgraphs that don't share inputs with any
+        // already kept.
+        let mut used = BTreeSet::new();
+        let mut independent = vec![];
+
+        for id in group {
+            if inputs[id.index()].is_disjoint(&used) {
+                used.extend(inputs[id.index()].iter().copied());
+                independent.push(id);
+            }
+        }
+
+        // Prefer later shapes on ties; they're the larger subgraphs.
+        if independent.len() >= best.len() {
+            best = independent;
+        }
+    }
+
+    if best.len() < 2 {
+        return vec![];
+    }
+
+    best
+}
+
+/**
+ * Returns the warning [`simd_diagnostics`](crate::GenericCompiler::simd_diagnostics)
+ * logs for the FHE program `name`, or [`None`] if it doesn't repeat a
+ * computation often enough to suggest batching.
+ */
+pub(crate) fn simd_diagnostic(name: &str, program: &FheFrontendCompilation) -> Option<String> {
+    let parallel = find_parallel_subgraphs(program);
+
+    if parallel.len() < SIMD_DIAGNOSTIC_THRESHOLD {
+        return None;
+    }
+
+    Some(format!(
+        "FHE program {} repeats the same computation on {} independent sets of inputs. \
+         Passing those inputs as a Batched type would compute them all at once.",
+        name,
+        parallel.len()
+    ))
+}
+
+/**
+ * Returns the positions, among `program`'s inputs in argument order, of
+ * the inputs no output depends on.
+ *
+ * # Remarks
+ * Backend compilation removes the dead code such inputs feed, but the
+ * program's call signature still declares them, so callers encrypt and
+ * send data that's never read. Each argument contributes one input per
+ * ciphertext (or plaintext) it encodes to, so for programs taking only
+ * scalars these are argument indices.
+ */
+pub fn find_unused_inputs(program: &FheFrontendCompilation) -> Vec<usize> {
+    let graph = &program.0;
+
+    let mut used = BTreeSet::new();
+    let mut visit = graph
+        .node_indices()
+        .filter(|id| matches!(graph[*id].operation, FheOperation::Output))
+        .collect::<Vec<_>>();
+
+    while let Some(id) = visit.pop() {
+        if used.insert(id) {
+            visit.extend(graph.neighbors_directed(id, Direction::Incoming));
+        }
+    }
+
+    let mut unused = graph
+        .node_indices()
+        .filter(|id| !used.contains(id))
+        .filter_map(|id| match graph[id].operation {
+            FheOperation::InputCiphertext(i) | FheOperation::InputPlaintext(i) => Some(i),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    unused.sort_unstable();
+
+    unused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe::tests::make_context;
+    use crate::fhe::FheContextOps;
+
+    #[test]
+    fn finds_repeated_independent_ops() {
+        let mut ctx = make_context();
+
+        let inputs = (0..8)
+            .map(|_| ctx.add_ciphertext_input())
+            .collect::<Vec<_>>();
+
+        let mut squares = inputs
+            .iter()
+            .map(|x| {
+                let sq = ctx.add_multiplication(*x, *x);
+                ctx.add_output(sq);
+
+                sq
+            })
+            .collect::<Vec<_>>();
+
+        let mut found = find_parallel_subgraphs(&ctx.graph);
+
+        found.sort();
+        squares.sort();
+
+        assert_eq!(found, squares);
+
+        let warning = simd_diagnostic("squares", &ctx.graph).unwrap();
+
+        assert!(warning.starts_with(
+            "FHE program squares repeats the same computation on 8 independent sets of inputs."
+        ));
+    }
+
+    #[test]
+    fn ignores_dependent_ops() {
+        let mut ctx = make_context();
+
+        let mut x = ctx.add_ciphertext_input();
+
+        for _ in 0..4 {
+            x = ctx.add_negate(x);
+        }
+
+        ctx.add_output(x);
+
+        assert!(find_parallel_subgraphs(&ctx.graph).is_empty());
+        assert_eq!(simd_diagnostic("chain", &ctx.graph), None);
+    }
+
+    #[test]
+    fn finds_unused_inputs() {
+        let mut ctx = make_context();
+
+        let a = ctx.add_ciphertext_input();
+        let b = ctx.add_ciphertext_input();
+        let c = ctx.add_plaintext_input();
+
+        // b feeds a computation, but not one any output uses.
+        ctx.add_negate(b);
+
+        let x = ctx.add_multiplication_plaintext(a, c);
+        ctx.add_output(x);
+
+        assert_eq!(find_unused_inputs(&ctx.graph), vec![1]);
+    }
+}
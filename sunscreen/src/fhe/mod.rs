@@ -1,15 +1,34 @@
-use petgraph::stable_graph::NodeIndex;
+use petgraph::{
+    algo::toposort,
+    dot::{Config, Dot},
+    stable_graph::{NodeIndex, StableGraph},
+    visit::EdgeRef,
+    Direction,
+};
 use serde::{Deserialize, Serialize};
-use sunscreen_backend::compile_inplace;
+use sunscreen_backend::{compile_inplace, RelinearizationStrategy};
 use sunscreen_compiler_common::{
-    CompilationResult, Context, EdgeInfo, NodeInfo, Operation as OperationTrait,
+    transforms::common_subexpression_elimination, CompilationResult, Context, EdgeInfo, NodeInfo,
+    Operation as OperationTrait,
 };
 use sunscreen_fhe_program::{
     FheProgram, Literal as FheProgramLiteral, Operation as FheProgramOperation, SchemeType,
 };
 use sunscreen_runtime::{InnerPlaintext, Params};
 
+use crate::types::bfv::make_encoder;
+use crate::{Error, Result};
+
 use std::cell::RefCell;
+use std::collections::HashMap;
+
+mod builder;
+mod diagnostics;
+mod prebuilt;
+
+pub use builder::*;
+pub use diagnostics::*;
+pub use prebuilt::*;
 
 #[derive(Clone, Debug, Deserialize, Hash, Serialize, PartialEq, Eq)]
 /**
@@ -25,6 +44,12 @@ pub enum Literal {
      * An encoded plaintext value.
      */
     Plaintext(InnerPlaintext),
+
+    /**
+     * An unsigned 128-bit integer. [`FheContextOps::add_literal`] stores
+     * values that fit in 64 bits as [`Literal::U64`].
+     */
+    U128(u128),
 }
 
 #[derive(Clone, Debug, Hash, Deserialize, Serialize, PartialEq, Eq)]
@@ -33,14 +58,16 @@ pub enum Literal {
  */
 pub enum FheOperation {
     /**
-     * This node indicates loading a cipher text from an input.
+     * This node indicates loading a cipher text from the input at the
+     * given argument position.
      */
-    InputCiphertext,
+    InputCiphertext(usize),
 
     /**
-     * This node indicates loading a plaintext from an input.
+     * This node indicates loading a plaintext from the input at the
+     * given argument position.
      */
-    InputPlaintext,
+    InputPlaintext(usize),
 
     /**
      * Addition.
@@ -141,6 +168,111 @@ impl OperationTrait for FheOperation {
     }
 }
 
+/**
+ * An implementation detail of an FHE program. During compilation, it
+ * holds the scheme parameters the program is being built for.
+ */
+pub struct FheData {
+    /**
+     * The parameters the FHE program is being built for.
+     */
+    pub params: Params,
+
+    // A lookup table to reuse literal nodes. Reduces the size
+    // of the graph.
+    literal_map: HashMap<Literal, NodeIndex>,
+
+    // Estimated fractional bits of Fractional nodes. Nodes without an
+    // entry (e.g. inputs) use DEFAULT_FRACTIONAL_SCALE.
+    fractional_scales: HashMap<NodeIndex, usize>,
+
+    // Fractional nodes where a multiply chain first exceeded the
+    // available fractional bits.
+    fractional_overflows: Vec<NodeIndex>,
+
+    // The most nodes the graph may hold before building fails.
+    max_nodes: Option<usize>,
+
+    // The input nodes in argument order. Node indices don't give this
+    // order, as a removed node's slot can be reused by a later input.
+    inputs: Vec<NodeIndex>,
+}
+
+/**
+ * The estimated fractional bits in a [`Fractional`](crate::types::bfv::Fractional)
+ * value of unknown origin, i.e. the 53 bits of an [`f64`] mantissa.
+ */
+pub(crate) const DEFAULT_FRACTIONAL_SCALE: usize = 53;
+
+impl FheData {
+    /**
+     * Creates an [`FheData`] for building an FHE program under `params`.
+     */
+    pub fn new(params: Params) -> Self {
+        Self {
+            params,
+            literal_map: HashMap::new(),
+            fractional_scales: HashMap::new(),
+            fractional_overflows: vec![],
+            max_nodes: None,
+            inputs: vec![],
+        }
+    }
+
+    /**
+     * Fails building the FHE program with [`Error::FheProgramTooLarge`]
+     * as soon as its graph holds more than `max_nodes` nodes.
+     */
+    pub fn with_max_nodes(mut self, max_nodes: Option<usize>) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /**
+     * Returns the estimated number of fractional bits in the
+     * [`Fractional`](crate::types::bfv::Fractional) value at `id`.
+     */
+    pub(crate) fn fractional_scale(&self, id: NodeIndex) -> usize {
+        self.fractional_scales
+            .get(&id)
+            .copied()
+            .unwrap_or(DEFAULT_FRACTIONAL_SCALE)
+    }
+
+    /**
+     * Records `scale` as the estimated fractional bits at `id`, and
+     * `id` as an overflow if `scale` exceeds `available` but none of
+     * `operands` did. Returns whether `id` is a new overflow.
+     */
+    pub(crate) fn set_fractional_scale(
+        &mut self,
+        id: NodeIndex,
+        scale: usize,
+        operands: &[usize],
+        available: usize,
+    ) -> bool {
+        self.fractional_scales.insert(id, scale);
+
+        let overflows = scale > available && operands.iter().all(|s| *s <= available);
+
+        if overflows {
+            self.fractional_overflows.push(id);
+        }
+
+        overflows
+    }
+
+    /**
+     * Returns the [`Fractional`](crate::types::bfv::Fractional) nodes
+     * where a multiplication chain is first estimated to exceed the
+     * available fractional bits.
+     */
+    #[cfg(test)]
+    pub(crate) fn fractional_overflows(&self) -> &[NodeIndex] {
+        &self.fractional_overflows
+    }
+}
+
 /**
  * The context for constructing the [`fhe_program`](crate::fhe_program) graph during compilation.
  *
@@ -148,7 +280,7 @@ impl OperationTrait for FheOperation {
  * [`fhe_program`](crate::fhe_program) macro, and you shouldn't need
  * to construct one.
  */
-pub type FheContext = Context<FheOperation, Params>;
+pub type FheContext = Context<FheOperation, FheData>;
 
 /**
  *
@@ -163,9 +295,56 @@ thread_local! {
     pub static CURRENT_FHE_CTX: RefCell<Option<&'static mut FheContext>> = RefCell::new(None);
 }
 
+/**
+ * The panic payload [`with_fhe_ctx`] unwinds with when an FHE program's
+ * graph exceeds the limit set with [`FheData::with_max_nodes`]. The
+ * [`fhe_program`](crate::fhe_program) macro converts it into
+ * [`Error::FheProgramTooLarge`].
+ *
+ * An implementation detail of the [`fhe_program`](crate::fhe_program)
+ * macro.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeLimitExceeded {
+    /**
+     * The number of nodes in the graph.
+     */
+    pub nodes: usize,
+
+    /**
+     * The configured limit.
+     */
+    pub max: usize,
+}
+
+impl From<NodeLimitExceeded> for Error {
+    fn from(e: NodeLimitExceeded) -> Self {
+        Self::FheProgramTooLarge {
+            nodes: e.nodes,
+            max: e.max,
+        }
+    }
+}
+
+/**
+ * Fails if `ctx`'s graph holds more nodes than its limit allows.
+ */
+pub(crate) fn check_node_limit(ctx: &FheContext) -> std::result::Result<(), NodeLimitExceeded> {
+    let nodes = ctx.graph.node_count();
+
+    match ctx.data.max_nodes {
+        Some(max) if nodes > max => Err(NodeLimitExceeded { nodes, max }),
+        _ => Ok(()),
+    }
+}
+
 /**
  * Runs the specified closure, injecting the current
  * [`fhe_program`](crate::fhe_program) context.
+ *
+ * # Panics
+ * Unwinds with a [`NodeLimitExceeded`] payload if `f` grows the graph
+ * past its node limit.
  */
 pub fn with_fhe_ctx<F, R>(f: F) -> R
 where
@@ -177,7 +356,15 @@ where
             .as_mut()
             .expect("Called Ciphertext::new() outside of a context.");
 
-        f(ctx)
+        let result = f(ctx);
+
+        // resume_unwind skips the panic hook, so this doesn't print a
+        // panic message before the macro turns it into an error.
+        if let Err(e) = check_node_limit(ctx) {
+            std::panic::resume_unwind(Box::new(e));
+        }
+
+        result
     })
 }
 
@@ -263,11 +450,17 @@ pub trait FheContextOps {
 
 impl FheContextOps for FheContext {
     fn add_ciphertext_input(&mut self) -> NodeIndex {
-        self.add_node(FheOperation::InputCiphertext)
+        let id = self.add_node(FheOperation::InputCiphertext(self.data.inputs.len()));
+        self.data.inputs.push(id);
+
+        id
     }
 
     fn add_plaintext_input(&mut self) -> NodeIndex {
-        self.add_node(FheOperation::InputPlaintext)
+        let id = self.add_node(FheOperation::InputPlaintext(self.data.inputs.len()));
+        self.data.inputs.push(id);
+
+        id
     }
 
     fn add_plaintext_literal(&mut self, plaintext: InnerPlaintext) -> NodeIndex {
@@ -303,19 +496,24 @@ impl FheContextOps for FheContext {
     }
 
     fn add_literal(&mut self, literal: Literal) -> NodeIndex {
+        // Give each integer one representation so equal values share a
+        // node regardless of the variant they were given as.
+        let literal = match literal {
+            Literal::U128(x) => u64::try_from(x).map_or(literal, Literal::U64),
+            _ => literal,
+        };
+
         // See if we already have a node for the given literal. If so, just return it.
         // If not, make a new one.
-        let existing_literal =
-            self.graph
-                .node_indices()
-                .find(|&i| match &self.graph[i].operation {
-                    FheOperation::Literal(x) => *x == literal,
-                    _ => false,
-                });
+        let existing_literal = self.data.literal_map.get(&literal);
 
         match existing_literal {
-            Some(x) => x,
-            None => self.add_node(FheOperation::Literal(literal)),
+            Some(x) => *x,
+            None => {
+                let idx = self.add_node(FheOperation::Literal(literal.clone()));
+                self.data.literal_map.insert(literal, idx);
+                idx
+            }
         }
     }
 
@@ -342,31 +540,183 @@ impl FheContextOps for FheContext {
 pub trait FheCompile {
     /**
      * Performs frontend compilation of this intermediate representation into a backend [`FheProgram`],
-     * then perform backend compilation and return the result.
+     * then perform backend compilation for `params` and return the result. The backend
+     * places relinearizations according to `relinearization`.
+     *
+     * # Remarks
+     * Fails with [`Error::Unsupported`] if the program uses an operation
+     * `params`' scheme can't execute (e.g. [`FheOperation::SwapRows`], which
+     * acts on BFV's batching rows), rather than emitting a program that
+     * silently computes the wrong thing.
+     */
+    fn compile(
+        &self,
+        params: &Params,
+        relinearization: RelinearizationStrategy,
+    ) -> Result<FheProgram>;
+
+    /**
+     * Returns the largest number of ciphertext multiplications along any
+     * path through the program.
+     *
+     * # Remarks
+     * Only [`FheOperation::Multiply`] increments the depth; inputs and
+     * literals have depth 0 and every other operation passes its deepest
+     * operand's depth through. Each level of depth roughly costs one
+     * `coeff_modulus` prime, so this lets you estimate parameters before
+     * compiling.
+     */
+    fn multiplicative_depth(&self) -> usize;
+
+    /**
+     * Returns how many inputs the program takes.
+     *
+     * # Remarks
+     * Each argument contributes one input per ciphertext (or plaintext)
+     * it encodes to, so this is the length of the argument list the
+     * backend program expects.
+     */
+    fn input_count(&self) -> usize;
+
+    /**
+     * Renders the program in Graphviz DOT format, e.g. to pipe into
+     * `dot -Tpng` and check how an [`fhe_program`](crate::fhe_program)
+     * was lowered.
+     *
+     * # Remarks
+     * Nodes are labeled with their [`FheOperation`], with inputs also
+     * showing their argument index, and edges with their operand
+     * position. Plaintext literals omit their (large) encoded data.
+     */
+    fn to_dot(&self) -> String;
+
+    /**
+     * Returns how many bits the plaintext modulus needs so no output
+     * wraps when every input's magnitude is at most `input_bound`.
+     *
+     * # Remarks
+     * Propagates magnitude bounds from the inputs and literals: additions
+     * and subtractions add their operands' bounds, multiplications
+     * multiply them, and other operations pass their operand's bound
+     * through. Plaintext arithmetic is modulo the plaintext modulus, so
+     * only the outputs need to fit; a modulus of the returned width holds
+     * every value in `-bound..=bound`.
+     *
+     * This treats each value as a single integer, which matches scalar
+     * encodings (e.g. [`Unsigned`](crate::types::bfv::Unsigned)'s
+     * constant coefficient) and [`Batched`](crate::types::bfv::Batched)
+     * lanes. A plaintext literal's bound is the smaller of its largest
+     * coefficient and, when its parameters support batching, its largest
+     * slot: each encoding scrambles the other view's values across the
+     * whole plaintext modulus. Digit-wise encodings such as
+     * [`Signed`](crate::types::bfv::Signed) grow more slowly, so the
+     * result is conservative for them.
      */
-    fn compile(&self) -> FheProgram;
+    fn min_plain_modulus_bits(&self, input_bound: u64) -> u32;
+
+    /**
+     * Returns a program that runs this program, then feeds its outputs
+     * (in order) to `next` as `next`'s ciphertext arguments.
+     *
+     * # Remarks
+     * The two graphs are stitched together as-is: this program's outputs
+     * are replaced by edges into `next`'s operations, so the result takes
+     * this program's arguments and returns `next`'s outputs. Frontend IR
+     * is untyped, so the caller must ensure each output's type matches the
+     * argument it feeds, and describe the result with a
+     * [`CallSignature`](crate::CallSignature) when wrapping it in a
+     * [`PrebuiltFheProgram`](crate::PrebuiltFheProgram).
+     *
+     * Fails with [`Error::Unsupported`] if `next` takes plaintext
+     * arguments or its argument count differs from this program's output
+     * count.
+     */
+    fn compose(&self, next: &Self) -> Result<FheFrontendCompilation>;
+
+    /**
+     * Like [`compose`](Self::compose), but then runs common subexpression
+     * elimination over the combined graph so work `next` repeats from
+     * this program is computed once.
+     *
+     * # Remarks
+     * Backend compilation already simplifies, deduplicates literals, and
+     * removes dead code across the whole graph; this adds the sharing of
+     * identical operations that would otherwise survive across the
+     * boundary.
+     */
+    fn compose_inlined(&self, next: &Self) -> Result<FheFrontendCompilation>;
+}
+
+/**
+ * Whether `scheme_type` can execute `operation`.
+ */
+fn scheme_supports(scheme_type: SchemeType, operation: &FheOperation) -> bool {
+    match scheme_type {
+        // Rotations and row swaps are only meaningful for BFV's batching
+        // layout, so any new scheme should opt into operations one by one.
+        SchemeType::Bfv => matches!(
+            operation,
+            FheOperation::Add
+                | FheOperation::AddPlaintext
+                | FheOperation::InputCiphertext(_)
+                | FheOperation::InputPlaintext(_)
+                | FheOperation::Literal(_)
+                | FheOperation::Sub
+                | FheOperation::SubPlaintext
+                | FheOperation::Negate
+                | FheOperation::Multiply
+                | FheOperation::MultiplyPlaintext
+                | FheOperation::RotateLeft
+                | FheOperation::RotateRight
+                | FheOperation::SwapRows
+                | FheOperation::Output
+        ),
+    }
+}
+
+fn is_input(operation: &FheOperation) -> bool {
+    matches!(
+        operation,
+        FheOperation::InputCiphertext(_) | FheOperation::InputPlaintext(_)
+    )
 }
 
 impl FheCompile for FheFrontendCompilation {
-    fn compile(&self) -> FheProgram {
-        let mut fhe_program = FheProgram::new(SchemeType::Bfv);
+    fn compile(
+        &self,
+        params: &Params,
+        relinearization: RelinearizationStrategy,
+    ) -> Result<FheProgram> {
+        let scheme_type = params.scheme_type;
+
+        if let Some(n) = self
+            .0
+            .node_weights()
+            .find(|n| !scheme_supports(scheme_type, &n.operation))
+        {
+            return Err(Error::unsupported(&format!(
+                "{:?} is not supported by the {:?} scheme",
+                n.operation, scheme_type
+            )));
+        }
+
+        let mut fhe_program = FheProgram::new(scheme_type);
 
         let mapped_graph = self.0.map(
-            |id, n| match &n.operation {
+            |_, n| match &n.operation {
                 FheOperation::Add => NodeInfo::new(FheProgramOperation::Add),
-                FheOperation::InputCiphertext => {
-                    // HACKHACK: Input nodes are always added first to the graph in the order
-                    // they're specified as function arguments. We should not depend on this.
-                    NodeInfo::new(FheProgramOperation::InputCiphertext(id.index()))
+                FheOperation::InputCiphertext(i) => {
+                    NodeInfo::new(FheProgramOperation::InputCiphertext(*i))
                 }
-                FheOperation::InputPlaintext => {
-                    // HACKHACK: Input nodes are always added first to the graph in the order
-                    // they're specified as function arguments. We should not depend on this.
-                    NodeInfo::new(FheProgramOperation::InputPlaintext(id.index()))
+                FheOperation::InputPlaintext(i) => {
+                    NodeInfo::new(FheProgramOperation::InputPlaintext(*i))
                 }
                 FheOperation::Literal(Literal::U64(x)) => {
                     NodeInfo::new(FheProgramOperation::Literal(FheProgramLiteral::U64(*x)))
                 }
+                FheOperation::Literal(Literal::U128(x)) => {
+                    NodeInfo::new(FheProgramOperation::Literal(FheProgramLiteral::U128(*x)))
+                }
                 FheOperation::Literal(Literal::Plaintext(x)) => {
                     // It's okay to unwrap here because fhe_program compilation will
                     // catch the panic and return a compilation error.
@@ -398,6 +748,605 @@ impl FheCompile for FheFrontendCompilation {
 
         fhe_program.graph = CompilationResult(mapped_graph);
 
-        compile_inplace(fhe_program)
+        Ok(compile_inplace(fhe_program, params, relinearization))
+    }
+
+    fn input_count(&self) -> usize {
+        self.0
+            .node_weights()
+            .filter(|n| is_input(&n.operation))
+            .count()
+    }
+
+    fn multiplicative_depth(&self) -> usize {
+        // Frontend programs are DAGs, so this can't fail.
+        let order = toposort(&self.0, None).unwrap();
+        let mut depth = vec![0; self.0.node_bound()];
+
+        for id in order {
+            let operand_depth = self
+                .0
+                .neighbors_directed(id, Direction::Incoming)
+                .map(|o| depth[o.index()])
+                .max()
+                .unwrap_or(0);
+
+            depth[id.index()] = match self.0[id].operation {
+                FheOperation::Multiply => operand_depth + 1,
+                _ => operand_depth,
+            };
+        }
+
+        depth.into_iter().max().unwrap_or(0)
+    }
+
+    fn to_dot(&self) -> String {
+        let dot = Dot::with_attr_getters(
+            &self.0,
+            &[Config::NodeNoLabel, Config::EdgeNoLabel],
+            &|_, e| format!("label=\"{:?}\"", e.weight()),
+            &|_, (_, n)| {
+                let label = match &n.operation {
+                    FheOperation::Literal(Literal::Plaintext(_)) => "Literal(Plaintext)".to_owned(),
+                    op => format!("{:?}", op),
+                };
+
+                format!("label=\"{}\"", label)
+            },
+        );
+
+        format!("{:?}", dot)
+    }
+
+    fn min_plain_modulus_bits(&self, input_bound: u64) -> u32 {
+        // Frontend programs are DAGs, so this can't fail.
+        let order = toposort(&self.0, None).unwrap();
+        let mut bounds = vec![0u128; self.0.node_bound()];
+        let mut output_bound = 0u128;
+        let mut encoders = HashMap::new();
+
+        for id in order {
+            let operands = self
+                .0
+                .neighbors_directed(id, Direction::Incoming)
+                .map(|o| bounds[o.index()]);
+
+            bounds[id.index()] = match &self.0[id].operation {
+                FheOperation::InputCiphertext(_) | FheOperation::InputPlaintext(_) => {
+                    input_bound as u128
+                }
+                FheOperation::Literal(Literal::Plaintext(InnerPlaintext::Seal(p))) => p
+                    .iter()
+                    .map(|p| {
+                        let modulus = p.params.plain_modulus;
+                        let magnitude = |c: u64| u64::min(c, modulus - c) as u128;
+
+                        let coefficients = (0..p.data.len())
+                            .map(|i| magnitude(p.data.get_coefficient(i)))
+                            .max()
+                            .unwrap_or(0);
+
+                        let slots = encoders
+                            .entry(p.params.clone())
+                            .or_insert_with(|| make_encoder(&p.params).ok())
+                            .as_ref()
+                            .and_then(|e| e.decode_unsigned(&p.data).ok())
+                            .and_then(|s| s.into_iter().map(magnitude).max());
+
+                        slots.map_or(coefficients, |s| s.min(coefficients))
+                    })
+                    .max()
+                    .unwrap_or(0),
+                // Only used as rotation amounts.
+                FheOperation::Literal(Literal::U64(_) | Literal::U128(_)) => 0,
+                FheOperation::Add
+                | FheOperation::AddPlaintext
+                | FheOperation::Sub
+                | FheOperation::SubPlaintext => {
+                    operands.fold(0u128, |acc, b| acc.saturating_add(b))
+                }
+                FheOperation::Multiply | FheOperation::MultiplyPlaintext => {
+                    operands.fold(1u128, |acc, b| acc.saturating_mul(b))
+                }
+                FheOperation::Output => {
+                    let bound = operands.max().unwrap_or(0);
+                    output_bound = output_bound.max(bound);
+
+                    bound
+                }
+                FheOperation::Negate
+                | FheOperation::RotateLeft
+                | FheOperation::RotateRight
+                | FheOperation::SwapRows => operands.max().unwrap_or(0),
+            };
+        }
+
+        // Holding -bound..=bound takes 2 * bound + 1 residues.
+        u128::BITS - output_bound.saturating_mul(2).leading_zeros()
+    }
+
+    fn compose(&self, next: &Self) -> Result<FheFrontendCompilation> {
+        let is_output = |op: &FheOperation| matches!(op, FheOperation::Output);
+
+        let mut inputs = vec![];
+
+        for id in next.0.node_indices() {
+            match next.0[id].operation {
+                FheOperation::InputCiphertext(i) => inputs.push((i, id)),
+                FheOperation::InputPlaintext(_) => {
+                    return Err(Error::unsupported(
+                        "Can't compose into a program taking plaintext arguments",
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        inputs.sort();
+
+        let outputs = self
+            .0
+            .node_indices()
+            .filter(|id| is_output(&self.0[*id].operation))
+            .collect::<Vec<_>>();
+
+        if inputs.len() != outputs.len() {
+            return Err(Error::unsupported(&format!(
+                "Can't feed {} outputs into a program taking {} arguments",
+                outputs.len(),
+                inputs.len()
+            )));
+        }
+
+        // Build into a fresh graph rather than editing a clone of this one.
+        // StableGraph reuses the slots of removed nodes last-freed first, so
+        // next's nodes would fill this program's output slots in reverse,
+        // and runtime output numbering follows node index order.
+        let mut graph = StableGraph::new();
+        let mut mapping = HashMap::new();
+
+        for id in self.0.node_indices() {
+            if !is_output(&self.0[id].operation) {
+                mapping.insert(id, graph.add_node(self.0[id].clone()));
+            }
+        }
+
+        for e in self.0.edge_references() {
+            if !is_output(&self.0[e.target()].operation) {
+                graph.add_edge(mapping[&e.source()], mapping[&e.target()], *e.weight());
+            }
+        }
+
+        // Every output node has exactly 1 operand.
+        let results = outputs
+            .iter()
+            .map(|id| {
+                let result = self
+                    .0
+                    .neighbors_directed(*id, Direction::Incoming)
+                    .next()
+                    .unwrap();
+
+                mapping[&result]
+            })
+            .collect::<Vec<_>>();
+
+        let mut next_mapping = inputs
+            .into_iter()
+            .map(|(_, id)| id)
+            .zip(results)
+            .collect::<HashMap<_, _>>();
+
+        for id in next.0.node_indices() {
+            if !next_mapping.contains_key(&id) {
+                let new_id = graph.add_node(next.0[id].clone());
+                next_mapping.insert(id, new_id);
+            }
+        }
+
+        for e in next.0.edge_references() {
+            graph.add_edge(
+                next_mapping[&e.source()],
+                next_mapping[&e.target()],
+                *e.weight(),
+            );
+        }
+
+        Ok(CompilationResult(graph))
+    }
+
+    fn compose_inlined(&self, next: &Self) -> Result<FheFrontendCompilation> {
+        let mut composed = self.compose(next)?;
+
+        common_subexpression_elimination(&mut composed.0);
+
+        Ok(composed)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::types::bfv::Batched;
+    use crate::SecurityLevel;
+    use seal_fhe::{CoefficientModulus, PlainModulus};
+    use sunscreen_compiler_common::GraphQuery;
+    use sunscreen_fhe_program::FheProgramTrait;
+    use sunscreen_runtime::TryIntoPlaintext;
+
+    pub(crate) fn make_context() -> FheContext {
+        FheContext::new(FheData::new(Params {
+            lattice_dimension: 1024,
+            plain_modulus: 1024,
+            coeff_modulus: vec![1, 2, 3, 4],
+            security_level: SecurityLevel::TC128,
+            scheme_type: SchemeType::Bfv,
+        }))
+    }
+
+    #[test]
+    fn multiply_chain_depth() {
+        let mut ctx = make_context();
+
+        let a = ctx.add_ciphertext_input();
+        let b = ctx.add_ciphertext_input();
+
+        let mut x = ctx.add_multiplication(a, b);
+        x = ctx.add_addition(x, a);
+        x = ctx.add_multiplication(x, b);
+        x = ctx.add_negate(x);
+        x = ctx.add_multiplication(x, a);
+        ctx.add_output(x);
+
+        assert_eq!(ctx.graph.multiplicative_depth(), 3);
+    }
+
+    #[test]
+    fn multiply_tree_depth() {
+        let mut ctx = make_context();
+
+        let mut layer = (0..8)
+            .map(|_| ctx.add_ciphertext_input())
+            .collect::<Vec<_>>();
+
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|p| ctx.add_multiplication(p[0], p[1]))
+                .collect();
+        }
+
+        ctx.add_output(layer[0]);
+
+        // 7 multiplications, but only log2(8) of them on any one path.
+        assert_eq!(ctx.graph.multiplicative_depth(), 3);
+    }
+
+    #[test]
+    fn dot_labels_operations_inputs_and_operands() {
+        let mut ctx = make_context();
+
+        let a = ctx.add_ciphertext_input();
+        let b = ctx.add_ciphertext_input();
+        let five = ctx.add_literal(Literal::U64(5));
+        let x = ctx.add_multiplication(a, b);
+        let y = ctx.add_rotate_left(x, five);
+        ctx.add_output(y);
+
+        let dot = ctx.graph.to_dot();
+
+        assert!(dot.starts_with("digraph"));
+
+        for label in [
+            "InputCiphertext(0)",
+            "InputCiphertext(1)",
+            "Literal(U64(5))",
+            "Multiply",
+            "RotateLeft",
+            "Output",
+            "Left",
+            "Right",
+            "Unary",
+        ] {
+            assert!(
+                dot.contains(&format!("label=\"{}\"", label)),
+                "missing {}",
+                label
+            );
+        }
+    }
+
+    #[test]
+    fn inputs_map_to_argument_positions_not_node_indices() {
+        let mut ctx = make_context();
+
+        // The literal takes node 0, so no input's node index is its
+        // argument position.
+        let three = ctx.add_literal(Literal::U64(3));
+        let a = ctx.add_ciphertext_input();
+        let b = ctx.add_plaintext_input();
+        let c = ctx.add_ciphertext_input();
+
+        let x = ctx.add_multiplication_plaintext(a, b);
+        let x = ctx.add_rotate_left(x, three);
+        let x = ctx.add_addition(x, c);
+        ctx.add_output(x);
+
+        assert_eq!(ctx.graph.input_count(), 3);
+
+        let program = ctx
+            .graph
+            .compile(&ctx.data.params, RelinearizationStrategy::EveryMultiply)
+            .unwrap();
+
+        let mut inputs = program
+            .graph
+            .node_weights()
+            .filter_map(|n| match n.operation {
+                FheProgramOperation::InputCiphertext(i) => Some((i, true)),
+                FheProgramOperation::InputPlaintext(i) => Some((i, false)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        inputs.sort();
+
+        assert_eq!(inputs, vec![(0, true), (1, false), (2, true)]);
+    }
+
+    #[test]
+    fn inputs_keep_argument_order_when_slots_are_reused() {
+        let mut ctx = make_context();
+
+        let scratch = ctx.add_literal(Literal::U64(1));
+        let a = ctx.add_ciphertext_input();
+
+        // b reuses the removed literal's slot, so its node index is lower
+        // than a's even though it's the later argument.
+        ctx.graph.remove_node(scratch);
+        let b = ctx.add_ciphertext_input();
+
+        assert!(b.index() < a.index());
+        assert_eq!(ctx.data.inputs, vec![a, b]);
+
+        let x = ctx.add_subtraction(a, b);
+        ctx.add_output(x);
+
+        let program = ctx
+            .graph
+            .compile(&ctx.data.params, RelinearizationStrategy::EveryMultiply)
+            .unwrap();
+
+        let query = GraphQuery::new(&program.graph.0);
+
+        let sub = program
+            .graph
+            .node_indices()
+            .find(|id| program.graph[*id].operation == FheProgramOperation::Sub)
+            .unwrap();
+
+        let (left, right) = query.get_binary_operands(sub).unwrap();
+
+        assert_eq!(
+            program.graph[left].operation,
+            FheProgramOperation::InputCiphertext(0)
+        );
+        assert_eq!(
+            program.graph[right].operation,
+            FheProgramOperation::InputCiphertext(1)
+        );
+    }
+
+    #[test]
+    fn compile_targets_requested_scheme() {
+        let mut ctx = make_context();
+
+        let a = ctx.add_ciphertext_input();
+        let x = ctx.add_swap_rows(a);
+        ctx.add_output(x);
+
+        let program = ctx
+            .graph
+            .compile(&ctx.data.params, RelinearizationStrategy::EveryMultiply)
+            .unwrap();
+
+        assert_eq!(program.data, SchemeType::Bfv);
+        assert!(program.requires_galois_keys());
+    }
+
+    #[test]
+    fn inlined_composition_shares_work_across_boundary() {
+        // first(a, b) = (a, b, a * b)
+        let mut first = make_context();
+
+        let a = first.add_ciphertext_input();
+        let b = first.add_ciphertext_input();
+        let ab = first.add_multiplication(a, b);
+        first.add_output(a);
+        first.add_output(b);
+        first.add_output(ab);
+
+        // second(x, y, z) = x * y + z
+        let mut second = make_context();
+
+        let x = second.add_ciphertext_input();
+        let y = second.add_ciphertext_input();
+        let z = second.add_ciphertext_input();
+        let xy = second.add_multiplication(x, y);
+        let sum = second.add_addition(xy, z);
+        second.add_output(sum);
+
+        let naive = first.graph.compose(&second.graph).unwrap();
+        let inlined = first.graph.compose_inlined(&second.graph).unwrap();
+
+        // a, b, a * b, a * b again, the sum, and the output.
+        assert_eq!(naive.node_count(), 6);
+        assert_eq!(inlined.node_count(), 5);
+        assert_eq!(inlined.multiplicative_depth(), 1);
+
+        let bad = make_context();
+
+        assert!(matches!(
+            first.graph.compose(&bad.graph),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn composition_keeps_output_order() {
+        // first(a, b) = (a + b, a * b)
+        let mut first = make_context();
+
+        let a = first.add_ciphertext_input();
+        let b = first.add_ciphertext_input();
+        let sum = first.add_addition(a, b);
+        let product = first.add_multiplication(a, b);
+        first.add_output(sum);
+        first.add_output(product);
+
+        // second(x, y) = (x - y, -y)
+        let mut second = make_context();
+
+        let x = second.add_ciphertext_input();
+        let y = second.add_ciphertext_input();
+        let difference = second.add_subtraction(x, y);
+        let negation = second.add_negate(y);
+        second.add_output(difference);
+        second.add_output(negation);
+
+        let composed = first.graph.compose(&second.graph).unwrap();
+
+        let operand = |id: NodeIndex| {
+            let operand = composed
+                .neighbors_directed(id, Direction::Incoming)
+                .next()
+                .unwrap();
+
+            composed[operand].operation.clone()
+        };
+
+        // Outputs are numbered in node index order.
+        let outputs = composed
+            .node_indices()
+            .filter(|id| matches!(composed[*id].operation, FheOperation::Output))
+            .map(operand)
+            .collect::<Vec<_>>();
+
+        assert_eq!(outputs, vec![FheOperation::Sub, FheOperation::Negate]);
+    }
+
+    #[test]
+    fn min_plain_modulus_bits_of_mad() {
+        let mut ctx = make_context();
+
+        let a = ctx.add_ciphertext_input();
+        let b = ctx.add_ciphertext_input();
+        let c = ctx.add_ciphertext_input();
+
+        let x = ctx.add_multiplication(a, b);
+        let x = ctx.add_addition(x, c);
+        ctx.add_output(x);
+
+        // |a * b + c| <= 100 * 100 + 100 = 10100, and 2 * 10100 + 1 needs
+        // 15 bits.
+        assert_eq!(ctx.graph.min_plain_modulus_bits(100), 15);
+
+        // Squaring the result needs about twice as many.
+        let y = ctx.add_multiplication(x, x);
+        ctx.add_output(y);
+
+        assert_eq!(ctx.graph.min_plain_modulus_bits(100), 28);
+    }
+
+    #[test]
+    fn min_plain_modulus_bits_reads_batched_literals_from_slots() {
+        let degree = 4096;
+
+        let params = Params {
+            lattice_dimension: degree,
+            plain_modulus: PlainModulus::batching(degree, 20).unwrap().value(),
+            coeff_modulus: CoefficientModulus::bfv_default(degree, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|m| m.value())
+                .collect(),
+            security_level: SecurityLevel::TC128,
+            scheme_type: SchemeType::Bfv,
+        };
+
+        let mut ctx = FheContext::new(FheData::new(params.clone()));
+
+        let row = (0..2048).map(|i| i % 9 - 4).collect::<Vec<i64>>();
+        let literal = Batched::<2048>::try_from([row.clone(), row])
+            .unwrap()
+            .try_into_plaintext(&params)
+            .unwrap();
+
+        let a = ctx.add_ciphertext_input();
+        let b = ctx.add_plaintext_literal(literal.inner);
+        let c = ctx.add_ciphertext_input();
+
+        let x = ctx.add_multiplication_plaintext(a, b);
+        let x = ctx.add_addition(x, c);
+        ctx.add_output(x);
+
+        // |a * b + c| <= 100 * 4 + 100 = 500, and 2 * 500 + 1 needs 10
+        // bits.
+        assert_eq!(ctx.graph.min_plain_modulus_bits(100), 10);
+    }
+
+    #[test]
+    fn reuses_literal_nodes() {
+        let mut ctx = make_context();
+
+        let a = ctx.add_ciphertext_input();
+        let mut x = a;
+
+        for _ in 0..10_000 {
+            let l = ctx.add_literal(Literal::U64(3));
+            x = ctx.add_rotate_left(x, l);
+        }
+
+        let other = ctx.add_literal(Literal::U64(4));
+        x = ctx.add_rotate_left(x, other);
+        ctx.add_output(x);
+
+        let literals = ctx
+            .graph
+            .node_weights()
+            .filter(|n| matches!(n.operation, FheOperation::Literal(_)))
+            .count();
+
+        assert_eq!(literals, 2);
+        assert_eq!(ctx.add_literal(Literal::U64(4)), other);
+    }
+
+    #[test]
+    fn wide_literals_reach_backend_intact() {
+        let mut ctx = make_context();
+
+        let wide = u64::MAX as u128 + 1;
+
+        let a = ctx.add_ciphertext_input();
+        let l = ctx.add_literal(Literal::U128(wide));
+        let x = ctx.add_rotate_left(a, l);
+        ctx.add_output(x);
+
+        // Values that fit in 64 bits share a node whichever variant they
+        // were given as.
+        let narrow = ctx.add_literal(Literal::U64(7));
+
+        assert_eq!(ctx.add_literal(Literal::U128(7)), narrow);
+        assert_eq!(ctx.add_literal(Literal::U128(wide)), l);
+
+        let program = ctx
+            .graph
+            .compile(&ctx.data.params, RelinearizationStrategy::EveryMultiply)
+            .unwrap();
+
+        assert!(program
+            .graph
+            .node_weights()
+            .any(|n| n.operation == FheProgramOperation::Literal(FheProgramLiteral::U128(wide))));
     }
 }
@@ -0,0 +1,81 @@
+use crate::{
+    fhe::FheFrontendCompilation, CallSignature, Error, FheProgramFn, Params, Result, SchemeType,
+};
+
+/**
+ * An FHE program whose frontend IR was produced elsewhere, e.g. by
+ * deserializing the output of [`FheProgramFn::build`] in another process.
+ *
+ * # Remarks
+ * You generally construct this via
+ * [`Compiler::with_frontend`](crate::Compiler::with_frontend).
+ *
+ * The IR is used as-is for every parameter set the compiler tries. Plaintext
+ * literals (e.g. from [`Batched`](crate::types::bfv::Batched) or
+ * [`Fractional`](crate::types::bfv::Fractional) constants) are already
+ * encoded under the parameters the IR was built with, so IR containing
+ * them should be compiled with
+ * [`with_params`](crate::GenericCompiler::with_params) set to those same
+ * parameters.
+ */
+#[derive(Clone)]
+pub struct PrebuiltFheProgram {
+    name: String,
+    signature: CallSignature,
+    frontend: FheFrontendCompilation,
+}
+
+impl PrebuiltFheProgram {
+    /**
+     * Wraps the given frontend IR as an FHE program named `name` with the
+     * given call signature.
+     */
+    pub fn new(name: &str, signature: CallSignature, frontend: FheFrontendCompilation) -> Self {
+        Self {
+            name: name.to_owned(),
+            signature,
+            frontend,
+        }
+    }
+}
+
+impl FheProgramFn for PrebuiltFheProgram {
+    fn signature(&self) -> CallSignature {
+        self.signature.clone()
+    }
+
+    fn build_with_max_nodes(
+        &self,
+        params: &Params,
+        max_nodes: Option<usize>,
+    ) -> Result<FheFrontendCompilation> {
+        if SchemeType::Bfv != params.scheme_type {
+            return Err(Error::IncorrectScheme);
+        }
+
+        let nodes = self.frontend.node_count();
+
+        match max_nodes {
+            Some(max) if nodes > max => Err(Error::FheProgramTooLarge { nodes, max }),
+            _ => Ok(self.frontend.clone()),
+        }
+    }
+
+    fn scheme_type(&self) -> SchemeType {
+        SchemeType::Bfv
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn chain_count(&self) -> usize {
+        1
+    }
+}
+
+impl AsRef<str> for PrebuiltFheProgram {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
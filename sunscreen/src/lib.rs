@@ -70,21 +70,24 @@ use sunscreen_runtime::{marker, Fhe, FheZkp, Zkp};
 use sunscreen_zkp_backend::CompiledZkpProgram;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
 
-pub use compiler::{Compiler, FheProgramFn, GenericCompiler};
+pub use compiler::{CompileReport, Compiler, FheProgramFn, GenericCompiler};
 pub use error::{Error, Result};
-pub use params::PlainModulusConstraint;
+pub use fhe::{BuilderValue, BuiltFheProgram, FheProgramBuilder, PrebuiltFheProgram};
+pub use params::{BindingConstraint, CandidateOutcome, ParamsCandidate, PlainModulusConstraint};
 pub use seal_fhe::Plaintext as SealPlaintext;
+pub use sunscreen_backend::RelinearizationStrategy;
 pub use sunscreen_compiler_macros::*;
 pub use sunscreen_fhe_program::{SchemeType, SecurityLevel};
 pub use sunscreen_runtime::{
     CallSignature, Ciphertext, CompiledFheProgram, Error as RuntimeError, FheProgramInput,
     FheProgramInputTrait, FheProgramMetadata, FheRuntime, FheZkpRuntime, InnerCiphertext,
-    InnerPlaintext, Params, Plaintext, PrivateKey, PublicKey, RequiredKeys, Runtime, WithContext,
-    ZkpProgramInput, ZkpRuntime,
+    InnerPlaintext, Params, Plaintext, PrivateKey, PublicKey, RequiredKeys, RunContext, Runtime,
+    TypedCiphertext, WithContext, ZkpProgramInput, ZkpRuntime,
 };
+pub use sunscreen_runtime::{set_thread_pool_size, thread_pool_size};
 pub use sunscreen_zkp_backend::{BackendField, Error as ZkpError, Result as ZkpResult, ZkpBackend};
 pub use zkp::ZkpProgramFn;
 pub use zkp::{
@@ -97,27 +100,27 @@ pub use zkp::{
  * The outcome of successful compilation. Contains one or more [`CompiledFheProgram`].
  */
 pub struct Application<T> {
-    fhe_programs: HashMap<String, CompiledFheProgram>,
-    zkp_programs: HashMap<String, CompiledZkpProgram>,
+    fhe_programs: BTreeMap<String, CompiledFheProgram>,
+    zkp_programs: BTreeMap<String, CompiledZkpProgram>,
     _phantom: PhantomData<T>,
 }
 
 impl<T> Application<T> {
     /**
-     * Constructs a new Application from the given HashMap of programs. The
+     * Constructs a new Application from the given map of programs. The
      * keys of this contain FHE program names and the values are the
      * compiled FHE programs.
      *
      * # Remarks
-     * The programs [`HashMap`] must contain at least 1 program or this
+     * The programs [`BTreeMap`] must contain at least 1 program or this
      * function will return [`Error::NoPrograms`].
      *
      * You should generally not call this function
      * It is an implementation detail of compilation.
      */
     pub(crate) fn new(
-        fhe_programs: HashMap<String, CompiledFheProgram>,
-        zkp_programs: HashMap<String, CompiledZkpProgram>,
+        fhe_programs: BTreeMap<String, CompiledFheProgram>,
+        zkp_programs: BTreeMap<String, CompiledZkpProgram>,
     ) -> Result<Self> {
         if fhe_programs.is_empty() && zkp_programs.is_empty() {
             return Err(Error::NoPrograms);
@@ -179,7 +182,7 @@ where
     }
 
     /**
-     * Returns an iterator over all the compiled programs.
+     * Returns an iterator over all the compiled programs, ordered by name.
      */
     pub fn get_fhe_programs(&self) -> impl Iterator<Item = (&String, &CompiledFheProgram)> {
         self.fhe_programs.iter()
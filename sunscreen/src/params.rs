@@ -6,8 +6,12 @@ use seal_fhe::{
     BfvEncryptionParametersBuilder, CoefficientModulus, Context, KeyGenerator, Modulus,
     PlainModulus,
 };
-use sunscreen_backend::noise_model::{
-    noise_budget_to_noise, predict_noise, MeasuredModel, TargetNoiseLevel,
+use sunscreen_backend::{
+    noise_model::{
+        noise_to_noise_budget, predict_noise, CanonicalEmbeddingNormModel, MeasuredModel,
+        NoiseModel, TargetNoiseLevel,
+    },
+    RelinearizationStrategy,
 };
 use sunscreen_fhe_program::{FheProgram, FheProgramTrait, Operation, SchemeType};
 pub use sunscreen_runtime::Params;
@@ -112,24 +116,324 @@ fn can_make_required_keys(fhe_program: &FheProgram, params: &Params) -> Result<b
     Ok(create_galois && create_relin)
 }
 
+/**
+ * Below this many bits of noise margin, the noise model's estimation
+ * error can exhaust an output's budget.
+ */
+pub(crate) const SAFE_NOISE_MARGIN: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * Advice on the noise margin given to [`determine_params`], comparing it
+ * with the budget the FHE programs actually need.
+ */
+pub(crate) enum NoiseMarginAdvice {
+    /**
+     * The margin alone forced a larger lattice dimension: reducing it by
+     * `bits` would let the search choose `lattice_dimension` instead.
+     */
+    CouldReduce { bits: u32, lattice_dimension: u64 },
+
+    /**
+     * The margin is below [`SAFE_NOISE_MARGIN`].
+     */
+    BelowSafeThreshold,
+}
+
+impl std::fmt::Display for NoiseMarginAdvice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CouldReduce {
+                bits,
+                lattice_dimension,
+            } => write!(
+                f,
+                "Noise margin could be reduced by {} bits to use the smaller lattice dimension {}.",
+                bits, lattice_dimension
+            ),
+            Self::BelowSafeThreshold => write!(
+                f,
+                "Noise margin is below the safe threshold of {} bits; outputs may fail to decrypt.",
+                SAFE_NOISE_MARGIN
+            ),
+        }
+    }
+}
+
+/**
+ * Predicts the smallest noise budget, in bits, left in any output of the
+ * given FHE programs under `params`. Returns `None` if `params` can't run
+ * them.
+ */
+fn predict_min_noise_budget(
+    fhe_program_fns: &[Box<dyn FheProgramFn>],
+    params: &Params,
+    max_nodes: Option<usize>,
+    relinearization: RelinearizationStrategy,
+) -> Result<Option<f64>> {
+    let mut min_budget = f64::INFINITY;
+
+    for program in fhe_program_fns {
+        trace!("Running backend compilation for {}", program.name());
+        let ir = program
+            .build_with_max_nodes(params, max_nodes)?
+            .compile(params, relinearization)?;
+
+        ir.validate().map_err(Error::FheProgramError)?;
+        trace!("Built and validated {}", program.name());
+
+        match can_make_required_keys(&ir, params) {
+            Ok(true) => {}
+            _ => return Ok(None),
+        };
+
+        let mut chain_noise_level = 0f64;
+
+        for _ in 0..program.chain_count() {
+            let noise_targets = ir
+                .graph
+                .node_weights()
+                .filter(|n| {
+                    matches!(
+                        n.operation,
+                        Operation::InputCiphertext(_) | Operation::InputPlaintext(_)
+                    )
+                })
+                .map(|n| match n.operation {
+                    Operation::InputCiphertext(_) => {
+                        if chain_noise_level == 0f64 {
+                            TargetNoiseLevel::Fresh
+                        } else {
+                            TargetNoiseLevel::InvariantNoise(chain_noise_level)
+                        }
+                    }
+                    Operation::InputPlaintext(_) => TargetNoiseLevel::NotApplicable,
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<TargetNoiseLevel>>();
+
+            let model = match MeasuredModel::new(&ir, params, &noise_targets) {
+                Ok(v) => v,
+                Err(_) => {
+                    trace!(
+                        "Failed to construct noise model for {} with lattice_dimension={}",
+                        program.name(),
+                        params.lattice_dimension
+                    );
+                    return Ok(None);
+                }
+            };
+
+            // Each chain's outputs feed the next, so the last chain's
+            // noisiest output bounds them all.
+            for output_noise in predict_noise(&model, &ir) {
+                chain_noise_level = f64::max(chain_noise_level, output_noise);
+            }
+        }
+
+        min_budget = f64::min(min_budget, noise_to_noise_budget(chain_noise_level));
+    }
+
+    Ok(Some(min_budget))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/**
+ * What the parameter search concluded about one lattice dimension.
+ */
+pub enum CandidateOutcome {
+    /**
+     * No plaintext modulus satisfying the [`PlainModulusConstraint`]
+     * exists at this dimension.
+     */
+    UnsatisfiablePlainModulus,
+
+    /**
+     * The FHE programs can't run at this dimension, e.g. because the
+     * required keys can't be generated.
+     */
+    CannotRun,
+
+    /**
+     * The smallest predicted noise budget, in bits, left in any output
+     * was less than the noise margin.
+     */
+    BelowNoiseMargin {
+        /**
+         * The predicted noise budget.
+         */
+        noise_budget: f64,
+    },
+
+    /**
+     * The search chose this dimension.
+     */
+    Selected {
+        /**
+         * The predicted noise budget.
+         */
+        noise_budget: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/**
+ * A lattice dimension the parameter search tried.
+ */
+pub struct ParamsCandidate {
+    /**
+     * The lattice dimension.
+     */
+    pub lattice_dimension: u64,
+
+    /**
+     * Whether the search chose this dimension and, if not, why.
+     */
+    pub outcome: CandidateOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * The constraint that kept the parameter search from choosing a smaller
+ * lattice dimension.
+ */
+pub enum BindingConstraint {
+    /**
+     * The next smaller dimension would have left a positive noise budget,
+     * but less than the requested noise margin.
+     */
+    NoiseMargin,
+
+    /**
+     * At the next smaller dimension, the [`SecurityLevel`] limits the
+     * coefficient modulus so much that the FHE programs exhaust their
+     * noise budget or can't run at all.
+     */
+    SecurityLevel,
+
+    /**
+     * The next smaller dimension can't satisfy the
+     * [`PlainModulusConstraint`].
+     */
+    PlainModulus,
+}
+
+/**
+ * The outcome of [`determine_params`].
+ */
+pub(crate) struct ParamsSearch {
+    /**
+     * The chosen parameters.
+     */
+    pub params: Params,
+
+    /**
+     * Advice on the requested noise margin, if any.
+     */
+    pub advice: Option<NoiseMarginAdvice>,
+
+    /**
+     * Every lattice dimension tried, in increasing order.
+     */
+    pub candidates: Vec<ParamsCandidate>,
+
+    /**
+     * The smallest predicted noise budget left in any output under
+     * `params`.
+     */
+    pub noise_budget: f64,
+}
+
+impl ParamsSearch {
+    /**
+     * Returns the constraint that ruled out the next smaller dimension
+     * tried, or `None` if the search chose the first dimension it tried.
+     */
+    pub fn binding_constraint(&self) -> Option<BindingConstraint> {
+        let rejected = self.candidates.len().checked_sub(2)?;
+
+        Some(match self.candidates[rejected].outcome {
+            CandidateOutcome::UnsatisfiablePlainModulus => BindingConstraint::PlainModulus,
+            CandidateOutcome::BelowNoiseMargin { noise_budget } if noise_budget > 0. => {
+                BindingConstraint::NoiseMargin
+            }
+            _ => BindingConstraint::SecurityLevel,
+        })
+    }
+}
+
+/**
+ * Estimates the noise budget, in bits, of a freshly encrypted ciphertext
+ * under `params`.
+ */
+pub(crate) fn fresh_noise_budget(params: &Params) -> Option<f64> {
+    CanonicalEmbeddingNormModel::new(params)
+        .ok()
+        .map(|model| noise_to_noise_budget(model.encrypt()))
+}
+
 /**
  * Determines the minimal parameters required to satisfy the noise constraint for
  * the given FHE program and plaintext modulo and security level.
+ *
+ * If `lattice_dimension` is given, only that dimension is considered. It
+ * must be a power of two SEAL supports (1024 through 32768).
+ *
+ * Also returns advice when `noise_margin_bits` is unsafely small or is
+ * the only reason the search rejected a smaller lattice dimension, and
+ * the outcome for each dimension tried.
  */
-pub fn determine_params(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn determine_params(
     fhe_program_fns: &[Box<dyn FheProgramFn>],
     plaintext_constraint: PlainModulusConstraint,
     security_level: SecurityLevel,
     noise_margin_bits: u32,
     scheme_type: SchemeType,
-) -> Result<Params> {
-    'params_loop: for (i, n) in LATTICE_DIMENSIONS.iter().enumerate() {
+    max_nodes: Option<usize>,
+    lattice_dimension: Option<u64>,
+    relinearization: RelinearizationStrategy,
+) -> Result<ParamsSearch> {
+    if let Some(n) = lattice_dimension {
+        if !LATTICE_DIMENSIONS.contains(&n) {
+            return Err(Error::unsupported(&format!(
+                "lattice dimension {} is not a power of two between {} and {}",
+                n,
+                LATTICE_DIMENSIONS[0],
+                LATTICE_DIMENSIONS[LATTICE_DIMENSIONS.len() - 1]
+            )));
+        }
+    }
+
+    let dimensions = LATTICE_DIMENSIONS
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| lattice_dimension.map_or(true, |d| **n == d));
+
+    let mut candidates = vec![];
+
+    // The largest lattice dimension rejected only for missing the margin,
+    // with the budget it would have left.
+    let mut rejected_for_margin = None;
+
+    // The parameters of the last dimension with a usable plain modulus.
+    let mut last_params = None;
+
+    for (i, n) in dimensions {
+        let mut reject = |outcome| {
+            candidates.push(ParamsCandidate {
+                lattice_dimension: *n,
+                outcome,
+            })
+        };
+
         // Select a plain modulus that meets needs of the passed
         // constraint.
         let plaintext_modulus = match plaintext_constraint_to_modulus(plaintext_constraint, i) {
             Ok(v) => v,
             Err(_) => {
-                continue 'params_loop;
+                reject(CandidateOutcome::UnsatisfiablePlainModulus);
+                continue;
             }
         };
 
@@ -145,6 +449,8 @@ pub fn determine_params(
             scheme_type,
         };
 
+        last_params = Some(params.clone());
+
         trace!(
             "Trying to build scheme with \\lambda={:#?} p={} n={} c=default(\\lambda, n).",
             security_level,
@@ -152,85 +458,151 @@ pub fn determine_params(
             n
         );
 
-        for program in fhe_program_fns {
-            trace!("Successfully created parameters.");
-            trace!("Running backend compilation for {}", program.name());
-            let ir = program.build(&params)?.compile();
-
-            ir.validate().map_err(Error::FheProgramError)?;
-            trace!("Built and validated {}", program.name());
-
-            match can_make_required_keys(&ir, &params) {
-                Ok(can_make_keys) => {
-                    if !can_make_keys {
-                        continue 'params_loop;
-                    }
-                }
-                Err(_) => {
-                    continue 'params_loop;
+        let budget =
+            match predict_min_noise_budget(fhe_program_fns, &params, max_nodes, relinearization)? {
+                Some(v) => v,
+                None => {
+                    reject(CandidateOutcome::CannotRun);
+                    continue;
                 }
             };
 
-            let mut chain_noise_level = 0f64;
-
-            for _ in 0..program.chain_count() {
-                let noise_targets = ir
-                    .graph
-                    .node_weights()
-                    .filter(|n| {
-                        matches!(
-                            n.operation,
-                            Operation::InputCiphertext(_) | Operation::InputPlaintext(_)
-                        )
-                    })
-                    .map(|n| match n.operation {
-                        Operation::InputCiphertext(_) => {
-                            if chain_noise_level == 0f64 {
-                                TargetNoiseLevel::Fresh
-                            } else {
-                                TargetNoiseLevel::InvariantNoise(chain_noise_level)
-                            }
-                        }
-                        Operation::InputPlaintext(_) => TargetNoiseLevel::NotApplicable,
-                        _ => unreachable!(),
-                    })
-                    .collect::<Vec<TargetNoiseLevel>>();
-
-                let model = match MeasuredModel::new(&ir, &params, &noise_targets) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        trace!(
-                            "Failed to construct noise model for {} with lattice_dimension={}",
-                            program.name(),
-                            n
-                        );
-                        continue 'params_loop;
-                    }
-                };
-
-                let output_noises = predict_noise(&model, &ir);
-
-                let target_noise = noise_budget_to_noise(noise_margin_bits as f64);
-
-                for output_noise in output_noises {
-                    if output_noise > target_noise {
-                        trace!(
-                            "Failed to meet noise constraints with lattice dimension {} for program {}",
-                            n,
-                            program.name()
-                        );
-                        continue 'params_loop;
-                    } else if output_noise > chain_noise_level {
-                        chain_noise_level = output_noise
-                    }
-                }
-            }
-
-            debug!("Using params lattice_dimension={} and ={:#?}", n, coeff);
+        if budget < noise_margin_bits as f64 {
+            trace!(
+                "Failed to meet noise constraints with lattice dimension {}",
+                n
+            );
+
+            reject(CandidateOutcome::BelowNoiseMargin {
+                noise_budget: budget,
+            });
+            rejected_for_margin = Some((*n, budget));
+            continue;
         }
 
-        return Ok(params);
+        debug!("Using params lattice_dimension={} and ={:#?}", n, coeff);
+
+        candidates.push(ParamsCandidate {
+            lattice_dimension: *n,
+            outcome: CandidateOutcome::Selected {
+                noise_budget: budget,
+            },
+        });
+
+        let advice = if noise_margin_bits < SAFE_NOISE_MARGIN {
+            Some(NoiseMarginAdvice::BelowSafeThreshold)
+        } else {
+            rejected_for_margin
+                .filter(|(_, budget)| *budget >= SAFE_NOISE_MARGIN as f64)
+                .map(
+                    |(lattice_dimension, budget)| NoiseMarginAdvice::CouldReduce {
+                        bits: noise_margin_bits - budget.floor() as u32,
+                        lattice_dimension,
+                    },
+                )
+        };
+
+        return Ok(ParamsSearch {
+            params,
+            advice,
+            candidates,
+            noise_budget: budget,
+        });
+    }
+
+    // No dimension had a plain modulus meeting the constraint, so the
+    // program never got a chance to fail.
+    let params = match last_params {
+        Some(p) => p,
+        None => return Err(Error::UnsatisfiableConstraint),
+    };
+
+    let mut depth = 0;
+
+    for program in fhe_program_fns {
+        let frontend = program.build_with_max_nodes(&params, max_nodes)?;
+        depth = usize::max(depth, frontend.multiplicative_depth());
+    }
+
+    Err(Error::NoParametersFound {
+        depth,
+        requested_margin: noise_margin_bits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as sunscreen, fhe_program,
+        types::{bfv::Signed, Cipher},
+    };
+
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    fn search(noise_margin_bits: u32) -> ParamsSearch {
+        let fns: Vec<Box<dyn FheProgramFn>> = vec![Box::new(add)];
+
+        determine_params(
+            &fns,
+            PlainModulusConstraint::Raw(64),
+            SecurityLevel::TC128,
+            noise_margin_bits,
+            SchemeType::Bfv,
+            None,
+            None,
+            RelinearizationStrategy::EveryMultiply,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn large_margin_could_be_reduced() {
+        let ParamsSearch { params, advice, .. } = search(60);
+
+        let (bits, lattice_dimension) = match advice {
+            Some(NoiseMarginAdvice::CouldReduce {
+                bits,
+                lattice_dimension,
+            }) => (bits, lattice_dimension),
+            _ => panic!("Expected CouldReduce, got {:?}", advice),
+        };
+
+        assert!(lattice_dimension < params.lattice_dimension);
+        assert!(advice.unwrap().to_string().contains("could be reduced"));
+
+        let reduced = search(60 - bits).params;
+
+        assert_eq!(reduced.lattice_dimension, lattice_dimension);
+    }
+
+    #[test]
+    fn large_margin_is_binding_constraint() {
+        let search = search(60);
+
+        let selected = search.candidates.last().unwrap();
+
+        assert_eq!(selected.lattice_dimension, search.params.lattice_dimension);
+        assert_eq!(
+            selected.outcome,
+            CandidateOutcome::Selected {
+                noise_budget: search.noise_budget
+            }
+        );
+        assert!(search.noise_budget >= 60.);
+        assert_eq!(
+            search.binding_constraint(),
+            Some(BindingConstraint::NoiseMargin)
+        );
     }
 
-    Err(Error::NoParams)
+    #[test]
+    fn small_margin_is_unsafe() {
+        let advice = search(SAFE_NOISE_MARGIN - 1).advice;
+
+        assert_eq!(advice, Some(NoiseMarginAdvice::BelowSafeThreshold));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    fhe::{with_fhe_ctx, FheContextOps, Literal},
+    fhe::{with_fhe_ctx, FheContext, FheContextOps, Literal},
     types::{
         intern::{Cipher, FheProgramNode},
         ops::*,
@@ -8,10 +8,12 @@ use crate::{
     },
     FheProgramInputTrait, InnerPlaintext, Params, Plaintext, WithContext,
 };
+use petgraph::stable_graph::NodeIndex;
 use seal_fhe::{
     BFVEncoder, BfvEncryptionParametersBuilder, Context as SealContext, Modulus,
-    Result as SealResult,
+    Plaintext as SealPlaintext, Result as SealResult,
 };
+use std::collections::HashMap;
 use std::ops::*;
 use sunscreen_runtime::{Error as RuntimeError, Result as RuntimeResult};
 
@@ -164,20 +166,7 @@ impl<const LANES: usize> TryIntoPlaintext for Batched<LANES> {
             ));
         }
 
-        let encryption_params = BfvEncryptionParametersBuilder::new()
-            .set_poly_modulus_degree(params.lattice_dimension)
-            .set_plain_modulus(Modulus::new(params.plain_modulus)?)
-            .set_coefficient_modulus(
-                params
-                    .coeff_modulus
-                    .iter()
-                    .map(|x| Modulus::new(*x))
-                    .collect::<SealResult<Vec<Modulus>>>()?,
-            )
-            .build()?;
-
-        let context = SealContext::new(&encryption_params, false, params.security_level)?;
-        let encoder = BFVEncoder::new(&context)?;
+        let encoder = make_encoder(params)?;
 
         let reps = params.lattice_dimension as usize / (2 * LANES);
 
@@ -212,20 +201,7 @@ impl<const LANES: usize> TryFromPlaintext for Batched<LANES> {
             return Err(sunscreen_runtime::Error::ParameterMismatch);
         }
 
-        let encryption_params = BfvEncryptionParametersBuilder::new()
-            .set_poly_modulus_degree(params.lattice_dimension)
-            .set_plain_modulus(Modulus::new(params.plain_modulus)?)
-            .set_coefficient_modulus(
-                params
-                    .coeff_modulus
-                    .iter()
-                    .map(|x| Modulus::new(*x))
-                    .collect::<SealResult<Vec<Modulus>>>()?,
-            )
-            .build()?;
-
-        let context = SealContext::new(&encryption_params, false, params.security_level)?;
-        let encoder = BFVEncoder::new(&context)?;
+        let encoder = make_encoder(params)?;
 
         let data = encoder.decode_signed(&plaintext[0].data)?;
 
@@ -554,6 +530,22 @@ impl<const LANES: usize> GraphCipherMul for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> GraphCipherPlainMul for Batched<LANES> {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_cipher_plain_mul(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_multiplication_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
 impl<const LANES: usize> GraphCipherConstMul for Batched<LANES> {
     type Left = Self;
     type Right = i64;
@@ -563,7 +555,7 @@ impl<const LANES: usize> GraphCipherConstMul for Batched<LANES> {
         b: Self::Right,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
             let l = ctx.add_plaintext_literal(b.inner);
             let n = ctx.add_multiplication_plaintext(a.ids[0], l);
 
@@ -628,6 +620,1110 @@ impl<const LANES: usize> LaneCount for Batched<LANES> {
     }
 }
 
+/**
+ * Computes `x^exp mod p`.
+ */
+fn pow_mod(x: u64, mut exp: u64, p: u64) -> u64 {
+    let mut result = 1u128;
+    let mut base = x as u128 % p as u128;
+
+    while exp > 0 {
+        if exp & 0x1 == 1 {
+            result = result * base % p as u128;
+        }
+
+        base = base * base % p as u128;
+        exp >>= 1;
+    }
+
+    result as u64
+}
+
+/**
+ * Computes `x^-1 mod p` for prime `p`.
+ */
+fn inverse_mod(x: u64, p: u64) -> u64 {
+    // Fermat's little theorem: x^(p - 2) = x^-1 mod p
+    pow_mod(x, p - 2, p)
+}
+
+/**
+ * Creates a batch encoder for `params`.
+ */
+pub(crate) fn make_encoder(params: &Params) -> SealResult<BFVEncoder> {
+    let encryption_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(params.lattice_dimension)
+        .set_plain_modulus(Modulus::new(params.plain_modulus)?)
+        .set_coefficient_modulus(
+            params
+                .coeff_modulus
+                .iter()
+                .map(|x| Modulus::new(*x))
+                .collect::<SealResult<Vec<Modulus>>>()?,
+        )
+        .build()?;
+
+    let context = SealContext::new(&encryption_params, false, params.security_level)?;
+
+    BFVEncoder::new(&context)
+}
+
+/**
+ * The roots of unity behind the batch encoding: a plaintext polynomial
+ * `m` has `m(psi^exponents[i])` in flat slot `i` (row 0's lanes, then
+ * row 1's).
+ */
+struct SlotRoots {
+    /**
+     * `powers[k] = psi^k` for the primitive `2n`-th root of unity `psi`.
+     */
+    powers: Vec<u64>,
+    exponents: Vec<usize>,
+}
+
+impl SlotRoots {
+    fn new(encoder: &BFVEncoder, params: &Params) -> Self {
+        let m = 2 * params.lattice_dimension as usize;
+        let p = params.plain_modulus;
+
+        // Slot i of the polynomial x holds its root.
+        let mut x = SealPlaintext::new().unwrap();
+        x.resize(2);
+        x.set_coefficient(1, 1);
+
+        let roots = encoder.decode_unsigned(&x).unwrap();
+
+        // Every root is a primitive 2n-th root of unity, so any serves as
+        // the base.
+        let psi = roots[0];
+
+        let mut powers = Vec::with_capacity(m);
+        let mut log = HashMap::with_capacity(m);
+        let mut power = 1;
+
+        for k in 0..m {
+            powers.push(power);
+            log.insert(power, k);
+            power = ((power as u128 * psi as u128) % p as u128) as u64;
+        }
+
+        let exponents = roots.iter().map(|r| log[r]).collect();
+
+        Self { powers, exponents }
+    }
+
+    /**
+     * Returns `root_i^k`.
+     */
+    fn pow(&self, i: usize, k: usize) -> u64 {
+        self.powers[self.exponents[i] * k % self.powers.len()]
+    }
+
+    /**
+     * Returns `root_i^-k`.
+     */
+    fn pow_inv(&self, i: usize, k: usize) -> u64 {
+        let m = self.powers.len();
+
+        self.powers[(m - self.exponents[i] * k % m) % m]
+    }
+}
+
+/**
+ * Computes `y = A x` on all `n` slots of `x` (including lanes beyond
+ * `LANES`), where `a(i, j)` is `A`'s entry at flat slot indices `i` and
+ * `j`.
+ *
+ * # Remarks
+ * Uses the diagonal method with baby-step giant-step rotations: each row
+ * rotation of `x` and of `x.swap_rows()` supplies one generalized
+ * diagonal of `A`, pre-rotated so the giant steps can factor out of the
+ * sums. This costs `n` plaintext multiplications and literals and about
+ * `3 * sqrt(n / 2)` rotations.
+ */
+fn slot_linear_transform<const LANES: usize, F>(
+    x: FheProgramNode<Cipher<Batched<LANES>>>,
+    a: F,
+) -> FheProgramNode<Cipher<Batched<LANES>>>
+where
+    F: Fn(usize, usize) -> u64,
+{
+    let params = with_fhe_ctx(|ctx| ctx.data.params.clone());
+    let encoder = make_encoder(&params).expect("parameters must support batching");
+
+    let n = params.lattice_dimension as usize;
+    let h = n / 2;
+    let baby = 1 << ((h.trailing_zeros() + 1) / 2);
+    let giant = h / baby;
+
+    let sources = [x, x.swap_rows()];
+
+    // rotated[b][s] = sources[s] << b
+    let rotated = (0..baby)
+        .map(|b| [sources[0].rotate(b as i64), sources[1].rotate(b as i64)])
+        .collect::<Vec<_>>();
+
+    (0..giant)
+        .map(|g| {
+            let giant_step = g * baby;
+
+            let inner = with_fhe_ctx(|ctx| {
+                let terms = (0..baby)
+                    .flat_map(|b| (0..2).map(move |s| (b, s)))
+                    .map(|(b, s)| {
+                        // Slot (r, c) of this diagonal multiplies slot
+                        // (r ^ s, c + b) of the input, and is stored
+                        // rotated right by the giant step.
+                        let diagonal = (0..n)
+                            .map(|i| {
+                                let (r, c) = (i / h, i % h);
+                                let out = r * h + (c + h - giant_step) % h;
+                                let src = (r ^ s) * h + (c + b) % h;
+
+                                a(out, src)
+                            })
+                            .collect::<Vec<_>>();
+
+                        let diagonal =
+                            ctx.add_plaintext_literal(InnerPlaintext::Seal(vec![WithContext {
+                                params: params.clone(),
+                                data: encoder.encode_unsigned(&diagonal).unwrap(),
+                            }]));
+
+                        ctx.add_multiplication_plaintext(rotated[b][s].ids[0], diagonal)
+                    })
+                    .collect::<Vec<_>>();
+
+                let n = terms[1..]
+                    .iter()
+                    .fold(terms[0], |acc, t| ctx.add_addition(acc, *t));
+
+                FheProgramNode::<Cipher<Batched<LANES>>>::new(&[n])
+            });
+
+            inner.rotate(giant_step as i64)
+        })
+        .reduce(|acc, x| acc + x)
+        .unwrap()
+}
+
+/**
+ * Encodes `value` and adds it to the graph as a plaintext literal.
+ */
+fn add_batched_literal<const LANES: usize>(
+    ctx: &mut FheContext,
+    value: Batched<LANES>,
+) -> NodeIndex {
+    let plaintext = value.try_into_plaintext(&ctx.data.params).unwrap();
+
+    ctx.add_plaintext_literal(plaintext.inner)
+}
+
+/**
+ * Maps `c` in `0..p` to the representative in `-p/2..=p/2`, so it encodes
+ * as a signed value.
+ */
+fn centered(c: u64, p: u64) -> i64 {
+    if c > p / 2 {
+        c as i64 - p as i64
+    } else {
+        c as i64
+    }
+}
+
+/**
+ * Multiplies the given nodes together as a balanced tree to minimize
+ * multiplicative depth.
+ */
+fn tree_product<const LANES: usize>(
+    mut nodes: Vec<FheProgramNode<Cipher<Batched<LANES>>>>,
+) -> FheProgramNode<Cipher<Batched<LANES>>> {
+    while nodes.len() > 1 {
+        nodes = nodes
+            .chunks(2)
+            .map(|c| if c.len() == 2 { c[0] * c[1] } else { c[0] })
+            .collect();
+    }
+
+    nodes[0]
+}
+
+/**
+ * Returns the coefficients (lowest degree first) of the unique polynomial
+ * of degree less than `domain.len()` passing through `(domain[i], values[i])`
+ * over `Z_p`. Requires `p` prime and the domain points distinct mod `p`.
+ */
+pub(super) fn interpolate_mod(domain: &[i64], values: &[i64], p: u64) -> Vec<u64> {
+    let p = p as u128;
+    let reduce = |x: i64| x.rem_euclid(p as i64) as u128;
+
+    // m(x) = prod_j (x - x_j)
+    let mut m = vec![1u128];
+
+    for x_j in domain {
+        let mut next = vec![0u128; m.len() + 1];
+
+        for (i, c) in m.iter().enumerate() {
+            next[i + 1] = (next[i + 1] + c) % p;
+            next[i] = (next[i] + (p - reduce(*x_j)) * c) % p;
+        }
+
+        m = next;
+    }
+
+    let mut coeffs = vec![0u128; domain.len()];
+
+    for (x_k, y_k) in domain.iter().zip(values.iter()) {
+        let x_k = reduce(*x_k);
+
+        // Synthetic division: n(x) = m(x) / (x - x_k)
+        let mut n = vec![0u128; domain.len()];
+        let mut carry = 0u128;
+
+        for i in (0..domain.len()).rev() {
+            carry = (m[i + 1] + carry * x_k) % p;
+            n[i] = carry;
+        }
+
+        // n(x_k) = prod_{j != k} (x_k - x_j)
+        let denominator = n.iter().rev().fold(0u128, |acc, c| (acc * x_k + c) % p);
+        let scale = reduce(*y_k) * inverse_mod(denominator as u64, p as u64) as u128 % p;
+
+        for (c, n_i) in coeffs.iter_mut().zip(n.iter()) {
+            *c = (*c + scale * n_i) % p;
+        }
+    }
+
+    coeffs.into_iter().map(|c| c as u64).collect()
+}
+
+impl<const LANES: usize> FheProgramNode<Cipher<Batched<LANES>>> {
+    /**
+     * Evaluates the polynomial with the given coefficients (lowest degree
+     * first, reduced mod the plaintext modulus) on each lane.
+     *
+     * # Panics
+     * Panics if the polynomial is constant, as no ciphertext operation
+     * produces a constant without yielding a transparent ciphertext.
+     */
+    fn eval_polynomial(self, coeffs: &[u64]) -> Self {
+        let p = Self::get_plain_modulus();
+
+        // x^k = x^(2^m) * x^(k - 2^m) where 2^m is the largest power of
+        // 2 not exceeding k. This keeps every power at depth
+        // ceil(log2(k)).
+        let mut powers = vec![self];
+
+        for k in 2..coeffs.len() {
+            let hi = 1 << (usize::BITS - 1 - k.leading_zeros());
+
+            let power = if hi == k {
+                powers[hi / 2 - 1] * powers[hi / 2 - 1]
+            } else {
+                powers[hi - 1] * powers[k - hi - 1]
+            };
+
+            powers.push(power);
+        }
+
+        // Multiplying by a zero constant gives a transparent ciphertext,
+        // which SEAL rejects, so skip zero terms. Even functions, such as
+        // zero tests, have no linear term at all.
+        let result = coeffs
+            .iter()
+            .skip(1)
+            .zip(powers.iter())
+            .filter(|(c, _)| **c != 0)
+            .map(|(c, x_k)| *x_k * centered(*c, p))
+            .reduce(|acc, x| acc + x)
+            .expect("polynomial must not be constant");
+
+        let c_0 = centered(coeffs.first().copied().unwrap_or(0), p);
+
+        with_fhe_ctx(|ctx| {
+            let c_0 = add_batched_literal(ctx, Batched::<LANES>::from(c_0));
+            let n = ctx.add_addition_plaintext(result.ids[0], c_0);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+
+    /**
+     * Compares each lane against a public threshold, returning `1` in
+     * lanes where `self >= threshold` and `0` elsewhere.
+     *
+     * # Remarks
+     * BFV can't branch on encrypted data, so this evaluates the sign
+     * indicator of `self - threshold` as the unique polynomial matching it
+     * on every point of `range`. Every lane must lie in `range`; lanes
+     * outside it produce garbage. Using a plaintext threshold saves
+     * encrypting it and lets the polynomial absorb the subtraction.
+     *
+     * Like batching itself, this requires a prime plaintext modulus.
+     *
+     * # Performance
+     * Evaluating a polynomial of degree `d = range.count() - 1` costs
+     * about `d` ciphertext multiplications at multiplicative depth
+     * `ceil(log2(d))`, plus `d` plaintext-constant multiplications. Keep
+     * `range` as tight as the data allows.
+     *
+     * # Panics
+     * Panics if `range` is empty or has at least as many values as the
+     * plaintext modulus, or if every value in `range` compares the same
+     * way against `threshold`. The result would then be a constant, which
+     * can't be computed from a ciphertext without making it transparent.
+     */
+    pub fn ge_const(self, threshold: i64, range: RangeInclusive<i64>) -> Self {
+        self.eval_on_range(range, |x| (x >= threshold) as i64)
+    }
+
+    /**
+     * Returns `-1`, `0`, or `1` in each lane according to whether that
+     * lane is negative, zero, or positive.
+     *
+     * # Remarks
+     * Like [`ge_const`](Self::ge_const), this evaluates the interpolating
+     * polynomial of the sign function over `range`, which every lane must
+     * lie in. That's the same polynomial as composing the zero test with
+     * the sign bit, `(x != 0) * (1 - 2 * (x < 0))`, but evaluating it
+     * directly avoids the extra multiplication.
+     *
+     * # Performance
+     * Costs the same as [`ge_const`](Self::ge_const) over the same
+     * `range`: multiplicative depth `ceil(log2(range.count() - 1))`.
+     *
+     * # Panics
+     * Panics if `range` is empty or has at least as many values as the
+     * plaintext modulus, or if every value in `range` has the same sign.
+     */
+    pub fn signum(self, range: RangeInclusive<i64>) -> Self {
+        self.eval_on_range(range, i64::signum)
+    }
+
+    /**
+     * Restricts each lane to the interval `[min, max]`.
+     *
+     * # Remarks
+     * Computing `max(x, min)` and then `min(x, max)` with encrypted
+     * comparisons and muxes would need two comparison polynomials plus
+     * the selections. Since `x.clamp(min, max)` is itself a function of
+     * `x`, this instead evaluates the single polynomial agreeing with it
+     * on every point of `range`, which every lane must lie in. Lanes
+     * outside `range` produce garbage.
+     *
+     * # Performance
+     * Costs the same as [`ge_const`](Self::ge_const) over the same
+     * `range`: about `d = range.count() - 1` ciphertext multiplications
+     * at multiplicative depth `ceil(log2(d))`.
+     *
+     * # Panics
+     * Panics if `min > max`, if `range` is empty or has at least as many
+     * values as the plaintext modulus, or if every value in `range`
+     * clamps to the same value. The result would then be a constant,
+     * which can't be computed from a ciphertext without making it
+     * transparent.
+     */
+    pub fn clamp(self, min: i64, max: i64, range: RangeInclusive<i64>) -> Self {
+        assert!(min <= max, "min must not exceed max");
+
+        self.eval_on_range(range, |x| x.clamp(min, max))
+    }
+
+    /**
+     * Raises the public `base` to an encrypted exponent given by its bits,
+     * least significant first, returning `base^exp mod p` in each lane
+     * where `p` is the plaintext modulus.
+     *
+     * # Remarks
+     * Every lane of every element of `exp_bits` must be `0` or `1`; other
+     * values produce garbage. Since the exponent is encrypted, this is
+     * square-and-multiply with every step taken: bit `i` selects between
+     * `1` and the precomputed `base^(2^i)` as
+     * `1 + bit_i * (base^(2^i) - 1)`, and the factors are multiplied
+     * together. Once `base^(2^i)` reaches `1`, the remaining bits can't
+     * change the result, so they're ignored.
+     *
+     * # Performance
+     * Selecting each factor is a plaintext multiplication, but combining
+     * `k = exp_bits.len()` factors costs `k - 1` ciphertext
+     * multiplications at depth `ceil(log2(k))`. That's on top of whatever
+     * produced the bits; decomposing an encrypted integer into bits is
+     * itself deep, so budget for the sum.
+     *
+     * # Panics
+     * Panics if `exp_bits` is empty, or if `base` is `1` modulo the plain
+     * modulus, since the result is then the constant `1`.
+     */
+    pub fn pow_const_base(base: u64, exp_bits: &[Self]) -> Self {
+        assert!(!exp_bits.is_empty(), "exp_bits must not be empty");
+
+        let p = Self::get_plain_modulus();
+        let mut power = base % p;
+        let mut factors = vec![];
+
+        for bit in exp_bits {
+            // Once base^(2^i) is 1, every later factor is 1 regardless of
+            // the bit. Selecting it would multiply the bit by 0, giving a
+            // transparent ciphertext.
+            if power == 1 {
+                break;
+            }
+
+            let scaled = *bit * centered((power + p - 1) % p, p);
+
+            let factor = with_fhe_ctx(|ctx| {
+                let one = add_batched_literal(ctx, Batched::<LANES>::from(1));
+                let n = ctx.add_addition_plaintext(scaled.ids[0], one);
+
+                FheProgramNode::new(&[n])
+            });
+
+            factors.push(factor);
+            power = (power as u128 * power as u128 % p as u128) as u64;
+        }
+
+        assert!(
+            !factors.is_empty(),
+            "base must not be 1 mod the plain modulus"
+        );
+
+        tree_product(factors)
+    }
+
+    /**
+     * Evaluates the polynomial agreeing with `f` on every point of `range`.
+     */
+    fn eval_on_range<F>(self, range: RangeInclusive<i64>, f: F) -> Self
+    where
+        F: Fn(i64) -> i64,
+    {
+        let domain = range.collect::<Vec<i64>>();
+        let p = Self::get_plain_modulus();
+
+        assert!(!domain.is_empty(), "range must not be empty");
+        assert!(
+            (domain.len() as u64) < p,
+            "range must have fewer values than the plaintext modulus"
+        );
+
+        let values = domain.iter().map(|x| f(*x)).collect::<Vec<i64>>();
+
+        assert!(
+            values.iter().any(|v| *v != values[0]),
+            "function must not be constant over range"
+        );
+
+        self.eval_polynomial(&interpolate_mod(&domain, &values, p))
+    }
+
+    /**
+     * Rotates each row left by an encrypted number of places.
+     *
+     * # Remarks
+     * Every lane of `amount` must contain the same value in
+     * `0..=max_steps`; any other value produces garbage. Since FHE
+     * programs can't branch on encrypted data, this computes every
+     * candidate rotation and selects one:
+     *
+     * `sum_k [amount == k] * (self << k)` for `k` in `0..=max_steps`.
+     *
+     * Each indicator `[amount == k]` is the Lagrange basis polynomial
+     * `prod_{j != k} (amount - j) / (k - j)` evaluated lane-wise, which
+     * requires a prime plaintext modulus (as batching always does).
+     *
+     * # Performance
+     * This costs `max_steps` rotations and `O(max_steps^2)`
+     * ciphertext multiplications, with multiplicative depth
+     * `ceil(log2(max_steps)) + 1`. Keep `max_steps` as small as the
+     * protocol allows; for a known amount, use `<<` instead.
+     *
+     * # Panics
+     * Panics if `max_steps` is not less than `LANES`.
+     */
+    pub fn rotate_by_encrypted(self, amount: Self, max_steps: u64) -> Self {
+        assert!(
+            (max_steps as usize) < LANES,
+            "max_steps must be less than the number of lanes"
+        );
+
+        if max_steps == 0 {
+            return self;
+        }
+
+        let p = Self::get_plain_modulus();
+
+        // amount - j for each candidate j.
+        let diffs = (0..=max_steps)
+            .map(|j| {
+                with_fhe_ctx(|ctx| {
+                    let j = add_batched_literal(ctx, Batched::<LANES>::from(-(j as i64)));
+                    let n = ctx.add_addition_plaintext(amount.ids[0], j);
+
+                    FheProgramNode::new(&[n])
+                })
+            })
+            .collect::<Vec<Self>>();
+
+        let mut result: Option<Self> = None;
+
+        for k in 0..=max_steps {
+            let numerator = tree_product(
+                diffs
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j as u64 != k)
+                    .map(|(_, d)| *d)
+                    .collect(),
+            );
+
+            // prod_{j != k} (k - j) mod p
+            let denominator = (0..=max_steps).filter(|j| *j != k).fold(1u128, |acc, j| {
+                let diff = (k as i64 - j as i64).rem_euclid(p as i64) as u128;
+
+                acc * diff % p as u128
+            }) as u64;
+
+            // Center the coefficient so it encodes as a signed value.
+            let coeff = inverse_mod(denominator, p);
+            let coeff = if coeff > p / 2 {
+                coeff as i64 - p as i64
+            } else {
+                coeff as i64
+            };
+
+            let indicator = numerator * coeff;
+            let term = if k == 0 {
+                indicator * self
+            } else {
+                indicator * (self << k)
+            };
+
+            result = Some(match result {
+                Some(r) => r + term,
+                None => term,
+            });
+        }
+
+        result.unwrap()
+    }
+
+    /**
+     * Computes the inclusive prefix product of each row, so lane `i`
+     * holds the product of lanes `0..=i`.
+     *
+     * # Remarks
+     * At each step `d = 1, 2, 4, ...`, every lane multiplies in the lane
+     * `d` places before it. Rotation wraps around, so a plaintext mask
+     * replaces the wrapped lanes with `1` before multiplying.
+     *
+     * # Performance
+     * This costs `ceil(log2(LANES))` rotations and ciphertext
+     * multiplications, each step consuming the previous one's result, so
+     * the multiplicative depth is `ceil(log2(LANES))`. The compiler
+     * relinearizes after every level, but wide vectors may still force
+     * larger scheme parameters.
+     */
+    pub fn prefix_product(self) -> Self {
+        let mut result = self;
+        let mut d = 1;
+
+        while d < LANES {
+            // Lanes below d wrapped around; turn them into 1.
+            let mut mask = [[1; LANES]; 2];
+            let mut fill = [[0; LANES]; 2];
+
+            for (m, f) in mask.iter_mut().zip(fill.iter_mut()) {
+                m[..d].fill(0);
+                f[..d].fill(1);
+            }
+
+            let shifted = result >> d as u64;
+
+            let shifted = with_fhe_ctx(|ctx| {
+                let mask = add_batched_literal(ctx, Batched::<LANES>::from(mask));
+                let fill = add_batched_literal(ctx, Batched::<LANES>::from(fill));
+
+                let n = ctx.add_multiplication_plaintext(shifted.ids[0], mask);
+                let n = ctx.add_addition_plaintext(n, fill);
+
+                FheProgramNode::new(&[n])
+            });
+
+            result = result * shifted;
+            d <<= 1;
+        }
+
+        result
+    }
+
+    /**
+     * Moves lane `index` of each row to lane 0 and zeroes every other
+     * lane, so the result decrypts to just that value.
+     *
+     * # Remarks
+     * This costs one rotation (none if `index` is 0) and one plaintext
+     * multiplication.
+     *
+     * # Panics
+     * Panics if `index` is not less than `LANES`.
+     */
+    pub fn extract_slot(self, index: usize) -> Self {
+        assert!(index < LANES, "index must be less than the number of lanes");
+
+        let rotated = if index == 0 {
+            self
+        } else {
+            self << index as u64
+        };
+
+        let mut mask = [[0; LANES]; 2];
+        mask[0][0] = 1;
+        mask[1][0] = 1;
+
+        with_fhe_ctx(|ctx| {
+            let mask = add_batched_literal(ctx, Batched::<LANES>::from(mask));
+            let n = ctx.add_multiplication_plaintext(rotated.ids[0], mask);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+
+    /**
+     * Turns an encrypted index into a one-hot vector: lane `j` of each row
+     * is `1` if `j` equals the index and `0` otherwise.
+     *
+     * # Remarks
+     * Every lane of `self` must hold the same index in `0..num_slots`;
+     * any other value produces garbage. Lanes at or above `num_slots` are
+     * always `0`. This subtracts the plaintext vector `[0, 1, 2, ...]`
+     * and evaluates the zero test `[x == 0]` on each lane as the
+     * interpolating polynomial over `-(num_slots - 1)..=(num_slots - 1)`,
+     * so, like [`ge_const`](Self::ge_const), it requires a prime
+     * plaintext modulus. Multiplying the result by a table gives an
+     * oblivious lookup or scatter.
+     *
+     * # Performance
+     * The polynomial has degree `2 * (num_slots - 1)`, costing that many
+     * ciphertext multiplications at depth
+     * `ceil(log2(2 * (num_slots - 1)))`, plus one plaintext
+     * multiplication to clear the unused lanes. A single slot costs just
+     * one plaintext addition.
+     *
+     * # Panics
+     * Panics if `num_slots` is 0 or exceeds `LANES`, or if the plaintext
+     * modulus isn't larger than `2 * num_slots - 1`.
+     */
+    pub fn one_hot(self, num_slots: usize) -> Self {
+        assert!(num_slots > 0, "num_slots must be positive");
+        assert!(
+            num_slots <= LANES,
+            "num_slots must not exceed the number of lanes"
+        );
+
+        // The only valid index is 0, so every lane of self is 0 and adding
+        // the answer to it gives the answer. Evaluating the zero test
+        // would instead yield a constant, which as a ciphertext is
+        // transparent.
+        if num_slots == 1 {
+            return with_fhe_ctx(|ctx| {
+                let hot = add_batched_literal(ctx, Batched::<LANES>::from([lane_mask(0..1); 2]));
+                let n = ctx.add_addition_plaintext(self.ids[0], hot);
+
+                FheProgramNode::new(&[n])
+            });
+        }
+
+        let mut indices = [[0; LANES]; 2];
+        let mut mask = [[0; LANES]; 2];
+
+        for (i, m) in indices.iter_mut().zip(mask.iter_mut()) {
+            for (j, (i_j, m_j)) in i.iter_mut().zip(m.iter_mut()).take(num_slots).enumerate() {
+                *i_j = -(j as i64);
+                *m_j = 1;
+            }
+        }
+
+        let diff = with_fhe_ctx(|ctx| {
+            let indices = add_batched_literal(ctx, Batched::<LANES>::from(indices));
+            let n = ctx.add_addition_plaintext(self.ids[0], indices);
+
+            Self::new(&[n])
+        });
+
+        let max_diff = num_slots as i64 - 1;
+        let is_zero = diff.eval_on_range(-max_diff..=max_diff, |x| (x == 0) as i64);
+
+        with_fhe_ctx(|ctx| {
+            let mask = add_batched_literal(ctx, Batched::<LANES>::from(mask));
+            let n = ctx.add_multiplication_plaintext(is_zero.ids[0], mask);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+
+    /**
+     * Looks up an encrypted index in a public table, returning
+     * `table[row][index]` in every lane of each row.
+     *
+     * # Remarks
+     * Pass the table as a plaintext [`Batched`] argument (i.e. without
+     * [`Cipher`]) holding one entry per lane; it's encoded into a single
+     * plaintext, so it costs neither encryption nor noise. Each row is a
+     * separate table. Every lane of `self` must hold the same index in
+     * `0..num_entries`, as for [`one_hot`](Self::one_hot).
+     *
+     * This multiplies the one-hot encoding of the index by the table and
+     * sums each row with rotations, so no lane reveals which entry was
+     * chosen. The table must not be entirely zero: multiplying by a zero
+     * plaintext makes a transparent ciphertext, which SEAL rejects at run
+     * time.
+     *
+     * # Performance
+     * Costs [`one_hot`](Self::one_hot) plus one plaintext multiplication
+     * and `log2(LANES)` rotations and additions.
+     *
+     * # Panics
+     * Panics under the same conditions as [`one_hot`](Self::one_hot).
+     */
+    pub fn lookup(self, table: FheProgramNode<Batched<LANES>>, num_entries: usize) -> Self {
+        let mut result = self.one_hot(num_entries) * table;
+        let mut d = 1;
+
+        while d < LANES {
+            result = result + (result << d as u64);
+            d <<= 1;
+        }
+
+        result
+    }
+
+    /**
+     * Keeps the first `current_len` lanes of each row and replaces the rest
+     * with `fill`, so a vector of `current_len` elements can feed
+     * algorithms that need a power-of-two length.
+     *
+     * # Remarks
+     * Rotations wrap around the whole row, so unused lanes leak into
+     * reductions, FFTs, and sorting networks. Choose `fill` as the
+     * operation's identity (e.g. `0` for sums, `1` for products). Every
+     * lane from `current_len` onward is filled, which covers the next
+     * power of two up to the full row.
+     *
+     * This costs one plaintext multiplication and one plaintext addition,
+     * or nothing if `current_len` is `LANES`.
+     *
+     * # Panics
+     * Panics if `current_len` is 0 or exceeds `LANES`. With no lanes
+     * kept, the result would be the constant `fill`, and masking every
+     * lane out makes a transparent ciphertext, which SEAL rejects.
+     */
+    pub fn pad_to_pow2(self, current_len: usize, fill: i64) -> Self {
+        assert!(current_len > 0, "current_len must be positive");
+        assert!(
+            current_len <= LANES,
+            "current_len must not exceed the number of lanes"
+        );
+
+        if current_len == LANES {
+            return self;
+        }
+
+        let mut mask = [[0; LANES]; 2];
+        let mut padding = [[fill; LANES]; 2];
+
+        for (m, p) in mask.iter_mut().zip(padding.iter_mut()) {
+            m[..current_len].fill(1);
+            p[..current_len].fill(0);
+        }
+
+        with_fhe_ctx(|ctx| {
+            let mask = add_batched_literal(ctx, Batched::<LANES>::from(mask));
+            let padding = add_batched_literal(ctx, Batched::<LANES>::from(padding));
+
+            let n = ctx.add_multiplication_plaintext(self.ids[0], mask);
+            let n = ctx.add_addition_plaintext(n, padding);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+
+    /**
+     * Permutes the first `slots` lanes of each row into bit-reversed
+     * order, as used by radix-2 FFT and NTT algorithms. Lane `i` of the
+     * result holds lane `reverse_bits(i)` of `self`, where the reversal is
+     * over `log2(slots)` bits. Lanes at or above `slots` are unchanged.
+     *
+     * # Remarks
+     * Lanes that move the same distance are gathered together: for each
+     * distinct distance `s`, this rotates `self` right by `s` and keeps
+     * the matching lanes with a plaintext mask. This costs at most
+     * `slots` rotations and plaintext multiplications and no ciphertext
+     * multiplications.
+     *
+     * # Panics
+     * Panics if `slots` is not a power of two or exceeds `LANES`.
+     */
+    pub fn bit_reverse(self, slots: usize) -> Self {
+        assert!(
+            slots.is_power_of_two() && slots <= LANES,
+            "slots must be a power of two no greater than the number of lanes"
+        );
+
+        let bits = slots.trailing_zeros();
+
+        // Lane i takes its value from lane rev(i), which sits
+        // (i - rev(i)) mod LANES lanes before it (cyclically).
+        let mut masks: Vec<(usize, [i64; LANES])> = vec![];
+
+        for i in 0..LANES {
+            let src = if i < slots && bits > 0 {
+                i.reverse_bits() >> (usize::BITS - bits)
+            } else {
+                i
+            };
+
+            let shift = (i + LANES - src) % LANES;
+
+            match masks.iter_mut().find(|(s, _)| *s == shift) {
+                Some((_, mask)) => mask[i] = 1,
+                None => {
+                    let mut mask = [0; LANES];
+                    mask[i] = 1;
+                    masks.push((shift, mask));
+                }
+            }
+        }
+
+        masks
+            .into_iter()
+            .map(|(shift, mask)| {
+                let rotated = if shift == 0 {
+                    self
+                } else {
+                    self >> shift as u64
+                };
+
+                with_fhe_ctx(|ctx| {
+                    let mask = add_batched_literal(ctx, Batched::<LANES>::from([mask; 2]));
+                    let n = ctx.add_multiplication_plaintext(rotated.ids[0], mask);
+
+                    FheProgramNode::new(&[n])
+                })
+            })
+            .reduce(|acc, x| acc + x)
+            .unwrap()
+    }
+
+    /**
+     * Returns the permutation that stably sorts the first `n` lanes of
+     * each row in ascending order: lane `r` of the result holds the index
+     * of the element that belongs at position `r`. Lanes at or above `n`
+     * are zeroed.
+     *
+     * # Remarks
+     * Every one of the first `n` lanes must lie in `range`; other values
+     * produce garbage. Rather than route values through a sorting
+     * network, this compares every pair of elements at once to find each
+     * element's rank (the number of elements that sort before it, with
+     * ties broken by index) and then scatters each index to the lane
+     * matching its rank. Each comparison and rank match is a polynomial
+     * evaluated as in [`ge_const`](Self::ge_const), so this also requires
+     * a prime plaintext modulus.
+     *
+     * # Performance
+     * This is very expensive. With `w = range.end() - range.start()`, it
+     * costs about `4 * w * (n - 1) + 2 * n^2` ciphertext multiplications
+     * and `4 * n` rotations, at multiplicative depth
+     * `ceil(log2(2 * w)) + ceil(log2(2 * n - 2))`. Keep both `n` and
+     * `range` as small as the data allows.
+     *
+     * # Panics
+     * Panics if `n` is less than 2 or exceeds `LANES`, if `range` has
+     * fewer than 2 values, or if `2 * w + 1` or `2 * n - 1` is not less
+     * than the plaintext modulus. In the excluded cases the permutation is
+     * known without looking at the data (a single element, or elements
+     * that must all be equal, sort to the identity), and computing a
+     * constant from a ciphertext makes it transparent, which SEAL rejects.
+     */
+    pub fn argsort(self, n: usize, range: RangeInclusive<i64>) -> Self {
+        assert!(
+            (2..=LANES).contains(&n),
+            "n must be at least 2 and no greater than the number of lanes"
+        );
+        assert!(
+            range.start() < range.end(),
+            "range must contain at least 2 values"
+        );
+
+        let width = range.end() - range.start();
+
+        // rank_i = #{j : x_j < x_i} + #{j < i : x_j == x_i}. Comparing
+        // against the element k places further on (cyclically), lanes
+        // from n - k onward see an element with a smaller index.
+        let rank = (1..n)
+            .map(|k| {
+                let d = self.rotate_within(n, k) - self;
+
+                let lt = d.eval_on_range(-width..=width, |d| (d < 0) as i64);
+                let eq = d.eval_on_range(-width..=width, |d| (d == 0) as i64);
+
+                lt + eq.mask(lane_mask(n - k..n))
+            })
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+
+        let mut positions = [0; LANES];
+
+        for (r, p) in positions.iter_mut().take(n).enumerate() {
+            *p = r as i64;
+        }
+
+        let max_offset = n as i64 - 1;
+
+        // Lane r collects index (r + k) mod n whenever that element's
+        // rank is r.
+        (0..n)
+            .map(|k| {
+                let shifted = rank.rotate_within(n, k);
+
+                let offset = with_fhe_ctx(|ctx| {
+                    let positions =
+                        add_batched_literal(ctx, Batched::<LANES>::from([positions; 2]));
+                    let o = ctx.add_subtraction_plaintext(shifted.ids[0], positions);
+
+                    FheProgramNode::new(&[o])
+                });
+
+                let hit = offset.eval_on_range(-max_offset..=max_offset, |o| (o == 0) as i64);
+
+                let mut indices = [0; LANES];
+
+                for (r, i) in indices.iter_mut().take(n).enumerate() {
+                    *i = ((r + k) % n) as i64;
+                }
+
+                hit.mask(indices)
+            })
+            .reduce(|acc, x| acc + x)
+            .unwrap()
+    }
+
+    /**
+     * Converts from slot encoding to coefficient encoding: the result
+     * encrypts the polynomial whose coefficients are this value's slots,
+     * in order from row 0's first lane to row 1's last. [`to_slots`](Self::to_slots)
+     * converts back.
+     *
+     * # Remarks
+     * Slot encoding makes lane-wise arithmetic and rotations cheap, while
+     * coefficient encoding is what scalar types such as
+     * [`Signed`](crate::types::bfv::Signed) and
+     * [`Fractional`](crate::types::bfv::Fractional) use. The result is
+     * still typed as `Batched`, but its lanes decrypt to the transform of
+     * the coefficients rather than meaningful values.
+     *
+     * The conversion covers all `n / 2` lanes of each row, where `n` is
+     * the lattice dimension, so values repeated beyond `LANES` become
+     * repeated coefficients.
+     *
+     * # Performance
+     * This is a homomorphic linear transform over every slot, and is very
+     * expensive: `n` plaintext multiplications, each by a distinct dense
+     * plaintext literal of `n` coefficients (512 MiB of literals when `n`
+     * is 8192), plus about `3 * sqrt(n / 2)` rotations. It adds no
+     * ciphertext multiplications, but the dense plaintexts consume roughly
+     * `log2(n) + log2(p)` bits of noise budget, where `p` is the plain
+     * modulus, which may force the compiler to choose a larger lattice
+     * dimension.
+     *
+     * Requires a plain modulus that supports batching, as every `Batched`
+     * value does.
+     *
+     * # Panics
+     * Panics if the FHE program's parameters don't support batching.
+     */
+    pub fn to_coeffs(self) -> Self {
+        let roots = with_fhe_ctx(|ctx| {
+            let params = &ctx.data.params;
+            let encoder = make_encoder(params).expect("parameters must support batching");
+
+            SlotRoots::new(&encoder, params)
+        });
+
+        // Slot i of a polynomial with coefficients x is sum_j root_i^j x_j.
+        slot_linear_transform(self, |i, j| roots.pow(i, j))
+    }
+
+    /**
+     * Converts from coefficient encoding to slot encoding, inverting
+     * [`to_coeffs`](Self::to_coeffs): the result's slots, in order from
+     * row 0's first lane to row 1's last, are the coefficients of the
+     * polynomial this value encrypts.
+     *
+     * # Remarks
+     * See [`to_coeffs`](Self::to_coeffs) for the cost, which is the same.
+     *
+     * # Panics
+     * Panics if the FHE program's parameters don't support batching.
+     */
+    pub fn to_slots(self) -> Self {
+        let (roots, n_inv, p) = with_fhe_ctx(|ctx| {
+            let params = &ctx.data.params;
+            let encoder = make_encoder(params).expect("parameters must support batching");
+            let roots = SlotRoots::new(&encoder, params);
+
+            (
+                roots,
+                inverse_mod(params.lattice_dimension, params.plain_modulus),
+                params.plain_modulus,
+            )
+        });
+
+        // The inverse of the map in to_coeffs, since the roots' powers are
+        // orthogonal: sum_j root_j^-i root_j^k = n [i == k].
+        slot_linear_transform(self, |i, j| {
+            ((roots.pow_inv(j, i) as u128 * n_inv as u128) % p as u128) as u64
+        })
+    }
+
+    /**
+     * Multiplies both rows lane-wise by the public `mask`.
+     */
+    fn mask(self, mask: [i64; LANES]) -> Self {
+        with_fhe_ctx(|ctx| {
+            let mask = add_batched_literal(ctx, Batched::<LANES>::from([mask; 2]));
+            let n = ctx.add_multiplication_plaintext(self.ids[0], mask);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+
+    /**
+     * Rotates the first `n` lanes of each row left by `k` places, wrapping
+     * within those lanes, and zeroes the rest.
+     */
+    fn rotate_within(self, n: usize, k: usize) -> Self {
+        if k == 0 {
+            return self.mask(lane_mask(0..n));
+        }
+
+        let head = (self << k as u64).mask(lane_mask(0..n - k));
+        let tail = (self >> (n - k) as u64).mask(lane_mask(n - k..n));
+
+        head + tail
+    }
+}
+
+/**
+ * Returns a mask that is `1` in `lanes` and `0` elsewhere.
+ */
+fn lane_mask<const LANES: usize>(lanes: Range<usize>) -> [i64; LANES] {
+    let mut mask = [0; LANES];
+    mask[lanes].fill(1);
+
+    mask
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -658,6 +1754,110 @@ mod tests {
         assert_eq!(x, y);
     }
 
+    #[test]
+    fn slot_roots_match_batch_decoding() {
+        let params = Params {
+            lattice_dimension: 4096,
+            plain_modulus: PlainModulus::batching(4096, 16).unwrap().value(),
+            coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|x| x.value())
+                .collect::<Vec<u64>>(),
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        };
+
+        let p = params.plain_modulus as u128;
+        let encoder = make_encoder(&params).unwrap();
+        let roots = SlotRoots::new(&encoder, &params);
+
+        let coeffs = [3u64, 1, 4, 1, 5, 9, 2, 6];
+
+        let mut plaintext = SealPlaintext::new().unwrap();
+        plaintext.resize(coeffs.len());
+
+        for (j, c) in coeffs.iter().enumerate() {
+            plaintext.set_coefficient(j, *c);
+        }
+
+        let slots = encoder.decode_unsigned(&plaintext).unwrap();
+
+        for i in [0, 1, 7, 2047, 2048, 4095] {
+            let expected = coeffs.iter().enumerate().fold(0, |acc, (j, c)| {
+                (acc + *c as u128 * roots.pow(i, j) as u128) % p
+            });
+
+            assert_eq!(slots[i] as u128, expected);
+            assert_eq!(roots.pow(i, 3) as u128 * roots.pow_inv(i, 3) as u128 % p, 1);
+        }
+    }
+
+    #[test]
+    fn even_polynomials_skip_zero_terms() {
+        use crate::fhe::{FheData, FheOperation, CURRENT_FHE_CTX};
+        use crate::types::intern::Input;
+        use std::cell::RefCell;
+        use std::mem::transmute;
+
+        let params = Params {
+            lattice_dimension: 4096,
+            plain_modulus: PlainModulus::batching(4096, 16).unwrap().value(),
+            coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|x| x.value())
+                .collect::<Vec<u64>>(),
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        };
+
+        let domain = (-2..=2).collect::<Vec<i64>>();
+        let values = domain.iter().map(|x| (*x == 0) as i64).collect::<Vec<_>>();
+        let coeffs = interpolate_mod(&domain, &values, params.plain_modulus);
+
+        // The zero test is even, so its odd coefficients vanish.
+        assert_eq!(coeffs[1], 0);
+        assert_eq!(coeffs[3], 0);
+
+        CURRENT_FHE_CTX.with(|ctx| {
+            let mut context = FheContext::new(FheData::new(params.clone()));
+
+            ctx.swap(&RefCell::new(Some(unsafe { transmute(&mut context) })));
+
+            let x = FheProgramNode::<Cipher<Batched<4>>>::input();
+            x.eval_on_range(-2..=2, |x| (x == 0) as i64);
+
+            ctx.swap(&RefCell::new(None));
+
+            // Multiplying by a zero coefficient would make a transparent
+            // ciphertext, so only the x^2 and x^4 terms get one.
+            let scalings = context
+                .graph
+                .node_weights()
+                .filter(|n| n.operation == FheOperation::MultiplyPlaintext)
+                .count();
+
+            assert_eq!(scalings, 2);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "current_len must be positive")]
+    fn pad_to_pow2_rejects_empty_vectors() {
+        let a = FheProgramNode::<Cipher<Batched<4>>>::new(&[NodeIndex::from(0)]);
+
+        a.pad_to_pow2(0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "n must be at least 2")]
+    fn argsort_rejects_single_element() {
+        let a = FheProgramNode::<Cipher<Batched<4>>>::new(&[NodeIndex::from(0)]);
+
+        a.argsort(1, 0..=3);
+    }
+
     const A_VEC: [[i64; 4]; 2] = [[1, 2, 3, 4], [5, 6, 7, 8]];
     const B_VEC: [[i64; 4]; 2] = [[5, 6, 7, 8], [1, 2, 3, 4]];
 
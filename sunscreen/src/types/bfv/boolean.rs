@@ -0,0 +1,361 @@
+use seal_fhe::Plaintext as SealPlaintext;
+
+use crate as sunscreen;
+use crate::{
+    fhe::{with_fhe_ctx, FheContext, FheContextOps},
+    types::{
+        intern::FheProgramNode,
+        ops::{GraphCipherMul, GraphCipherSub, GraphPlainCipherSub},
+        BfvType, Cipher, FheType, TypeNameInstance,
+    },
+    FheProgramInputTrait, Params, TypeName as DeriveTypeName, WithContext,
+};
+
+use petgraph::stable_graph::NodeIndex;
+use sunscreen_runtime::{
+    InnerPlaintext, NumCiphertexts, Plaintext, TryFromPlaintext, TryIntoPlaintext,
+};
+
+use std::ops::{BitAnd, BitOr, Not};
+
+#[derive(Debug, Clone, Copy, DeriveTypeName, PartialEq, Eq, Default)]
+/**
+ * A single boolean value.
+ *
+ * # Remarks
+ * Encodes `false` and `true` as `0` and `1` in the plaintext's constant
+ * coefficient, so logic operators are arithmetic on that coefficient:
+ *
+ * | Operator | Computes          | Ciphertext multiplications | Depth |
+ * |----------|-------------------|----------------------------|-------|
+ * | `!a`     | `1 - a`           | 0                          | 0     |
+ * | `a & b`  | `a * b`           | 1                          | 1     |
+ * | `a \| b` | `a + b - a * b`   | 1                          | 1     |
+ *
+ * `a | b` is De Morgan's `!(!a & !b)`, expanded so it needs only the one
+ * multiplication. Depth accumulates along chains of `&` and `|`, so
+ * combine many values as a balanced tree rather than a chain.
+ */
+pub struct Boolean {
+    val: bool,
+}
+
+impl NumCiphertexts for Boolean {
+    const NUM_CIPHERTEXTS: usize = 1;
+}
+
+impl FheProgramInputTrait for Boolean {}
+impl FheType for Boolean {}
+impl BfvType for Boolean {}
+
+impl std::fmt::Display for Boolean {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.val)
+    }
+}
+
+impl TryIntoPlaintext for Boolean {
+    fn try_into_plaintext(
+        &self,
+        params: &Params,
+    ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
+        Self::check_plain_modulus(params)?;
+
+        let mut seal_plaintext = SealPlaintext::new()?;
+
+        seal_plaintext
+            .try_resize(1)
+            .map_err(|e| sunscreen_runtime::Error::plaintext_resize_failed(1, e))?;
+
+        seal_plaintext
+            .try_set_coefficient(0, self.val as u64)
+            .map_err(|e| sunscreen_runtime::Error::plaintext_set_coefficient_failed(0, e))?;
+
+        Ok(Plaintext {
+            data_type: self.type_name_instance(),
+            inner: InnerPlaintext::Seal(vec![WithContext {
+                params: params.clone(),
+                data: seal_plaintext,
+            }]),
+        })
+    }
+}
+
+impl TryFromPlaintext for Boolean {
+    fn try_from_plaintext(
+        plaintext: &Plaintext,
+        _params: &Params,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        let val = match &plaintext.inner {
+            InnerPlaintext::Seal(p) => {
+                if p.len() != 1 {
+                    return Err(sunscreen_runtime::Error::IncorrectCiphertextCount);
+                }
+
+                let p = &p[0];
+
+                // Anything other than 0 or 1 means a non-boolean value was
+                // fed to a logic operator.
+                if (1..p.len()).any(|i| p.get_coefficient(i) != 0) {
+                    return Err(sunscreen_runtime::Error::fhe_type_error(
+                        "Boolean plaintext has nonzero higher coefficients",
+                    ));
+                }
+
+                match p.len() {
+                    0 => false,
+                    _ => match p.get_coefficient(0) {
+                        0 => false,
+                        1 => true,
+                        _ => {
+                            return Err(sunscreen_runtime::Error::fhe_type_error(
+                                "Boolean plaintext is neither 0 nor 1",
+                            ))
+                        }
+                    },
+                }
+            }
+        };
+
+        Ok(Self { val })
+    }
+}
+
+impl From<bool> for Boolean {
+    fn from(val: bool) -> Self {
+        Self { val }
+    }
+}
+
+impl From<Boolean> for bool {
+    fn from(b: Boolean) -> Self {
+        b.val
+    }
+}
+
+impl BitAnd for Boolean {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self::from(self.val & rhs.val)
+    }
+}
+
+impl BitOr for Boolean {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self::from(self.val | rhs.val)
+    }
+}
+
+impl Not for Boolean {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self::from(!self.val)
+    }
+}
+
+/**
+ * Adds the plaintext literal `true` to the current FHE program.
+ */
+fn add_one_literal(ctx: &mut FheContext) -> NodeIndex {
+    let one = Boolean::from(true)
+        .try_into_plaintext(&ctx.data.params)
+        .unwrap();
+
+    ctx.add_plaintext_literal(one.inner)
+}
+
+impl GraphCipherMul for Boolean {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_cipher_mul(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_multiplication(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl GraphCipherSub for Boolean {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_cipher_sub(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_subtraction(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl GraphPlainCipherSub for Boolean {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_plain_cipher_sub(
+        a: FheProgramNode<Self::Left>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let neg = ctx.add_negate(b.ids[0]);
+            let n = ctx.add_addition_plaintext(neg, a.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl BitAnd for FheProgramNode<Cipher<Boolean>> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Boolean::graph_cipher_mul(self, rhs)
+    }
+}
+
+impl BitOr for FheProgramNode<Cipher<Boolean>> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        // a - (a * b - b) == a + b - a * b
+        let product = Boolean::graph_cipher_mul(self, rhs);
+
+        Boolean::graph_cipher_sub(self, Boolean::graph_cipher_sub(product, rhs))
+    }
+}
+
+impl Not for FheProgramNode<Cipher<Boolean>> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        let one = with_fhe_ctx(|ctx| FheProgramNode::new(&[add_one_literal(ctx)]));
+
+        Boolean::graph_plain_cipher_sub(one, self)
+    }
+}
+
+/**
+ * Adds a plaintext literal encoding `c` in the constant coefficient.
+ */
+fn add_scalar_literal(ctx: &mut FheContext, c: u64) -> NodeIndex {
+    let mut plaintext = SealPlaintext::new().unwrap();
+    plaintext.resize(1);
+    plaintext.set_coefficient(0, c);
+
+    ctx.add_plaintext_literal(InnerPlaintext::Seal(vec![WithContext {
+        params: ctx.data.params.clone(),
+        data: plaintext,
+    }]))
+}
+
+impl FheProgramNode<Cipher<Boolean>> {
+    /**
+     * Returns `true` iff more than half of `bits` are `true`.
+     *
+     * # Remarks
+     * Sums the bits, then evaluates the unique polynomial of degree `n`
+     * that maps each possible count `0..=n` to whether it exceeds `n / 2`.
+     * The sum is free, so the cost is the polynomial: about `n`
+     * ciphertext multiplications at depth `ceil(log2(n))`.
+     *
+     * The interpolation requires a prime plaintext modulus larger than
+     * `n`.
+     *
+     * # Panics
+     * Panics if `bits` is empty or the plaintext modulus isn't larger
+     * than `bits.len()`.
+     */
+    pub fn majority(bits: &[Self]) -> Self {
+        let n = bits.len();
+        let p = Self::get_plain_modulus();
+
+        assert!(n > 0, "majority requires at least one bit");
+        assert!(
+            (n as u64) < p,
+            "majority requires a plaintext modulus larger than the number of bits"
+        );
+
+        if n == 1 {
+            return bits[0];
+        }
+
+        let domain = (0..=n as i64).collect::<Vec<_>>();
+        let values = domain
+            .iter()
+            .map(|k| (*k > n as i64 / 2) as i64)
+            .collect::<Vec<_>>();
+
+        let coeffs = super::batched::interpolate_mod(&domain, &values, p);
+
+        with_fhe_ctx(|ctx| {
+            let sum = bits[1..]
+                .iter()
+                .fold(bits[0].ids[0], |acc, b| ctx.add_addition(acc, b.ids[0]));
+
+            // powers[k - 1] = sum^k, built as in Batched's polynomial
+            // evaluation so every power has depth ceil(log2(k)).
+            let mut powers = vec![sum];
+
+            for k in 2..coeffs.len() {
+                let hi = 1 << (usize::BITS - 1 - k.leading_zeros());
+
+                let power = if hi == k {
+                    ctx.add_multiplication(powers[hi / 2 - 1], powers[hi / 2 - 1])
+                } else {
+                    ctx.add_multiplication(powers[hi - 1], powers[k - hi - 1])
+                };
+
+                powers.push(power);
+            }
+
+            // No bits set is never a majority, so the constant term is 0.
+            let terms = coeffs
+                .iter()
+                .skip(1)
+                .zip(powers.iter())
+                .filter(|(c, _)| **c != 0)
+                .map(|(c, x_k)| {
+                    let c = add_scalar_literal(ctx, *c);
+
+                    ctx.add_multiplication_plaintext(*x_k, c)
+                })
+                .collect::<Vec<_>>();
+
+            let n = terms[1..]
+                .iter()
+                .fold(terms[0], |acc, t| ctx.add_addition(acc, *t));
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_apply_logic_non_fhe() {
+        let t = Boolean::from(true);
+        let f = Boolean::from(false);
+
+        assert_eq!(t & f, f);
+        assert_eq!(t & t, t);
+        assert_eq!(t | f, t);
+        assert_eq!(f | f, f);
+        assert_eq!(!t, f);
+        assert_eq!(!f, t);
+    }
+}
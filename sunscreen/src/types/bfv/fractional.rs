@@ -1,7 +1,7 @@
 use seal_fhe::Plaintext as SealPlaintext;
 
 use crate::{
-    fhe::{with_fhe_ctx, FheContextOps},
+    fhe::{with_fhe_ctx, FheContext, FheContextOps},
     types::{
         ops::{
             GraphCipherAdd, GraphCipherConstAdd, GraphCipherConstDiv, GraphCipherConstMul,
@@ -22,9 +22,12 @@ use sunscreen_runtime::{
     TypeNameInstance,
 };
 
+use log::warn;
+use petgraph::stable_graph::NodeIndex;
+
 use std::ops::*;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy)]
 /**
  * A quasi fixed-point representation capable of storing values with
  * both integer and fractional components.
@@ -104,8 +107,8 @@ use std::ops::*;
  *
  * ## Limitations
  * When encrypting a Fractional type, encoding will fail if:
- * * The underlying [`f64`] is infinite.
- * * The underlying [`f64`] is NaN
+ * * The underlying [`f64`] is infinite or NaN, with
+ * [`Error::InvalidFloatValue`](sunscreen_runtime::Error::InvalidFloatValue).
  * * The integer portion of the underlying [`f64`] exceeds the precision for
  * `INT_BITS`
  *
@@ -122,7 +125,9 @@ use std::ops::*;
  * portion exceeds `2^INT_BITS`. Finally, repeated multiplications of
  * numbers with decimal components introduce new decmal digits. If more than
  * `2^(n-INT_BITS)` decimals appear, they will overflow into the integer
- * portion and garble the number.
+ * portion and garble the number. While building an FHE program, the
+ * compiler estimates each value's decimal places (assuming 53 for inputs)
+ * and logs a warning at the first multiplication likely to overflow.
  *
  * To mitigate these issues, you should do some mix of the following:
  * * Ensure inputs never result in either of these scenarios. Inputs to a
@@ -160,6 +165,20 @@ use std::ops::*;
  */
 pub struct Fractional<const INT_BITS: usize> {
     val: f64,
+
+    /**
+     * The exact value, when constructed with [`Fractional::from_i64`].
+     */
+    int: Option<i64>,
+}
+
+impl<const INT_BITS: usize> PartialEq for Fractional<INT_BITS> {
+    fn eq(&self, other: &Self) -> bool {
+        // Comparing the exact integers only when both sides have one
+        // would make equality intransitive, as distinct integers can
+        // round to the same f64.
+        self.val == other.val
+    }
 }
 
 impl<const INT_BITS: usize> std::ops::Deref for Fractional<INT_BITS> {
@@ -202,7 +221,142 @@ impl<const INT_BITS: usize> TypeNameInstance for Fractional<INT_BITS> {
 impl<const INT_BITS: usize> FheType for Fractional<INT_BITS> {}
 impl<const INT_BITS: usize> BfvType for Fractional<INT_BITS> {}
 
-impl<const INT_BITS: usize> Fractional<INT_BITS> {}
+impl<const INT_BITS: usize> Fractional<INT_BITS> {
+    /**
+     * Creates a [`Fractional`] holding the integer `val` exactly.
+     *
+     * # Remarks
+     * Converting an integer to [`f64`] to call [`Fractional::from`] rounds
+     * it to 53 significant bits, so large integers lose their low digits
+     * before they're ever encoded. A value built here instead encodes
+     * each binary digit of `val` directly into the integer coefficients,
+     * which is exact for any `|val| < 2^INT_BITS`.
+     *
+     * Dereferencing the result, converting it to [`f64`], and comparing
+     * it for equality all use the rounded value. Arithmetic on
+     * unencrypted values goes through [`f64`] and so discards the exact
+     * integer. Likewise, decrypting yields a [`Fractional`] with no exact
+     * integer, so a round trip through encryption is only exact for
+     * `|val| <= 2^53`.
+     */
+    pub fn from_i64(val: i64) -> Self {
+        Self {
+            val: val as f64,
+            int: Some(val),
+        }
+    }
+
+    /**
+     * Creates a [`Fractional`] from `val`, rounding it to the nearest value
+     * representable under the given `params`.
+     *
+     * # Remarks
+     * Under a lattice dimension `n`, a [`Fractional`] holds `n - INT_BITS`
+     * fractional bits. Encoding a value with more precision than this simply
+     * drops the excess low-order bits, which truncates the magnitude towards
+     * zero and biases long computations. Constructing the value with this
+     * method instead rounds to the nearest multiple of `2^-(n - INT_BITS)`
+     * (ties away from zero) before encoding.
+     *
+     * Values that already fit in the encoding are returned unchanged.
+     *
+     * In practice, this only matters for small lattice dimensions or tiny
+     * values. An `f64` carries 53 significant bits, so under the smallest
+     * lattice dimension BFV supports (1024) and `INT_BITS = 64`, only values
+     * with magnitude below roughly `2^-907` have bits past the 960 available
+     * fractional bits. Every other value is already representable.
+     */
+    pub fn from_rounded(val: f64, params: &Params) -> Self {
+        let frac_bits = params.lattice_dimension as i64 - INT_BITS as i64;
+
+        // The scale factor would overflow. Only values near f64::MIN_POSITIVE
+        // carry bits this small, so leave them be.
+        if !val.is_finite() || frac_bits > f64::MAX_EXP as i64 {
+            return Self::from(val);
+        }
+
+        let scale = (frac_bits as f64).exp2();
+        let scaled = val * scale;
+
+        // Once the scaled value needs no bits below the binary point, it's
+        // already exactly representable.
+        if !scaled.is_finite() || scaled.abs() >= (f64::MANTISSA_DIGITS as f64).exp2() {
+            return Self::from(val);
+        }
+
+        Self::from(scaled.round() / scale)
+    }
+
+    /**
+     * Restricts the value to the interval `[min, max]`.
+     *
+     * # Remarks
+     * This only operates on unencrypted values. BFV offers no comparison
+     * primitive over this carryless encoding, so there is no way to clamp a
+     * [`Cipher<Fractional>`](crate::types::Cipher) inside an FHE program.
+     * Instead, clamp values before encrypting them, or decrypt, clamp, and
+     * re-encrypt as described in the type-level documentation. To clamp
+     * inside an FHE program, encode fixed-point values as scaled integers
+     * in a [`Batched`](crate::types::bfv::Batched) and use its encrypted
+     * [`clamp`](crate::types::intern::FheProgramNode::clamp).
+     *
+     * # Panics
+     * Panics if `min > max`, `min` is NaN, or `max` is NaN.
+     */
+    pub fn clamp(self, min: f64, max: f64) -> Self {
+        Self::from(self.val.clamp(min, max))
+    }
+}
+
+/**
+ * Returns how many binary digits `x` has after the point.
+ */
+fn fractional_bits(x: f64) -> usize {
+    // Infinities and NaN fail to encode anyway.
+    if !x.is_finite() {
+        return 0;
+    }
+
+    // Doubling is exact, so this terminates after at most 1074 steps.
+    let mut x = x.fract();
+    let mut bits = 0;
+
+    while x != 0.0 {
+        x = (x * 2.0).fract();
+        bits += 1;
+    }
+
+    bits
+}
+
+/**
+ * Records `scale` as the estimated fractional bits of the [`Fractional`]
+ * node `n`, computed from operands with the given scales.
+ *
+ * # Remarks
+ * Each multiplication adds its operands' fractional bits, so chains
+ * eventually exceed the `lattice_dimension - INT_BITS` coefficients that
+ * hold the fraction and wrap into the integer part. This warns at the
+ * first node in a chain where that's estimated to happen.
+ */
+fn track_scale<const INT_BITS: usize>(
+    ctx: &mut FheContext,
+    n: NodeIndex,
+    scale: usize,
+    operands: &[usize],
+) {
+    let available = (ctx.data.params.lattice_dimension as usize).saturating_sub(INT_BITS);
+
+    if ctx.data.set_fractional_scale(n, scale, operands, available) {
+        warn!(
+            "Fractional<{}> node {} needs an estimated {} fractional bits, but only {} are available. Its decimal digits may overflow into the integer part.",
+            INT_BITS,
+            n.index(),
+            scale,
+            available
+        );
+    }
+}
 
 impl<const INT_BITS: usize> GraphCipherAdd for Fractional<INT_BITS> {
     type Left = Fractional<INT_BITS>;
@@ -215,6 +369,10 @@ impl<const INT_BITS: usize> GraphCipherAdd for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_addition(a.ids[0], b.ids[0]);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -231,6 +389,10 @@ impl<const INT_BITS: usize> GraphCipherPlainAdd for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_addition_plaintext(a.ids[0], b.ids[0]);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -245,11 +407,15 @@ impl<const INT_BITS: usize> GraphCipherConstAdd for Fractional<INT_BITS> {
         b: Self::Right,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b_scale = fractional_bits(b);
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(b.inner);
             let n = ctx.add_addition_plaintext(a.ids[0], lit);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -266,6 +432,10 @@ impl<const INT_BITS: usize> GraphCipherSub for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_subtraction(a.ids[0], b.ids[0]);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -282,6 +452,10 @@ impl<const INT_BITS: usize> GraphCipherPlainSub for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_subtraction_plaintext(a.ids[0], b.ids[0]);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -299,6 +473,10 @@ impl<const INT_BITS: usize> GraphPlainCipherSub for Fractional<INT_BITS> {
             let n = ctx.add_subtraction_plaintext(b.ids[0], a.ids[0]);
             let n = ctx.add_negate(n);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -313,11 +491,15 @@ impl<const INT_BITS: usize> GraphCipherConstSub for Fractional<INT_BITS> {
         b: Self::Right,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b_scale = fractional_bits(b);
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(b.inner);
             let n = ctx.add_subtraction_plaintext(a.ids[0], lit);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -332,12 +514,16 @@ impl<const INT_BITS: usize> GraphConstCipherSub for Fractional<INT_BITS> {
         b: FheProgramNode<Cipher<Self::Right>>,
     ) -> FheProgramNode<Cipher<Self::Right>> {
         with_fhe_ctx(|ctx| {
-            let a = Self::from(a).try_into_plaintext(&ctx.data).unwrap();
+            let a_scale = fractional_bits(a);
+            let a = Self::from(a).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(a.inner);
             let n = ctx.add_subtraction_plaintext(b.ids[0], lit);
             let n = ctx.add_negate(n);
 
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale.max(b_scale), &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -354,6 +540,10 @@ impl<const INT_BITS: usize> GraphCipherMul for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_multiplication(a.ids[0], b.ids[0]);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale + b_scale, &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -370,6 +560,10 @@ impl<const INT_BITS: usize> GraphCipherPlainMul for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_multiplication_plaintext(a.ids[0], b.ids[0]);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            let b_scale = ctx.data.fractional_scale(b.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale + b_scale, &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -384,11 +578,15 @@ impl<const INT_BITS: usize> GraphCipherConstMul for Fractional<INT_BITS> {
         b: Self::Right,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b_scale = fractional_bits(b);
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
             let lit = ctx.add_plaintext_literal(b.inner);
 
             let n = ctx.add_multiplication_plaintext(a.ids[0], lit);
 
+            let a_scale = ctx.data.fractional_scale(a.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, a_scale + b_scale, &[a_scale, b_scale]);
+
             FheProgramNode::new(&[n])
         })
     }
@@ -402,19 +600,131 @@ impl<const INT_BITS: usize> GraphCipherConstDiv for Fractional<INT_BITS> {
         a: FheProgramNode<Cipher<Self::Left>>,
         b: f64,
     ) -> FheProgramNode<Cipher<Self::Left>> {
+        a.try_div_const(b).unwrap()
+    }
+}
+
+impl<const INT_BITS: usize> FheProgramNode<Cipher<Fractional<INT_BITS>>> {
+    /**
+     * Divides by the public constant `divisor`.
+     *
+     * # Remarks
+     * Encodes `1 / divisor` as a plaintext and multiplies by it, so this
+     * costs one plaintext multiplication. The `/` operator does the same
+     * but panics where this returns an error.
+     *
+     * Returns [`Error::FheTypeError`](sunscreen_runtime::Error::FheTypeError)
+     * if `divisor` is zero or its reciprocal can't be encoded (e.g. it
+     * needs more than `INT_BITS` integer bits).
+     */
+    pub fn try_div_const(
+        self,
+        divisor: f64,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        if divisor == 0.0 {
+            return Err(sunscreen_runtime::Error::fhe_type_error(
+                "Division by zero.",
+            ));
+        }
+
         with_fhe_ctx(|ctx| {
-            let b = Self::try_from(1. / b)
-                .unwrap()
-                .try_into_plaintext(&ctx.data)
-                .unwrap();
+            let reciprocal_scale = fractional_bits(1. / divisor);
+            let reciprocal =
+                Fractional::<INT_BITS>::from(1. / divisor).try_into_plaintext(&ctx.data.params)?;
 
-            let lit = ctx.add_plaintext_literal(b.inner);
+            let lit = ctx.add_plaintext_literal(reciprocal.inner);
 
-            let n = ctx.add_multiplication_plaintext(a.ids[0], lit);
+            let n = ctx.add_multiplication_plaintext(self.ids[0], lit);
 
-            FheProgramNode::new(&[n])
+            let scale = ctx.data.fractional_scale(self.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, scale + reciprocal_scale, &[scale, reciprocal_scale]);
+
+            Ok(FheProgramNode::new(&[n]))
         })
     }
+
+    /**
+     * Multiplies by `2^k`.
+     *
+     * # Remarks
+     * Each coefficient of a [`Fractional`] holds one binary digit, so
+     * scaling by `2^k` shifts every digit `k` coefficients over. This
+     * multiplies by the plaintext monomial `x^k` to do that, which adds far
+     * less noise than a ciphertext multiplication. Negative `k` divides,
+     * since `x^-k = -x^(n - k)` in the negacyclic plaintext ring.
+     *
+     * Digits shifted past the top of the integer part wrap into the
+     * fraction and vice versa. Dividing counts towards the fractional bits
+     * a program tracks, but multiplying can't be checked under encryption,
+     * so scaled values must still fit in `INT_BITS` bits.
+     *
+     * Returns [`Error::FheTypeError`](sunscreen_runtime::Error::FheTypeError)
+     * if `2^k` itself doesn't fit in `INT_BITS` integer bits or the
+     * `lattice_dimension - INT_BITS` fractional bits.
+     */
+    pub fn try_scale_pow2(self, k: i32) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        with_fhe_ctx(|ctx| {
+            let params = ctx.data.params.clone();
+            let n = params.lattice_dimension as i64;
+            let k = k as i64;
+
+            if k >= INT_BITS as i64 {
+                return Err(sunscreen_runtime::Error::fhe_type_error(
+                    "Scale overflows the integer bits.",
+                ));
+            }
+
+            if k < INT_BITS as i64 - n {
+                return Err(sunscreen_runtime::Error::fhe_type_error(
+                    "Scale overflows the fractional bits.",
+                ));
+            }
+
+            let (index, coeff) = if k >= 0 {
+                (k as usize, 1)
+            } else {
+                ((n + k) as usize, params.plain_modulus - 1)
+            };
+
+            let mut monomial = SealPlaintext::new()?;
+            monomial
+                .try_resize(n as usize)
+                .map_err(|e| sunscreen_runtime::Error::plaintext_resize_failed(n as usize, e))?;
+            monomial.try_set_coefficient(index, coeff).map_err(|e| {
+                sunscreen_runtime::Error::plaintext_set_coefficient_failed(index, e)
+            })?;
+
+            let lit = ctx.add_plaintext_literal(InnerPlaintext::Seal(vec![WithContext {
+                params,
+                data: monomial,
+            }]));
+
+            let n = ctx.add_multiplication_plaintext(self.ids[0], lit);
+
+            let scale = ctx.data.fractional_scale(self.ids[0]);
+            let shift = k.unsigned_abs() as usize;
+            let new_scale = if k >= 0 {
+                scale.saturating_sub(shift)
+            } else {
+                scale + shift
+            };
+
+            track_scale::<INT_BITS>(ctx, n, new_scale, &[scale]);
+
+            Ok(FheProgramNode::new(&[n]))
+        })
+    }
+
+    /**
+     * Multiplies by `2^k`. See
+     * [`try_scale_pow2`](FheProgramNode::try_scale_pow2).
+     *
+     * # Panics
+     * If `2^k` doesn't fit in this type's integer or fractional bits.
+     */
+    pub fn scale_pow2(self, k: i32) -> Self {
+        self.try_scale_pow2(k).unwrap()
+    }
 }
 
 impl<const INT_BITS: usize> GraphCipherNeg for Fractional<INT_BITS> {
@@ -424,29 +734,47 @@ impl<const INT_BITS: usize> GraphCipherNeg for Fractional<INT_BITS> {
         with_fhe_ctx(|ctx| {
             let n = ctx.add_negate(a.ids[0]);
 
+            let scale = ctx.data.fractional_scale(a.ids[0]);
+            track_scale::<INT_BITS>(ctx, n, scale, &[scale]);
+
             FheProgramNode::new(&[n])
         })
     }
 }
 
 impl<const INT_BITS: usize> TryIntoPlaintext for Fractional<INT_BITS> {
+    fn min_plain_modulus(params: &Params) -> u64 {
+        // Digits use the same signed encoding.
+        crate::types::bfv::Signed::min_plain_modulus(params)
+    }
+
     fn try_into_plaintext(
         &self,
         params: &Params,
     ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
+        Self::check_plain_modulus(params)?;
+
+        if let Some(int) = self.int {
+            return self.int_into_plaintext(int, params);
+        }
+
         if self.val.is_nan() {
-            return Err(sunscreen_runtime::Error::fhe_type_error("Value is NaN."));
+            return Err(sunscreen_runtime::Error::invalid_float_value(
+                "Value is NaN.",
+            ));
         }
 
         if self.val.is_infinite() {
-            return Err(sunscreen_runtime::Error::fhe_type_error(
+            return Err(sunscreen_runtime::Error::invalid_float_value(
                 "Value is infinite.",
             ));
         }
 
         let mut seal_plaintext = SealPlaintext::new()?;
         let n = params.lattice_dimension as usize;
-        seal_plaintext.resize(n);
+        seal_plaintext
+            .try_resize(n)
+            .map_err(|e| sunscreen_runtime::Error::plaintext_resize_failed(n, e))?;
 
         // Just flush subnormals, as they're tiny and annoying.
         if self.val.is_subnormal() || self.val == 0.0 {
@@ -486,6 +814,12 @@ impl<const INT_BITS: usize> TryIntoPlaintext for Fractional<INT_BITS> {
             let bit_value = (mantissa & 0x1 << i) >> i;
             let bit_power = power - (f64::MANTISSA_DIGITS - i - 1) as i64;
 
+            // Bits below the fractional precision would otherwise wrap into
+            // the integer coefficients, so truncate them.
+            if bit_power < INT_BITS as i64 - n as i64 {
+                continue;
+            }
+
             let coeff_index = if bit_power >= 0 {
                 bit_power as usize
             } else {
@@ -503,7 +837,56 @@ impl<const INT_BITS: usize> TryIntoPlaintext for Fractional<INT_BITS> {
                 0
             };
 
-            seal_plaintext.set_coefficient(coeff_index, coeff);
+            seal_plaintext
+                .try_set_coefficient(coeff_index, coeff)
+                .map_err(|e| {
+                    sunscreen_runtime::Error::plaintext_set_coefficient_failed(coeff_index, e)
+                })?;
+        }
+
+        Ok(Plaintext {
+            data_type: self.type_name_instance(),
+            inner: InnerPlaintext::Seal(vec![WithContext {
+                params: params.clone(),
+                data: seal_plaintext,
+            }]),
+        })
+    }
+}
+
+impl<const INT_BITS: usize> Fractional<INT_BITS> {
+    /**
+     * Encodes the integer `int` by setting each of its binary digits
+     * directly in the integer coefficients.
+     */
+    fn int_into_plaintext(
+        &self,
+        int: i64,
+        params: &Params,
+    ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
+        let magnitude = int.unsigned_abs();
+
+        if INT_BITS < 64 && magnitude >> INT_BITS != 0 {
+            return Err(sunscreen_runtime::Error::fhe_type_error("Out of range"));
+        }
+
+        let mut seal_plaintext = SealPlaintext::new()?;
+        let n = params.lattice_dimension as usize;
+        seal_plaintext
+            .try_resize(n)
+            .map_err(|e| sunscreen_runtime::Error::plaintext_resize_failed(n, e))?;
+
+        // Negative values negate every digit.
+        let digit = if int < 0 { params.plain_modulus - 1 } else { 1 };
+
+        for i in 0..64 {
+            if magnitude & (0x1 << i) == 0 {
+                continue;
+            }
+
+            seal_plaintext
+                .try_set_coefficient(i, digit)
+                .map_err(|e| sunscreen_runtime::Error::plaintext_set_coefficient_failed(i, e))?;
         }
 
         Ok(Plaintext {
@@ -553,7 +936,7 @@ impl<const INT_BITS: usize> TryFromPlaintext for Fractional<INT_BITS> {
                     };
                 }
 
-                Self { val }
+                Self::from(val)
             }
         };
 
@@ -563,7 +946,7 @@ impl<const INT_BITS: usize> TryFromPlaintext for Fractional<INT_BITS> {
 
 impl<const INT_BITS: usize> From<f64> for Fractional<INT_BITS> {
     fn from(val: f64) -> Self {
-        Self { val }
+        Self { val, int: None }
     }
 }
 
@@ -577,9 +960,7 @@ impl<const INT_BITS: usize> Add for Fractional<INT_BITS> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self {
-        Self {
-            val: self.val + rhs.val,
-        }
+        Self::from(self.val + rhs.val)
     }
 }
 
@@ -587,9 +968,7 @@ impl<const INT_BITS: usize> Add<f64> for Fractional<INT_BITS> {
     type Output = Self;
 
     fn add(self, rhs: f64) -> Self {
-        Self {
-            val: self.val + rhs,
-        }
+        Self::from(self.val + rhs)
     }
 }
 
@@ -597,9 +976,7 @@ impl<const INT_BITS: usize> Add<Fractional<INT_BITS>> for f64 {
     type Output = Fractional<INT_BITS>;
 
     fn add(self, rhs: Fractional<INT_BITS>) -> Self::Output {
-        Fractional {
-            val: self + rhs.val,
-        }
+        Fractional::from(self + rhs.val)
     }
 }
 
@@ -607,9 +984,7 @@ impl<const INT_BITS: usize> Mul for Fractional<INT_BITS> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self {
-        Self {
-            val: self.val * rhs.val,
-        }
+        Self::from(self.val * rhs.val)
     }
 }
 
@@ -617,9 +992,7 @@ impl<const INT_BITS: usize> Mul<f64> for Fractional<INT_BITS> {
     type Output = Self;
 
     fn mul(self, rhs: f64) -> Self {
-        Self {
-            val: self.val * rhs,
-        }
+        Self::from(self.val * rhs)
     }
 }
 
@@ -627,9 +1000,7 @@ impl<const INT_BITS: usize> Mul<Fractional<INT_BITS>> for f64 {
     type Output = Fractional<INT_BITS>;
 
     fn mul(self, rhs: Fractional<INT_BITS>) -> Self::Output {
-        Fractional {
-            val: self * rhs.val,
-        }
+        Fractional::from(self * rhs.val)
     }
 }
 
@@ -637,9 +1008,7 @@ impl<const INT_BITS: usize> Sub for Fractional<INT_BITS> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self {
-        Self {
-            val: self.val - rhs.val,
-        }
+        Self::from(self.val - rhs.val)
     }
 }
 
@@ -647,9 +1016,7 @@ impl<const INT_BITS: usize> Sub<f64> for Fractional<INT_BITS> {
     type Output = Self;
 
     fn sub(self, rhs: f64) -> Self {
-        Self {
-            val: self.val - rhs,
-        }
+        Self::from(self.val - rhs)
     }
 }
 
@@ -657,9 +1024,7 @@ impl<const INT_BITS: usize> Sub<Fractional<INT_BITS>> for f64 {
     type Output = Fractional<INT_BITS>;
 
     fn sub(self, rhs: Fractional<INT_BITS>) -> Self::Output {
-        Fractional {
-            val: self - rhs.val,
-        }
+        Fractional::from(self - rhs.val)
     }
 }
 
@@ -667,9 +1032,7 @@ impl<const INT_BITS: usize> Div<f64> for Fractional<INT_BITS> {
     type Output = Self;
 
     fn div(self, rhs: f64) -> Self {
-        Self {
-            val: self.val / rhs,
-        }
+        Self::from(self.val / rhs)
     }
 }
 
@@ -677,7 +1040,7 @@ impl<const INT_BITS: usize> Neg for Fractional<INT_BITS> {
     type Output = Self;
 
     fn neg(self) -> Self {
-        Self { val: -self.val }
+        Self::from(-self.val)
     }
 }
 
@@ -690,6 +1053,16 @@ mod tests {
     use crate::{SchemeType, SecurityLevel};
     use float_cmp::ApproxEq;
 
+    fn params(lattice_dimension: u64, plain_modulus: u64) -> Params {
+        Params {
+            lattice_dimension,
+            plain_modulus,
+            coeff_modulus: vec![],
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        }
+    }
+
     #[test]
     fn can_encode_decode_fractional() {
         let round_trip = |x: f64| {
@@ -726,6 +1099,136 @@ mod tests {
         round_trip(-0.0000000005);
     }
 
+    #[test]
+    fn try_div_const_rejects_zero() {
+        let a = FheProgramNode::<Cipher<Fractional<64>>>::new(&[NodeIndex::from(0)]);
+
+        assert!(matches!(
+            a.try_div_const(0.0),
+            Err(sunscreen_runtime::Error::FheTypeError(_))
+        ));
+        assert!(matches!(
+            a.try_div_const(-0.0),
+            Err(sunscreen_runtime::Error::FheTypeError(_))
+        ));
+    }
+
+    #[test]
+    fn rounding_beats_truncation_on_excess_precision() {
+        // 1024 - 64 = 960 fractional bits, the fewest BFV allows.
+        let params = params(1024, 1_000_000);
+
+        // Only values this small carry bits past 2^-960.
+        let ulp = (-960f64).exp2();
+        let x = 0.875 * ulp;
+
+        let round_trip = |f: Fractional<64>| {
+            let pt = f.try_into_plaintext(&params).unwrap();
+            Fractional::<64>::try_from_plaintext(&pt, &params).unwrap()
+        };
+
+        let truncated = round_trip(Fractional::<64>::from(x));
+        let rounded = round_trip(Fractional::<64>::from_rounded(x, &params));
+
+        assert_eq!(*truncated, 0.0);
+        assert_eq!(*rounded, ulp);
+        assert!((*rounded - x).abs() < (*truncated - x).abs());
+
+        let truncated = round_trip(Fractional::<64>::from(-x));
+        let rounded = round_trip(Fractional::<64>::from_rounded(-x, &params));
+
+        assert_eq!(*truncated, 0.0);
+        assert_eq!(*rounded, -ulp);
+
+        // Representable values are untouched.
+        assert_eq!(
+            Fractional::<64>::from_rounded(5.8125, &params),
+            5.8125.into()
+        );
+        assert_eq!(
+            Fractional::<64>::from_rounded(1.0 + f64::EPSILON, &params),
+            (1.0 + f64::EPSILON).into()
+        );
+    }
+
+    #[test]
+    fn tiny_plain_modulus_is_rejected() {
+        let params = params(4096, 2);
+
+        let err = Fractional::<64>::from(-3.14)
+            .try_into_plaintext(&params)
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            sunscreen_runtime::Error::PlainModulusTooSmall {
+                required: 3,
+                actual: 2
+            }
+        );
+    }
+
+    #[test]
+    fn resize_failure_reports_plaintext_error() {
+        let params = params(u64::MAX, 1_000_000);
+
+        let err = Fractional::<64>::from(3.14)
+            .try_into_plaintext(&params)
+            .unwrap_err();
+
+        match err {
+            sunscreen_runtime::Error::PlaintextResizeFailed(x) => {
+                assert_eq!(x.0, u64::MAX as usize);
+            }
+            e => panic!("Expected PlaintextResizeFailed, got {:#?}", e),
+        }
+    }
+
+    #[test]
+    fn decodes_raw_coefficients_around_negative_cutoff() {
+        let params = params(4096, 1_000_001);
+
+        let decode = |coeffs: &[u64]| {
+            let plaintext = Plaintext::from_coefficients(coeffs, &params).unwrap();
+
+            f64::from(Fractional::<64>::try_from_plaintext(&plaintext, &params).unwrap())
+        };
+
+        // Coefficients at or above (plain_modulus + 1) / 2 are negative.
+        assert_eq!(decode(&[500_000]), 500_000.);
+        assert_eq!(decode(&[500_001]), -500_000.);
+        assert_eq!(decode(&[0, 1_000_000]), -2.);
+
+        // Negative powers flip sign, so the same boundary applies inverted.
+        let mut coeffs = vec![0; 4096];
+        coeffs[4095] = 500_000;
+        assert_eq!(decode(&coeffs), -250_000.);
+        coeffs[4095] = 500_001;
+        assert_eq!(decode(&coeffs), 250_000.);
+    }
+
+    #[test]
+    fn raw_coefficients_are_validated() {
+        let params = params(4, 7);
+
+        assert!(Plaintext::from_coefficients(&[6, 0, 6, 6], &params).is_ok());
+
+        assert!(matches!(
+            Plaintext::from_coefficients(&[1, 2, 7], &params),
+            Err(sunscreen_runtime::Error::CoefficientOutOfRange {
+                index: 2,
+                plain_modulus: 7
+            })
+        ));
+        assert!(matches!(
+            Plaintext::from_coefficients(&[0; 5], &params),
+            Err(sunscreen_runtime::Error::TooManyCoefficients {
+                count: 5,
+                lattice_dimension: 4
+            })
+        ));
+    }
+
     #[test]
     fn can_add_non_fhe() {
         let a = Fractional::<64>::from(3.14);
@@ -767,6 +1270,15 @@ mod tests {
         assert!((a / 1.5).approx_eq(3.14 / 1.5, (0.0, 1)));
     }
 
+    #[test]
+    fn can_clamp_non_fhe() {
+        let clamp = |x: f64| Fractional::<64>::from(x).clamp(-1.5, 2.25);
+
+        assert_eq!(clamp(-7.0), (-1.5).into());
+        assert_eq!(clamp(0.75), 0.75.into());
+        assert_eq!(clamp(3.14), 2.25.into());
+    }
+
     #[test]
     fn can_neg_non_fhe() {
         let a = Fractional::<64>::from(3.14);
@@ -774,4 +1286,171 @@ mod tests {
         // Allow 1 ULP of error
         assert_eq!(-a, (-3.14).into());
     }
+
+    #[test]
+    fn deep_multiply_chain_exceeds_fractional_bits() {
+        use crate::fhe::{FheData, CURRENT_FHE_CTX};
+        use crate::types::intern::Input;
+        use std::cell::RefCell;
+        use std::mem::transmute;
+
+        CURRENT_FHE_CTX.with(|ctx| {
+            // 256 - 64 = 192 fractional bits, so x^4 at an estimated
+            // 4 * 53 = 212 bits is the first power to overflow.
+            let mut context = FheContext::new(FheData::new(params(256, 1_000_000)));
+
+            ctx.swap(&RefCell::new(Some(unsafe { transmute(&mut context) })));
+
+            let x = FheProgramNode::<Cipher<Fractional<64>>>::input();
+
+            let x_3 = x * x * x;
+            let x_4 = x_3 * x;
+            let x_5 = x_4 * x;
+
+            let scale = |n: FheProgramNode<Cipher<Fractional<64>>>| {
+                with_fhe_ctx(|ctx| ctx.data.fractional_scale(n.ids[0]))
+            };
+
+            assert_eq!(scale(x_3), 159);
+            assert_eq!(scale(x_5), 265);
+            assert_eq!(scale(x_3 + x * 0.5), 159);
+            assert_eq!(scale(x * 0.75), 55);
+
+            // Only the first node past the limit warns.
+            assert_eq!(
+                with_fhe_ctx(|ctx| ctx.data.fractional_overflows().to_vec()),
+                vec![x_4.ids[0]]
+            );
+
+            ctx.swap(&RefCell::new(None));
+        });
+    }
+
+    #[test]
+    fn scale_pow2_checks_range_and_tracks_scale() {
+        use crate::fhe::{FheData, CURRENT_FHE_CTX};
+        use crate::types::intern::Input;
+        use std::cell::RefCell;
+        use std::mem::transmute;
+
+        CURRENT_FHE_CTX.with(|ctx| {
+            // 64 integer bits and 256 - 64 = 192 fractional bits.
+            let mut context = FheContext::new(FheData::new(params(256, 1_000_000)));
+
+            ctx.swap(&RefCell::new(Some(unsafe { transmute(&mut context) })));
+
+            let x = FheProgramNode::<Cipher<Fractional<64>>>::input();
+
+            assert!(x.try_scale_pow2(63).is_ok());
+            assert!(x.try_scale_pow2(-192).is_ok());
+            assert!(matches!(
+                x.try_scale_pow2(64),
+                Err(sunscreen_runtime::Error::FheTypeError(_))
+            ));
+            assert!(matches!(
+                x.try_scale_pow2(-193),
+                Err(sunscreen_runtime::Error::FheTypeError(_))
+            ));
+
+            let scale = |n: FheProgramNode<Cipher<Fractional<64>>>| {
+                with_fhe_ctx(|ctx| ctx.data.fractional_scale(n.ids[0]))
+            };
+
+            let up = x.scale_pow2(3);
+            let down = x.scale_pow2(-3);
+            let overflow = x.scale_pow2(-150);
+
+            assert_eq!(scale(up), 50);
+            assert_eq!(scale(down), 56);
+            assert_eq!(scale(up.scale_pow2(-3)), 53);
+
+            // Shifting by -192 and -150 are in range, but both push the 53
+            // bits an input is estimated to use past the available 192.
+            let shifted_out = with_fhe_ctx(|ctx| ctx.data.fractional_overflows().to_vec());
+
+            assert_eq!(shifted_out.len(), 2);
+            assert_eq!(shifted_out[1], overflow.ids[0]);
+
+            ctx.swap(&RefCell::new(None));
+        });
+    }
+
+    fn coefficients(plaintext: &Plaintext) -> Vec<u64> {
+        match &plaintext.inner {
+            InnerPlaintext::Seal(p) => (0..p[0].len()).map(|i| p[0].get_coefficient(i)).collect(),
+        }
+    }
+
+    #[test]
+    fn int_and_float_paths_agree() {
+        let params = params(4096, 1_000_000);
+
+        for x in [0i64, 1, -1, 6, -6, 10_000_000_000_000, -(1 << 52), 1 << 53] {
+            let from_int = Fractional::<64>::from_i64(x)
+                .try_into_plaintext(&params)
+                .unwrap();
+            let from_float = Fractional::<64>::from(x as f64)
+                .try_into_plaintext(&params)
+                .unwrap();
+
+            assert_eq!(coefficients(&from_int), coefficients(&from_float));
+            assert_eq!(
+                f64::from(Fractional::<64>::try_from_plaintext(&from_int, &params).unwrap()),
+                x as f64
+            );
+        }
+    }
+
+    #[test]
+    fn equality_compares_rounded_values() {
+        let a = Fractional::<64>::from_i64((1 << 53) + 1);
+        let b = Fractional::<64>::from((1i64 << 53) as f64);
+        let c = Fractional::<64>::from_i64(1 << 53);
+
+        // 2^53 + 1 rounds to 2^53, so all three must agree for equality
+        // to stay transitive.
+        assert_eq!(a, b);
+        assert_eq!(b, c);
+        assert_eq!(a, c);
+    }
+
+    #[test]
+    fn int_path_encodes_largest_value_exactly() {
+        let params = params(4096, 1_000_000);
+
+        let max = (1i64 << 60) - 1;
+
+        // Every integer digit is set, and nothing else.
+        let pt = Fractional::<60>::from_i64(max)
+            .try_into_plaintext(&params)
+            .unwrap();
+        let mut expected = vec![0; 4096];
+        expected[..60].iter_mut().for_each(|c| *c = 1);
+        assert_eq!(coefficients(&pt), expected);
+
+        let pt = Fractional::<60>::from_i64(-max)
+            .try_into_plaintext(&params)
+            .unwrap();
+        expected[..60].iter_mut().for_each(|c| *c = 999_999);
+        assert_eq!(coefficients(&pt), expected);
+
+        // The float path rounds this up to 2^60, which doesn't fit.
+        assert!(Fractional::<60>::from(max as f64)
+            .try_into_plaintext(&params)
+            .is_err());
+        assert!(Fractional::<60>::from_i64(max + 1)
+            .try_into_plaintext(&params)
+            .is_err());
+
+        // Small enough for f64 to hold, so this also round trips.
+        let max = (1i64 << 32) - 1;
+        let pt = Fractional::<32>::from_i64(max)
+            .try_into_plaintext(&params)
+            .unwrap();
+
+        assert_eq!(
+            Fractional::<32>::try_from_plaintext(&pt, &params).unwrap(),
+            Fractional::<32>::from_i64(max)
+        );
+    }
 }
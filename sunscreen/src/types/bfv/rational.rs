@@ -325,11 +325,11 @@ impl GraphCipherConstAdd for Rational {
         with_fhe_ctx(|ctx| {
             let b = Self::try_from(b).unwrap();
 
-            let b_num =
-                ctx.add_plaintext_literal(b.num.try_into_plaintext(&ctx.data).unwrap().inner);
+            let b_num = ctx
+                .add_plaintext_literal(b.num.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
-            let b_den =
-                ctx.add_plaintext_literal(b.den.try_into_plaintext(&ctx.data).unwrap().inner);
+            let b_den = ctx
+                .add_plaintext_literal(b.den.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
             // Scale each numinator by the other's denominator.
             let num_a_2 = ctx.add_multiplication_plaintext(a.ids[0], b_den);
@@ -425,10 +425,10 @@ impl GraphCipherConstSub for Rational {
         with_fhe_ctx(|ctx| {
             let b = Self::try_from(b).unwrap();
 
-            let b_num =
-                ctx.add_plaintext_literal(b.num.try_into_plaintext(&ctx.data).unwrap().inner);
-            let b_den =
-                ctx.add_plaintext_literal(b.den.try_into_plaintext(&ctx.data).unwrap().inner);
+            let b_num = ctx
+                .add_plaintext_literal(b.num.try_into_plaintext(&ctx.data.params).unwrap().inner);
+            let b_den = ctx
+                .add_plaintext_literal(b.den.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
             // Scale each numinator by the other's denominator.
             let num_a_2 = ctx.add_multiplication_plaintext(a.ids[0], b_den);
@@ -455,10 +455,10 @@ impl GraphConstCipherSub for Rational {
         with_fhe_ctx(|ctx| {
             let a = Self::try_from(a).unwrap();
 
-            let a_num =
-                ctx.add_plaintext_literal(a.num.try_into_plaintext(&ctx.data).unwrap().inner);
-            let a_den =
-                ctx.add_plaintext_literal(a.den.try_into_plaintext(&ctx.data).unwrap().inner);
+            let a_num = ctx
+                .add_plaintext_literal(a.num.try_into_plaintext(&ctx.data.params).unwrap().inner);
+            let a_den = ctx
+                .add_plaintext_literal(a.den.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
             // Scale each numinator by the other's denominator.
             let num_b_2 = ctx.add_multiplication_plaintext(b.ids[0], a_den);
@@ -525,10 +525,10 @@ impl GraphCipherConstMul for Rational {
         with_fhe_ctx(|ctx| {
             let b = Self::try_from(b).unwrap();
 
-            let num_b =
-                ctx.add_plaintext_literal(b.num.try_into_plaintext(&ctx.data).unwrap().inner);
-            let den_b =
-                ctx.add_plaintext_literal(b.den.try_into_plaintext(&ctx.data).unwrap().inner);
+            let num_b = ctx
+                .add_plaintext_literal(b.num.try_into_plaintext(&ctx.data.params).unwrap().inner);
+            let den_b = ctx
+                .add_plaintext_literal(b.den.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
             // Scale each numinator by the other's denominator.
             let mul_num = ctx.add_multiplication_plaintext(a.ids[0], num_b);
@@ -612,10 +612,10 @@ impl GraphCipherConstDiv for Rational {
         with_fhe_ctx(|ctx| {
             let b = Self::try_from(b).unwrap();
 
-            let num_b =
-                ctx.add_plaintext_literal(b.num.try_into_plaintext(&ctx.data).unwrap().inner);
-            let den_b =
-                ctx.add_plaintext_literal(b.den.try_into_plaintext(&ctx.data).unwrap().inner);
+            let num_b = ctx
+                .add_plaintext_literal(b.num.try_into_plaintext(&ctx.data.params).unwrap().inner);
+            let den_b = ctx
+                .add_plaintext_literal(b.den.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
             // Scale each numinator by the other's denominator.
             let mul_num = ctx.add_multiplication_plaintext(a.ids[0], den_b);
@@ -639,10 +639,10 @@ impl GraphConstCipherDiv for Rational {
         with_fhe_ctx(|ctx| {
             let a = Self::try_from(a).unwrap();
 
-            let num_a =
-                ctx.add_plaintext_literal(a.num.try_into_plaintext(&ctx.data).unwrap().inner);
-            let den_a =
-                ctx.add_plaintext_literal(a.den.try_into_plaintext(&ctx.data).unwrap().inner);
+            let num_a = ctx
+                .add_plaintext_literal(a.num.try_into_plaintext(&ctx.data.params).unwrap().inner);
+            let den_a = ctx
+                .add_plaintext_literal(a.den.try_into_plaintext(&ctx.data.params).unwrap().inner);
 
             // Scale each numinator by the other's denominator.
             let mul_num = ctx.add_multiplication_plaintext(b.ids[1], num_a);
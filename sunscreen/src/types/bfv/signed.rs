@@ -2,7 +2,7 @@ use seal_fhe::Plaintext as SealPlaintext;
 
 use crate as sunscreen;
 use crate::{
-    fhe::{with_fhe_ctx, FheContextOps},
+    fhe::{with_fhe_ctx, FheContext, FheContextOps},
     types::{
         ops::{
             GraphCipherAdd, GraphCipherConstAdd, GraphCipherConstMul, GraphCipherConstSub,
@@ -17,6 +17,7 @@ use crate::{
     FheProgramInputTrait, Params, TypeName as DeriveTypeName, WithContext,
 };
 
+use petgraph::stable_graph::NodeIndex;
 use sunscreen_runtime::{
     InnerPlaintext, NumCiphertexts, Plaintext, TryFromPlaintext, TryIntoPlaintext,
 };
@@ -64,16 +65,26 @@ fn significant_bits(val: u64) -> usize {
 }
 
 impl TryIntoPlaintext for Signed {
+    fn min_plain_modulus(_params: &Params) -> u64 {
+        // Negative digits encode as plain_modulus - 1, which must differ from 1.
+        3
+    }
+
     fn try_into_plaintext(
         &self,
         params: &Params,
     ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
+        Self::check_plain_modulus(params)?;
+
         let mut seal_plaintext = SealPlaintext::new()?;
 
-        let signed_val = if self.val < 0 { -self.val } else { self.val } as u64;
+        // unsigned_abs rather than negation, which overflows for i64::MIN.
+        let signed_val = self.val.unsigned_abs();
 
         let sig_bits = significant_bits(signed_val);
-        seal_plaintext.resize(sig_bits);
+        seal_plaintext
+            .try_resize(sig_bits)
+            .map_err(|e| sunscreen_runtime::Error::plaintext_resize_failed(sig_bits, e))?;
 
         for i in 0..sig_bits {
             let bit_value = (signed_val & 0x1 << i) >> i;
@@ -84,7 +95,9 @@ impl TryIntoPlaintext for Signed {
                 bit_value
             };
 
-            seal_plaintext.set_coefficient(i, coeff_value);
+            seal_plaintext
+                .try_set_coefficient(i, coeff_value)
+                .map_err(|e| sunscreen_runtime::Error::plaintext_set_coefficient_failed(i, e))?;
         }
 
         Ok(Plaintext {
@@ -117,13 +130,18 @@ impl TryFromPlaintext for Signed {
 
                 let mut val: i64 = 0;
 
+                // Sum in two's complement, wrapping on overflow: partial sums
+                // can leave i64's range (e.g. while decoding i64::MIN), but the
+                // result is exact whenever the encoded value is in range.
                 for i in 0..bits {
                     let coeff = p[0].get_coefficient(i);
 
                     if coeff < negative_cutoff {
-                        val += ((0x1 << i) * coeff) as i64;
+                        val = val.wrapping_add((1u64 << i).wrapping_mul(coeff) as i64);
                     } else {
-                        val -= ((0x1 << i) * (params.plain_modulus - coeff)) as i64;
+                        val = val.wrapping_sub(
+                            (1u64 << i).wrapping_mul(params.plain_modulus - coeff) as i64,
+                        );
                     }
                 }
 
@@ -286,7 +304,7 @@ impl GraphCipherConstAdd for Signed {
         b: i64,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(b.inner);
             let add = ctx.add_addition_plaintext(a.ids[0], lit);
@@ -354,7 +372,7 @@ impl GraphCipherConstSub for Signed {
         b: Self::Right,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(b.inner);
             let n = ctx.add_subtraction_plaintext(a.ids[0], lit);
@@ -373,7 +391,7 @@ impl GraphConstCipherSub for Signed {
         b: FheProgramNode<Cipher<Self::Right>>,
     ) -> FheProgramNode<Cipher<Self::Right>> {
         with_fhe_ctx(|ctx| {
-            let a = Self::from(a).try_into_plaintext(&ctx.data).unwrap();
+            let a = Self::from(a).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(a.inner);
             let n = ctx.add_subtraction_plaintext(b.ids[0], lit);
@@ -421,7 +439,7 @@ impl GraphCipherConstMul for Signed {
         b: i64,
     ) -> FheProgramNode<Cipher<Self::Left>> {
         with_fhe_ctx(|ctx| {
-            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(b.inner);
             let add = ctx.add_multiplication_plaintext(a.ids[0], lit);
@@ -447,9 +465,119 @@ impl GraphCipherPlainMul for Signed {
     }
 }
 
+/**
+ * Adds a plaintext literal encoding the polynomial `coefficient * x^power`
+ * to the current FHE program.
+ */
+fn add_monomial_literal(ctx: &mut FheContext, coefficient: u64, power: usize) -> NodeIndex {
+    let mut seal_plaintext = SealPlaintext::new().unwrap();
+    seal_plaintext.resize(power + 1);
+    seal_plaintext.set_coefficient(power, coefficient);
+
+    ctx.add_plaintext_literal(InnerPlaintext::Seal(vec![WithContext {
+        params: ctx.data.params.clone(),
+        data: seal_plaintext,
+    }]))
+}
+
+impl FheProgramNode<Cipher<Signed>> {
+    /**
+     * Computes `self * 2^k` on the encrypted value.
+     *
+     * # Remarks
+     * This is an arithmetic shift of the encoded integer, not a rotation
+     * of SIMD slots (see [`Batched`](crate::types::bfv::Batched) for
+     * that). Since [`Signed`] stores one binary digit per polynomial
+     * coefficient, this multiplies by the plaintext `x^k`, which moves
+     * every digit up `k` positions and adds almost no noise.
+     *
+     * As with `i64`, results that need more than 64 bits are
+     * undefined.
+     *
+     * # Panics
+     * Panics if `k` is 64 or greater.
+     */
+    pub fn shl_value(self, k: usize) -> Self {
+        assert!(k < 64, "Cannot shift a Signed value by 64 or more bits.");
+
+        with_fhe_ctx(|ctx| {
+            let lit = add_monomial_literal(ctx, 1, k);
+            let n = ctx.add_multiplication_plaintext(self.ids[0], lit);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+
+    /**
+     * Computes `self / 2^k` on the encrypted value, rounding toward zero.
+     *
+     * # Remarks
+     * This is an arithmetic shift of the encoded integer, not a rotation
+     * of SIMD slots. BFV can't divide, but the plaintext ring
+     * `Z_t[x]/(x^n + 1)` satisfies `x^-k = -x^(n - k)`. Multiplying by
+     * that literal moves every digit at or above `k` down `k` positions,
+     * unchanged. The `k` lowest digits instead wrap into the top `k`
+     * coefficients, past the 64 [`Signed`] decodes, so the result drops
+     * them. Since each digit carries the value's sign, the result
+     * truncates toward zero (unlike `i64`'s `>>`, which rounds toward
+     * negative infinity).
+     *
+     * The wrapped digits remain in the ciphertext, so operations that
+     * move coefficients back past `x^n`, such as a following
+     * [`shl_value`](Self::shl_value), bring them back.
+     *
+     * # Panics
+     * Panics if `k` is 64 or greater.
+     */
+    pub fn shr_value(self, k: usize) -> Self {
+        assert!(k < 64, "Cannot shift a Signed value by 64 or more bits.");
+
+        if k == 0 {
+            return self;
+        }
+
+        with_fhe_ctx(|ctx| {
+            let lattice_dimension = ctx.data.params.lattice_dimension as usize;
+
+            let plain_modulus = ctx.data.params.plain_modulus;
+
+            // Encode x^-k directly, so the shift is a single plaintext
+            // multiplication.
+            let lit = add_monomial_literal(ctx, plain_modulus - 1, lattice_dimension - k);
+            let n = ctx.add_multiplication_plaintext(self.ids[0], lit);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{SchemeType, SecurityLevel};
+    use seal_fhe::CoefficientModulus;
+
+    #[test]
+    fn can_roundtrip_boundary_values() {
+        let params = Params {
+            lattice_dimension: 4096,
+            plain_modulus: 1024,
+            coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|x| x.value())
+                .collect::<Vec<u64>>(),
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        };
+
+        for val in [0, 1, -1, i64::MAX, i64::MIN, i64::MIN + 1, -(1 << 62)] {
+            let plaintext = Signed::from(val).try_into_plaintext(&params).unwrap();
+            let decoded = Signed::try_from_plaintext(&plaintext, &params).unwrap();
+
+            assert_eq!(i64::from(decoded), val);
+        }
+    }
 
     #[test]
     fn can_add_non_fhe() {
@@ -5,7 +5,8 @@ use paste::paste;
 use seal_fhe::Plaintext as SealPlaintext;
 
 use sunscreen_runtime::{
-    InnerPlaintext, NumCiphertexts, Plaintext, TryFromPlaintext, TryIntoPlaintext,
+    InnerPlaintext, NumCiphertexts, Plaintext, TryFromPlaintext, TryFromPlaintextChecked,
+    TryIntoPlaintext,
 };
 
 use crate as sunscreen;
@@ -21,7 +22,7 @@ use crate::{
     },
 };
 use crate::{
-    types::{intern::FheProgramNode, BfvType, FheType, TypeNameInstance},
+    types::{bfv::Boolean, intern::FheProgramNode, BfvType, FheType, TypeNameInstance},
     FheProgramInputTrait, Params, TypeName as DeriveTypeName, WithContext,
 };
 
@@ -68,11 +69,15 @@ impl<const LIMBS: usize> TryIntoPlaintext for Unsigned<LIMBS> {
         let mut seal_plaintext = SealPlaintext::new()?;
 
         let sig_bits = self.val.bits_vartime();
-        seal_plaintext.resize(sig_bits);
+        seal_plaintext
+            .try_resize(sig_bits)
+            .map_err(|e| sunscreen_runtime::Error::plaintext_resize_failed(sig_bits, e))?;
 
         for i in 0..sig_bits {
             let bit_value = self.val.bit_vartime(i);
-            seal_plaintext.set_coefficient(i, bit_value);
+            seal_plaintext
+                .try_set_coefficient(i, bit_value)
+                .map_err(|e| sunscreen_runtime::Error::plaintext_set_coefficient_failed(i, e))?;
         }
 
         Ok(Plaintext {
@@ -127,6 +132,70 @@ impl<const LIMBS: usize> TryFromPlaintext for Unsigned<LIMBS> {
     }
 }
 
+/**
+ * Detects when a decrypted plaintext holds a negative value or one that
+ * needs more than `LIMBS * 64` bits.
+ *
+ * # Remarks
+ * Each coefficient holds a (signed) binary digit, so the encoded value
+ * is the sum of `digit_i * 2^i`. Multiplication and addition carry it
+ * past the type's width, which [`TryFromPlaintext`] silently truncates,
+ * and subtraction can make it negative, which it wraps.
+ *
+ * A digit that itself grows past half the plaintext modulus wraps to a
+ * different digit before decryption and can't be detected. Choose a
+ * plaintext modulus large enough for your program's digits.
+ */
+impl<const LIMBS: usize> TryFromPlaintextChecked for Unsigned<LIMBS> {
+    fn try_from_plaintext_checked(
+        plaintext: &Plaintext,
+        params: &Params,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        match &plaintext.inner {
+            InnerPlaintext::Seal(p) => {
+                if p.len() != 1 {
+                    return Err(sunscreen_runtime::Error::IncorrectCiphertextCount);
+                }
+
+                let width = std::mem::size_of::<UInt<LIMBS>>() * 8;
+                let negative_cutoff = (params.plain_modulus + 1) / 2;
+                let len = p[0].len();
+
+                // Normalize the digits to bits, carrying from least to most
+                // significant.
+                let mut carry = 0i128;
+
+                for i in 0..len {
+                    let coeff = p[0].get_coefficient(i);
+
+                    let digit = if coeff < negative_cutoff {
+                        coeff as i128
+                    } else {
+                        coeff as i128 - params.plain_modulus as i128
+                    };
+
+                    let t = digit + carry;
+                    let bit = t.rem_euclid(2);
+                    carry = (t - bit) / 2;
+
+                    if bit != 0 && i >= width {
+                        return Err(sunscreen_runtime::Error::Overflow);
+                    }
+                }
+
+                // The remaining carry occupies the bits from len upward.
+                let carry_bits = (i128::BITS - carry.leading_zeros()) as usize;
+
+                if carry < 0 || (carry > 0 && len + carry_bits > width) {
+                    return Err(sunscreen_runtime::Error::Overflow);
+                }
+            }
+        }
+
+        Self::try_from_plaintext(plaintext, params)
+    }
+}
+
 impl<const LIMBS: usize> From<UInt<LIMBS>> for Unsigned<LIMBS> {
     fn from(val: UInt<LIMBS>) -> Self {
         Self { val }
@@ -264,7 +333,7 @@ macro_rules! impl_graph_cipher_op {
                         b: UInt<LIMBS>,
                     ) -> FheProgramNode<Cipher<Self::Left>> {
                         with_fhe_ctx(|ctx| {
-                            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+                            let b = Self::from(b).try_into_plaintext(&ctx.data.params).unwrap();
 
                             let lit = ctx.add_plaintext_literal(b.inner);
                             let [<$op:lower>] = ctx.[<add_ $op_noun _plaintext>](a.ids[0], lit);
@@ -284,6 +353,25 @@ impl_graph_cipher_op! {
     (Mul, multiplication)
 }
 
+// cipher + u64. The constant encodes as a plaintext literal and adds
+// without encryption, which leaves the noise budget nearly untouched.
+impl<const LIMBS: usize> Add<u64> for FheProgramNode<Cipher<Unsigned<LIMBS>>> {
+    type Output = Self;
+
+    fn add(self, rhs: u64) -> Self::Output {
+        self + UInt::<LIMBS>::from_u64(rhs)
+    }
+}
+
+// u64 + cipher
+impl<const LIMBS: usize> Add<FheProgramNode<Cipher<Unsigned<LIMBS>>>> for u64 {
+    type Output = FheProgramNode<Cipher<Unsigned<LIMBS>>>;
+
+    fn add(self, rhs: FheProgramNode<Cipher<Unsigned<LIMBS>>>) -> Self::Output {
+        rhs + UInt::<LIMBS>::from_u64(self)
+    }
+}
+
 impl<const LIMBS: usize> GraphConstCipherSub for Unsigned<LIMBS> {
     type Left = UInt<LIMBS>;
     type Right = Self;
@@ -293,7 +381,7 @@ impl<const LIMBS: usize> GraphConstCipherSub for Unsigned<LIMBS> {
         b: FheProgramNode<Cipher<Self::Right>>,
     ) -> FheProgramNode<Cipher<Self::Right>> {
         with_fhe_ctx(|ctx| {
-            let a = Self::from(a).try_into_plaintext(&ctx.data).unwrap();
+            let a = Self::from(a).try_into_plaintext(&ctx.data.params).unwrap();
 
             let lit = ctx.add_plaintext_literal(a.inner);
             let n = ctx.add_subtraction_plaintext(b.ids[0], lit);
@@ -321,10 +409,164 @@ impl<const LIMBS: usize> GraphPlainCipherSub for Unsigned<LIMBS> {
     }
 }
 
+/**
+ * Whether `n` is prime, by the Miller-Rabin test with bases that are
+ * deterministic for every [`u64`].
+ */
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+
+    if let Some(b) = BASES.iter().find(|b| n % **b == 0) {
+        return n == *b;
+    }
+
+    let mul = |a: u64, b: u64| (a as u128 * b as u128 % n as u128) as u64;
+
+    let pow = |mut x: u64, mut e: u64| {
+        let mut result = 1;
+
+        while e > 0 {
+            if e & 0x1 == 1 {
+                result = mul(result, x);
+            }
+
+            x = mul(x, x);
+            e >>= 1;
+        }
+
+        result
+    };
+
+    // n - 1 = d * 2^s with d odd.
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    BASES.iter().all(|a| {
+        let mut x = pow(*a, d);
+
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+
+        for _ in 1..s {
+            x = mul(x, x);
+
+            if x == n - 1 {
+                return true;
+            }
+        }
+
+        false
+    })
+}
+
+impl<const LIMBS: usize> FheProgramNode<Cipher<Unsigned<LIMBS>>> {
+    /**
+     * Returns an encrypted [`Boolean`] that is `true` when `self` and
+     * `rhs` are equal.
+     *
+     * # Remarks
+     * Computes `1 - (self - rhs)^(p - 1)`, where `p` is the plain modulus.
+     * By Fermat's little theorem, the power is `1` for any nonzero
+     * difference and `0` otherwise, so `p` must be prime. Raising to
+     * `p - 1` by repeated squaring costs multiplicative depth
+     * `ceil(log2(p - 1))`, so this is only practical with a small plain
+     * modulus (see [`PlainModulusConstraint::Raw`](crate::PlainModulusConstraint::Raw)).
+     *
+     * [`Unsigned`] stores one bit per plaintext coefficient and the
+     * theorem only holds for the constant coefficient, so `self` and `rhs`
+     * must agree in every bit but the lowest: their difference must be
+     * `-1`, `0`, or `1`. In practice, this means comparing values in
+     * `0..=1`. This can't be checked on ciphertexts, and operands that
+     * differ in higher bits give a meaningless result.
+     *
+     * # Panics
+     * Panics if the plain modulus isn't prime.
+     */
+    pub fn eq_encrypted(self, rhs: Self) -> FheProgramNode<Cipher<Boolean>> {
+        let p = Self::get_plain_modulus();
+
+        assert!(is_prime(p), "plain modulus must be prime");
+
+        let pow = (self - rhs).pow(p - 1);
+
+        !FheProgramNode::<Cipher<Boolean>>::new(pow.ids)
+    }
+
+    /**
+     * Returns `self^exponent` for a public `exponent`.
+     *
+     * # Remarks
+     * Uses square-and-multiply: `floor(log2(exponent))` squarings, plus
+     * one multiplication for each further set bit of `exponent`.
+     * Multiplying in the set bits' powers from least to most significant
+     * keeps the multiplicative depth at `ceil(log2(exponent))`, the
+     * minimum for any way of computing the power.
+     *
+     * An `exponent` of 1 gives `self` without a multiplication.
+     *
+     * # Panics
+     * Panics if `exponent` is 0. `self^0` is the constant 1, and deriving a
+     * constant from a ciphertext (e.g. as `self - self + 1`) yields a
+     * transparent ciphertext, which SEAL rejects. Use a plaintext 1
+     * instead.
+     */
+    pub fn pow(self, exponent: u64) -> Self {
+        assert!(exponent > 0, "exponent must be positive");
+
+        with_fhe_ctx(|ctx| {
+            let mut exp = exponent;
+            let mut square = self.ids[0];
+            let mut pow = None;
+
+            loop {
+                if exp & 0x1 == 1 {
+                    pow = Some(match pow {
+                        Some(p) => ctx.add_multiplication(p, square),
+                        None => square,
+                    });
+                }
+
+                exp >>= 1;
+
+                if exp == 0 {
+                    break;
+                }
+
+                square = ctx.add_multiplication(square, square);
+            }
+
+            // exponent is nonzero, so some bit is set.
+            FheProgramNode::new(&[pow.unwrap()])
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn detects_prime_plain_moduli() {
+        let primes = (0..100).filter(|n| is_prime(*n)).collect::<Vec<_>>();
+
+        assert_eq!(
+            primes,
+            vec![
+                2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79,
+                83, 89, 97
+            ]
+        );
+
+        // A strong pseudoprime to bases 2 through 11, and a batching prime.
+        assert!(!is_prime(2_152_302_898_747));
+        assert!(is_prime(1_032_193));
+    }
+
     #[test]
     fn can_add_non_fhe() {
         let a = Unsigned256::from(5);
@@ -1,13 +1,14 @@
 use crate::{
     fhe::with_fhe_ctx,
     types::{
-        intern::FheLiteral, ops::*, Cipher, FheType, LaneCount, NumCiphertexts, SwapRows, Type,
-        TypeName,
+        bfv::Boolean, intern::FheLiteral, ops::*, Cipher, FheType, LaneCount, NumCiphertexts,
+        SwapRows, Type, TypeName,
     },
     INDEX_ARENA,
 };
 use petgraph::stable_graph::NodeIndex;
 
+use std::iter::{Product, Sum};
 use std::ops::{Add, Div, Mul, Neg, Shl, Shr, Sub};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -94,7 +95,64 @@ impl<T: NumCiphertexts> FheProgramNode<T> {
      * Returns the plain modulus parameter for the given BFV scheme
      */
     pub fn get_plain_modulus() -> u64 {
-        with_fhe_ctx(|ctx| ctx.data.plain_modulus)
+        with_fhe_ctx(|ctx| ctx.data.params.plain_modulus)
+    }
+}
+
+impl<T> FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherAdd<Left = T, Right = T> + GraphCipherMul<Left = T, Right = T>,
+{
+    /**
+     * Returns `self + cond * delta`, i.e. adds `delta` to this accumulator
+     * only when `cond` is 1.
+     *
+     * # Remarks
+     * `cond` must encrypt exactly 0 or 1; other values scale `delta`
+     * rather than select it. Since this is branch-free, an observer
+     * can't tell whether `delta` was added.
+     *
+     * This costs one ciphertext multiplication.
+     */
+    pub fn cond_add(self, cond: Self, delta: Self) -> Self {
+        self + cond * delta
+    }
+}
+
+impl<T> FheProgramNode<Cipher<T>>
+where
+    T: FheType,
+{
+    /**
+     * Returns `a` when `cond` is `true` and `b` when it's `false`, i.e.
+     * the encrypted `cond ? a : b`.
+     *
+     * # Remarks
+     * Lowers to `cond * a + (1 - cond) * b`, computed per underlying
+     * ciphertext as the equivalent `b + cond * (a - b)` so it needs only
+     * one multiplication each. This adds one to the multiplicative depth
+     * of `a` and `b`. Since both arms are always evaluated, an observer
+     * can't tell which was selected.
+     *
+     * `cond` encodes 0 or 1 in its constant coefficient, which scales
+     * every slot of a batched value alike, so this works for any FHE type.
+     */
+    pub fn select(cond: FheProgramNode<Cipher<Boolean>>, a: Self, b: Self) -> Self {
+        with_fhe_ctx(|ctx| {
+            let ids = a
+                .ids
+                .iter()
+                .zip(b.ids.iter())
+                .map(|(a, b)| {
+                    let diff = ctx.add_subtraction(*a, *b);
+                    let scaled = ctx.add_multiplication(cond.ids[0], diff);
+
+                    ctx.add_addition(*b, scaled)
+                })
+                .collect::<Vec<_>>();
+
+            Self::new(&ids)
+        })
     }
 }
 
@@ -353,6 +411,96 @@ where
     }
 }
 
+/**
+ * Combines `items` pairwise, level by level, into a balanced tree of
+ * `op` applications, so each item passes through only `ceil(log2(n))`
+ * of them.
+ *
+ * # Panics
+ * Panics if `items` is empty.
+ */
+fn reduce_balanced<T, I, F>(items: I, op: F) -> T
+where
+    I: Iterator<Item = T>,
+    F: Fn(T, T) -> T,
+{
+    let mut level = items.collect::<Vec<_>>();
+
+    assert!(
+        !level.is_empty(),
+        "Cannot sum or multiply an empty iterator of ciphertexts"
+    );
+
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity((level.len() + 1) / 2);
+        let mut items = level.into_iter();
+
+        while let Some(a) = items.next() {
+            next.push(match items.next() {
+                Some(b) => op(a, b),
+                None => a,
+            });
+        }
+
+        level = next;
+    }
+
+    level.pop().unwrap()
+}
+
+// sum of ciphertexts
+impl<T> Sum for FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherAdd<Left = T, Right = T>,
+{
+    /**
+     * Adds the ciphertexts as a balanced tree rather than a chain.
+     *
+     * # Panics
+     * Panics if `iter` is empty, as there's no ciphertext zero to return.
+     */
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        reduce_balanced(iter, |a, b| a + b)
+    }
+}
+
+// sum of ciphertexts
+impl<'a, T> Sum<&'a Self> for FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherAdd<Left = T, Right = T>,
+{
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.map(|x| Self::new(x.ids)).sum()
+    }
+}
+
+// product of ciphertexts
+impl<T> Product for FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherMul<Left = T, Right = T>,
+{
+    /**
+     * Multiplies the ciphertexts as a balanced tree, so the product of `n`
+     * values has multiplicative depth `ceil(log2(n))` rather than `n - 1`.
+     *
+     * # Panics
+     * Panics if `iter` is empty, as there's no ciphertext one to return.
+     */
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        reduce_balanced(iter, |a, b| a * b)
+    }
+}
+
+// product of ciphertexts
+impl<'a, T> Product<&'a Self> for FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherMul<Left = T, Right = T>,
+{
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.map(|x| Self::new(x.ids)).product()
+    }
+}
+
 // ciphertext / ciphertext
 impl<T> Div for FheProgramNode<Cipher<T>>
 where
@@ -493,6 +641,27 @@ where
     }
 }
 
+impl<T> FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherRotateLeft + GraphCipherRotateRight,
+{
+    /**
+     * Rotates left by `amount` when it's positive and right by
+     * `-amount` when it's negative, so `x.rotate(k).rotate(-k)` is `x`.
+     *
+     * # Remarks
+     * This lowers to `<<` or `>>` and adds nothing to the program when
+     * `amount` is zero.
+     */
+    pub fn rotate(self, amount: i64) -> Self {
+        match amount.signum() {
+            1 => self << amount.unsigned_abs(),
+            -1 => self >> amount.unsigned_abs(),
+            _ => self,
+        }
+    }
+}
+
 impl<T> NumCiphertexts for FheProgramNode<T>
 where
     T: NumCiphertexts,
@@ -68,7 +68,7 @@ where
 #[test]
 fn can_create_inputs() {
     use crate::{
-        fhe::{FheContext, FheOperation, CURRENT_FHE_CTX},
+        fhe::{FheContext, FheData, FheOperation, CURRENT_FHE_CTX},
         types::{bfv::Rational, intern::FheProgramNode},
         Params, SchemeType, SecurityLevel,
     };
@@ -78,13 +78,13 @@ fn can_create_inputs() {
     use petgraph::stable_graph::NodeIndex;
 
     CURRENT_FHE_CTX.with(|ctx| {
-        let mut context = FheContext::new(Params {
+        let mut context = FheContext::new(FheData::new(Params {
             lattice_dimension: 0,
             coeff_modulus: vec![],
             plain_modulus: 0,
             scheme_type: SchemeType::Bfv,
             security_level: SecurityLevel::TC128,
-        });
+        }));
 
         ctx.swap(&RefCell::new(Some(unsafe { transmute(&mut context) })));
 
@@ -127,21 +127,21 @@ fn can_create_inputs() {
         for i in 0..2 {
             assert_eq!(
                 context.graph[NodeIndex::from(i)].operation,
-                FheOperation::InputPlaintext
+                FheOperation::InputPlaintext(i as usize)
             );
         }
 
         for i in 2..14 {
             assert_eq!(
                 context.graph[NodeIndex::from(i)].operation,
-                FheOperation::InputPlaintext
+                FheOperation::InputPlaintext(i as usize)
             );
         }
 
         for i in 14..context.graph.node_count() {
             assert_eq!(
                 context.graph[NodeIndex::from(i as u32)].operation,
-                FheOperation::InputCiphertext
+                FheOperation::InputCiphertext(i)
             );
         }
     });
@@ -39,6 +39,8 @@
  * Arithmetic operations semantically execute per-lane, enabling high-throughput;
  * e.g. a single addition operation `a + b` will element-wise add the many lanes of a to the
  * many lanes in b.
+ * * The [`Boolean`](crate::types::bfv::Boolean) type represents `true` or `false`
+ * as `1` or `0` and supports `&`, `|`, and `!` on encrypted values.
  * Type comparison:
  *
  * | Type       | # ciphertexts | overflow conditions | values            | ops/add        | ops/mul | ops/sub        | ops/neg | ops/div |
@@ -74,7 +76,7 @@ mod ops;
  */
 pub mod zkp;
 
-use crate::types::ops::*;
+use crate::types::{intern::FheProgramNode, ops::*};
 
 pub use sunscreen_runtime::{
     BfvType, FheType, NumCiphertexts, TryFromPlaintext, TryIntoPlaintext, Type, TypeName,
@@ -135,3 +137,39 @@ where
         }
     }
 }
+
+/**
+ * Computes `inputs[0] * weights[0] + inputs[1] * weights[1] + ...` inside an
+ * [`fhe_program`](crate::fhe_program), e.g. to evaluate a linear model
+ * with public coefficients.
+ *
+ * # Remarks
+ * Each weight is encoded as a plaintext literal, so this costs one
+ * plaintext multiplication per input and needs no relinearization.
+ *
+ * # Panics
+ * Panics if `inputs` and `weights` have different lengths or are empty.
+ */
+pub fn weighted_sum<T>(
+    inputs: &[FheProgramNode<Cipher<T>>],
+    weights: &[f64],
+) -> FheProgramNode<Cipher<T>>
+where
+    T: FheType
+        + Copy
+        + GraphCipherConstMul<Left = T, Right = f64>
+        + GraphCipherAdd<Left = T, Right = T>,
+{
+    assert_eq!(
+        inputs.len(),
+        weights.len(),
+        "weighted_sum requires one weight per input"
+    );
+
+    inputs
+        .iter()
+        .zip(weights)
+        .map(|(x, w)| T::graph_cipher_const_mul(*x, *w))
+        .reduce(|acc, x| acc + x)
+        .expect("weighted_sum requires at least one input")
+}
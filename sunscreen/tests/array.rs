@@ -2,7 +2,10 @@
 
 use sunscreen::{
     fhe_program,
-    types::{bfv::Signed, Cipher},
+    types::{
+        bfv::{Signed, Unsigned64},
+        Cipher,
+    },
     Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
 };
 
@@ -286,3 +289,49 @@ fn can_return_array() {
 
     assert_eq!(c, expected);
 }
+
+#[test]
+fn can_add_arrays_elementwise() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: [Cipher<Unsigned64>; 4], b: [Cipher<Unsigned64>; 4]) -> [Cipher<Unsigned64>; 4] {
+        let mut c = a;
+
+        for i in 0..c.len() {
+            c[i] = a[i] + b[i];
+        }
+
+        c
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = [1, 2, 3, 4].map(Unsigned64::from);
+    let b = [10, 20, 30, 40].map(Unsigned64::from);
+
+    let a_enc = runtime.encrypt(a, &public_key).unwrap();
+    let b_enc = runtime.encrypt(b, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(add).unwrap(),
+            vec![a_enc, b_enc],
+            &public_key,
+        )
+        .unwrap();
+
+    // Each array argument and return value is one grouped Ciphertext
+    // holding a ciphertext per element.
+    assert_eq!(result.len(), 1);
+
+    let c: [Unsigned64; 4] = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, [11, 22, 33, 44].map(Unsigned64::from));
+}
@@ -0,0 +1,157 @@
+use sunscreen::{
+    fhe_program,
+    types::{
+        bfv::{Boolean, Signed},
+        Cipher,
+    },
+    CompiledFheProgram, Compiler, FheRuntime, PlainModulusConstraint, PrivateKey, PublicKey,
+    Runtime,
+};
+
+#[test]
+fn can_apply_logic_to_ciphertexts() {
+    #[fhe_program(scheme = "bfv")]
+    fn logic(
+        a: Cipher<Boolean>,
+        b: Cipher<Boolean>,
+    ) -> (Cipher<Boolean>, Cipher<Boolean>, Cipher<Boolean>) {
+        (a & b, a | b, !a)
+    }
+
+    let app = Compiler::new().fhe_program(logic).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for a in [false, true] {
+        for b in [false, true] {
+            let a_c = runtime.encrypt(Boolean::from(a), &public_key).unwrap();
+            let b_c = runtime.encrypt(Boolean::from(b), &public_key).unwrap();
+
+            let result = runtime
+                .run(
+                    app.get_fhe_program(logic).unwrap(),
+                    vec![a_c, b_c],
+                    &public_key,
+                )
+                .unwrap();
+
+            let and: Boolean = runtime.decrypt(&result[0], &private_key).unwrap();
+            let or: Boolean = runtime.decrypt(&result[1], &private_key).unwrap();
+            let not: Boolean = runtime.decrypt(&result[2], &private_key).unwrap();
+
+            assert_eq!(bool::from(and), a && b, "{} & {}", a, b);
+            assert_eq!(bool::from(or), a || b, "{} | {}", a, b);
+            assert_eq!(bool::from(not), !a, "!{}", a);
+        }
+    }
+}
+
+#[test]
+fn can_compute_majority() {
+    #[fhe_program(scheme = "bfv")]
+    fn majority_3(bits: [Cipher<Boolean>; 3]) -> Cipher<Boolean> {
+        FheProgramNode::majority(&bits)
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn majority_5(bits: [Cipher<Boolean>; 5]) -> Cipher<Boolean> {
+        FheProgramNode::majority(&bits)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(majority_3)
+        .fhe_program(majority_5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(17))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    fn run_majority<const N: usize>(
+        runtime: &FheRuntime,
+        program: &CompiledFheProgram,
+        public_key: &PublicKey,
+        private_key: &PrivateKey,
+        pattern: u32,
+    ) {
+        let mut bits = [Boolean::default(); N];
+
+        for (i, b) in bits.iter_mut().enumerate() {
+            *b = Boolean::from(pattern & (1 << i) != 0);
+        }
+
+        let result = runtime
+            .run(
+                program,
+                vec![runtime.encrypt(bits, public_key).unwrap()],
+                public_key,
+            )
+            .unwrap();
+
+        let actual: Boolean = runtime.decrypt(&result[0], private_key).unwrap();
+
+        assert_eq!(
+            bool::from(actual),
+            pattern.count_ones() as usize > N / 2,
+            "majority of {:0width$b}",
+            pattern,
+            width = N
+        );
+    }
+
+    for pattern in 0..8 {
+        run_majority::<3>(
+            &runtime,
+            app.get_fhe_program(majority_3).unwrap(),
+            &public_key,
+            &private_key,
+            pattern,
+        );
+    }
+
+    for pattern in [
+        0b00000, 0b00011, 0b00111, 0b01101, 0b10110, 0b11000, 0b11111,
+    ] {
+        run_majority::<5>(
+            &runtime,
+            app.get_fhe_program(majority_5).unwrap(),
+            &public_key,
+            &private_key,
+            pattern,
+        );
+    }
+}
+
+#[test]
+fn can_select_between_ciphertexts() {
+    #[fhe_program(scheme = "bfv")]
+    fn mux(cond: Cipher<Boolean>, a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        FheProgramNode::select(cond, a, b)
+    }
+
+    let app = Compiler::new().fhe_program(mux).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for cond in [false, true] {
+        let args = vec![
+            runtime.encrypt(Boolean::from(cond), &public_key).unwrap(),
+            runtime.encrypt(Signed::from(-7), &public_key).unwrap(),
+            runtime.encrypt(Signed::from(12), &public_key).unwrap(),
+        ];
+
+        let result = runtime
+            .run(app.get_fhe_program(mux).unwrap(), args, &public_key)
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(i64::from(c), if cond { -7 } else { 12 });
+    }
+}
@@ -0,0 +1,13 @@
+#[test]
+fn fhe_program_return_type_must_match_body() {
+    let t = trybuild::TestCases::new();
+
+    t.compile_fail("tests/ui/mismatched_return.rs");
+}
+
+#[test]
+fn plain_arguments_must_not_be_ciphertexts() {
+    let t = trybuild::TestCases::new();
+
+    t.compile_fail("tests/ui/plain_cipher_argument.rs");
+}
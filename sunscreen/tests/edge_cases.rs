@@ -1,5 +1,5 @@
 use sunscreen::{
-    types::{bfv::Signed, Cipher},
+    types::{bfv::Signed, Cipher, TypeName},
     *,
 };
 
@@ -194,3 +194,483 @@ fn unused_plain_parameter_3() {
 
     assert_eq!(c, 20.into());
 }
+
+#[test]
+fn program_exceeding_max_nodes_fails() {
+    #[fhe_program(scheme = "bfv")]
+    fn unrolled(a: Cipher<Signed>) -> Cipher<Signed> {
+        let mut x = a;
+
+        for _ in 0..100 {
+            x = x + a;
+        }
+
+        x
+    }
+
+    let result = Compiler::new()
+        .fhe_program(unrolled)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .max_nodes(50)
+        .compile();
+
+    assert!(matches!(
+        result,
+        Err(Error::FheProgramTooLarge { max: 50, nodes }) if nodes > 50
+    ));
+
+    let app = Compiler::new()
+        .fhe_program(unrolled)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .max_nodes(1000)
+        .compile();
+
+    assert!(app.is_ok());
+}
+
+#[test]
+fn program_exceeding_max_inputs_fails() {
+    #[fhe_program(scheme = "bfv")]
+    fn many_scalars(
+        a: Cipher<Signed>,
+        b: Cipher<Signed>,
+        c: Cipher<Signed>,
+        d: Cipher<Signed>,
+    ) -> Cipher<Signed> {
+        a + b + c + d
+    }
+
+    let result = Compiler::new()
+        .fhe_program(many_scalars)
+        .max_inputs(3)
+        .compile();
+
+    assert!(matches!(
+        result,
+        Err(Error::TooManyInputs { inputs: 4, max: 3 })
+    ));
+
+    let app = Compiler::new()
+        .fhe_program(many_scalars)
+        .max_inputs(4)
+        .compile();
+
+    assert!(app.is_ok());
+}
+
+#[test]
+fn can_pin_lattice_dimension() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .lattice_dimension(8192)
+        .compile()
+        .unwrap();
+
+    assert_eq!(app.params().lattice_dimension, 8192);
+    assert_eq!(
+        app.get_fhe_program(add)
+            .unwrap()
+            .metadata
+            .params
+            .lattice_dimension,
+        8192
+    );
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(5), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(add).unwrap(), vec![a, b], &public_key)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 20.into());
+}
+
+#[test]
+fn unsupported_lattice_dimension_fails() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    for n in [3000, 512, 65536] {
+        let result = Compiler::new()
+            .fhe_program(add)
+            .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+            .lattice_dimension(n)
+            .compile();
+
+        assert!(matches!(result, Err(Error::Unsupported(_))));
+    }
+}
+
+#[test]
+fn too_deep_program_reports_depth_and_margin() {
+    #[fhe_program(scheme = "bfv")]
+    fn deep(a: Cipher<Signed>) -> Cipher<Signed> {
+        let mut x = a;
+
+        for _ in 0..6 {
+            x = x * x;
+        }
+
+        x
+    }
+
+    // 4096 can't fit 6 multiplications, and pinning it keeps the search
+    // short.
+    let result = Compiler::new()
+        .fhe_program(deep)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .lattice_dimension(4096)
+        .additional_noise_budget(25)
+        .compile();
+
+    assert_eq!(
+        result.map(|_| ()),
+        Err(Error::NoParametersFound {
+            depth: 6,
+            requested_margin: 25
+        })
+    );
+}
+
+#[test]
+fn relinearization_strategies_agree() {
+    #[fhe_program(scheme = "bfv")]
+    fn mad(a: Cipher<Signed>, b: Cipher<Signed>, c: Cipher<Signed>) -> Cipher<Signed> {
+        a * b * c + c * a
+    }
+
+    for strategy in [
+        RelinearizationStrategy::EveryMultiply,
+        RelinearizationStrategy::Minimal,
+    ] {
+        let app = Compiler::new()
+            .fhe_program(mad)
+            .plain_modulus_constraint(PlainModulusConstraint::Raw(1024))
+            .relinearization_strategy(strategy)
+            .compile()
+            .unwrap();
+
+        let runtime = Runtime::new_fhe(app.params()).unwrap();
+        let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+        let args = [2, 3, 4]
+            .iter()
+            .map(|x| runtime.encrypt(Signed::from(*x), &public_key).unwrap())
+            .collect::<Vec<_>>();
+
+        let result = runtime
+            .run(app.get_fhe_program(mad).unwrap(), args, &public_key)
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(c, 32.into());
+    }
+}
+
+#[test]
+fn metadata_reports_unused_inputs() {
+    #[fhe_program(scheme = "bfv")]
+    fn first(a: Cipher<Signed>, _b: Cipher<Signed>) -> Cipher<Signed> {
+        a * a
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn both(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(first)
+        .fhe_program(both)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let program = app.get_fhe_program(first).unwrap();
+
+    assert_eq!(program.metadata.unused_inputs, vec![1]);
+    assert!(app
+        .get_fhe_program(both)
+        .unwrap()
+        .metadata
+        .unused_inputs
+        .is_empty());
+
+    // The unused argument is still required, but its value doesn't matter.
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(0), &public_key).unwrap();
+
+    let result = runtime.run(program, vec![a, b], &public_key).unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 9.into());
+}
+
+#[test]
+fn compile_report_explains_params() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul3(a: Cipher<Signed>, b: Cipher<Signed>, c: Cipher<Signed>) -> Cipher<Signed> {
+        a * b * c
+    }
+
+    let (app, report) = Compiler::new()
+        .fhe_program(mul3)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile_with_report()
+        .unwrap();
+
+    assert_eq!(&report.params, app.params());
+    assert_eq!(report.multiplicative_depth.get("mul3"), Some(&2));
+
+    let selected = report.candidates.last().unwrap();
+    let noise_budget = report.noise_budget.unwrap();
+
+    assert_eq!(selected.lattice_dimension, app.params().lattice_dimension);
+    assert_eq!(
+        selected.outcome,
+        CandidateOutcome::Selected { noise_budget }
+    );
+    assert!(noise_budget >= 20.);
+    assert!(report.noise_budget_consumed.unwrap() > 0.);
+
+    // Every smaller dimension was rejected.
+    assert!(report.candidates[..report.candidates.len() - 1]
+        .iter()
+        .all(|c| !matches!(c.outcome, CandidateOutcome::Selected { .. })));
+    assert_eq!(
+        report.binding_constraint.is_some(),
+        report.candidates.len() > 1
+    );
+    assert!(report.to_string().contains("selected"));
+
+    let (_, manual) = Compiler::new()
+        .fhe_program(mul3)
+        .with_params(app.params())
+        .compile_with_report()
+        .unwrap();
+
+    assert!(manual.candidates.is_empty());
+    assert_eq!(manual.noise_budget, None);
+    assert_eq!(manual.multiplicative_depth.get("mul3"), Some(&2));
+}
+
+#[test]
+fn outputs_keep_declaration_order() {
+    #[fhe_program(scheme = "bfv")]
+    fn three(
+        a: Cipher<Signed>,
+        b: Cipher<Signed>,
+    ) -> (Cipher<Signed>, Cipher<Signed>, Cipher<Signed>) {
+        // Dead code, removed during compilation.
+        let _ = -a;
+
+        (a * b, b, a + b)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(three)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(5), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(three).unwrap(), vec![a, b], &public_key)
+        .unwrap();
+
+    let result = result
+        .iter()
+        .map(|c| runtime.decrypt(c, &private_key).unwrap())
+        .collect::<Vec<Signed>>();
+
+    assert_eq!(
+        result,
+        vec![Signed::from(15), Signed::from(5), Signed::from(8)]
+    );
+}
+
+#[test]
+fn run_validates_arguments_against_signature() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let program = app.get_fhe_program(add).unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+    assert_eq!(
+        runtime.validate(program, &[a.clone()]),
+        Err(RuntimeError::TooFewArguments {
+            expected: 2,
+            actual: 1
+        })
+    );
+
+    let result = runtime.run(program, vec![a.clone(), a.clone(), a.clone()], &public_key);
+
+    assert_eq!(
+        result.err(),
+        Some(RuntimeError::TooManyArguments {
+            expected: 2,
+            actual: 3
+        })
+    );
+
+    // A plaintext where the signature declares a ciphertext.
+    let args: Vec<FheProgramInput> = vec![a.clone().into(), Signed::from(4).into()];
+
+    match runtime.run(program, args, &public_key) {
+        Err(RuntimeError::ArgumentTypeMismatch(e)) => {
+            assert_eq!(*e, (1, Cipher::<Signed>::type_name(), Signed::type_name()));
+        }
+        r => panic!("Expected ArgumentTypeMismatch, got {:?}", r.map(|_| ())),
+    }
+
+    assert_eq!(runtime.validate(program, &[a.clone(), a]), Ok(()));
+}
+
+#[test]
+fn sequential_run_matches_parallel_run() {
+    #[fhe_program(scheme = "bfv")]
+    fn wide(
+        a: Cipher<Signed>,
+        b: Cipher<Signed>,
+        c: Cipher<Signed>,
+        d: Cipher<Signed>,
+    ) -> (Cipher<Signed>, Cipher<Signed>) {
+        // Four independent branches feeding two outputs.
+        let w = a * b;
+        let x = c * d;
+        let y = a + c;
+        let z = b - d;
+
+        (w + x, y * z)
+    }
+
+    let app = Compiler::new().fhe_program(wide).compile().unwrap();
+    let program = app.get_fhe_program(wide).unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let args = [3, -5, 7, 2]
+        .iter()
+        .map(|x| runtime.encrypt(Signed::from(*x), &public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let parallel = runtime.run(program, args.clone(), &public_key).unwrap();
+
+    let sequential = runtime
+        .run_with_context(program, args, &public_key, &RunContext::new().sequential())
+        .unwrap();
+
+    assert_eq!(parallel.len(), sequential.len());
+
+    for (p, s) in parallel.iter().zip(sequential.iter()) {
+        let p: Signed = runtime.decrypt(p, &private_key).unwrap();
+        let s: Signed = runtime.decrypt(s, &private_key).unwrap();
+
+        assert_eq!(p, s);
+    }
+
+    let w: Signed = runtime.decrypt(&sequential[0], &private_key).unwrap();
+    let y: Signed = runtime.decrypt(&sequential[1], &private_key).unwrap();
+
+    assert_eq!(w, Signed::from(3 * -5 + 7 * 2));
+    assert_eq!(y, Signed::from((3 + 7) * (-5 - 2)));
+}
+
+#[test]
+fn can_relinearize_and_mod_switch_manually() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    // Minimal leaves a product that only feeds an output at 3 polynomials.
+    let app = Compiler::new()
+        .fhe_program(mul)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(1024))
+        .relinearization_strategy(RelinearizationStrategy::Minimal)
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(6), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(-7), &public_key).unwrap();
+
+    let product = runtime
+        .run(app.get_fhe_program(mul).unwrap(), vec![a, b], &public_key)
+        .unwrap()
+        .remove(0);
+
+    let seal_ciphertext = |c: &Ciphertext| match &c.inner {
+        InnerCiphertext::Seal(v) => v[0].data.clone(),
+    };
+
+    assert_eq!(seal_ciphertext(&product).num_polynomials(), 3);
+
+    let relinearized = runtime.relinearize(&product, &public_key).unwrap();
+
+    assert_eq!(seal_ciphertext(&relinearized).num_polynomials(), 2);
+
+    let c: Signed = runtime.decrypt(&relinearized, &private_key).unwrap();
+    assert_eq!(c, Signed::from(-42));
+
+    let switched = runtime.mod_switch_to_next(&relinearized).unwrap();
+
+    assert_eq!(
+        seal_ciphertext(&switched).coeff_modulus_size(),
+        seal_ciphertext(&relinearized).coeff_modulus_size() - 1
+    );
+
+    let c: Signed = runtime.decrypt(&switched, &private_key).unwrap();
+    assert_eq!(c, Signed::from(-42));
+
+    let (no_relin_key, _) = runtime
+        .generate_keys_for(Vec::<&CompiledFheProgram>::new())
+        .unwrap();
+
+    assert_eq!(
+        runtime.relinearize(&product, &no_relin_key).map(|_| ()),
+        Err(RuntimeError::MissingRelinearizationKeys)
+    );
+}
@@ -29,3 +29,227 @@ fn can_encrypt_decrypt() {
 
     assert_eq!(c, 20.into());
 }
+
+#[test]
+fn decrypt_with_budget_reports_consumed_noise() {
+    #[fhe_program(scheme = "bfv")]
+    fn cube(a: Cipher<Signed>) -> Cipher<Signed> {
+        a * a * a
+    }
+
+    let app = Compiler::new()
+        .fhe_program(cube)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+    let (fresh_val, fresh_budget) = runtime
+        .decrypt_with_budget::<Signed>(&a, &private_key)
+        .unwrap();
+
+    assert_eq!(fresh_val, 3.into());
+    assert_eq!(
+        fresh_budget,
+        runtime.measure_noise_budget(&a, &private_key).unwrap()
+    );
+
+    let result = runtime
+        .run(app.get_fhe_program(cube).unwrap(), vec![a], &public_key)
+        .unwrap();
+
+    let (c, budget): (Signed, u32) = runtime
+        .decrypt_with_budget(&result[0], &private_key)
+        .unwrap();
+
+    assert_eq!(c, 27.into());
+    assert!(budget > 0);
+    assert!(budget < fresh_budget);
+}
+
+#[test]
+fn typed_ciphertext_rejects_wrong_type() {
+    use sunscreen::types::bfv::{Fractional, Unsigned64};
+
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Unsigned64>, b: Cipher<Unsigned64>) -> Cipher<Unsigned64> {
+        a + b
+    }
+
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt_typed(Unsigned64::from(42), &public_key)
+        .unwrap();
+
+    // The decrypted type is inferred from the ciphertext.
+    let val = runtime.decrypt_typed(&a, &private_key).unwrap();
+    assert_eq!(val, Unsigned64::from(42));
+
+    // Once the type is erased, the runtime still catches misuse.
+    let result = runtime.decrypt::<Fractional<64>>(a.as_untyped(), &private_key);
+    assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))));
+
+    let result = TypedCiphertext::<Fractional<64>>::try_from(a.into_untyped());
+    assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))));
+}
+
+#[test]
+fn can_decrypt_ciphertext_from_external_seal_context() {
+    use seal_fhe::{
+        BfvEncryptionParametersBuilder, Context, Encryptor, Modulus, Plaintext as SealPlaintext,
+    };
+    use sunscreen::types::TypeName as _;
+
+    // Parameters chosen by some other SEAL-based system rather than by
+    // Sunscreen's compiler.
+    let coeff_modulus = vec![0x800004001, 0x800008001, 0x1000002001];
+
+    let params = Params {
+        lattice_dimension: 4096,
+        coeff_modulus: coeff_modulus.clone(),
+        plain_modulus: 65537,
+        scheme_type: SchemeType::Bfv,
+        security_level: SecurityLevel::TC128,
+    };
+
+    let params = Params::try_from_bytes(&params.to_bytes()).unwrap();
+    assert_eq!(params.coeff_modulus, coeff_modulus);
+
+    let runtime = Runtime::new_fhe(&params).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    // The other system builds its own SEAL context from the same numbers
+    // and encrypts under our public key.
+    let seal_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(4096)
+        .set_coefficient_modulus(
+            coeff_modulus
+                .iter()
+                .map(|q| Modulus::new(*q).unwrap())
+                .collect(),
+        )
+        .set_plain_modulus_u64(65537)
+        .build()
+        .unwrap();
+
+    let context = Context::new(&seal_params, true, SecurityLevel::TC128).unwrap();
+    let encryptor = Encryptor::with_public_key(&context, &public_key.public_key.data).unwrap();
+
+    // Signed encodes 42 as the constant polynomial 42.
+    let mut plaintext = SealPlaintext::new().unwrap();
+    plaintext.resize(1);
+    plaintext.set_coefficient(0, 42);
+
+    let ciphertext = Ciphertext {
+        data_type: Signed::type_name(),
+        inner: InnerCiphertext::Seal(vec![WithContext {
+            params: params.clone(),
+            data: encryptor.encrypt(&plaintext).unwrap(),
+        }]),
+    };
+
+    let val: Signed = runtime.decrypt(&ciphertext, &private_key).unwrap();
+
+    assert_eq!(val, Signed::from(42));
+}
+
+#[test]
+fn noise_budget_keeps_requested_margin() {
+    #[fhe_program(scheme = "bfv")]
+    fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+        a * a
+    }
+
+    let app = Compiler::new()
+        .fhe_program(square)
+        .additional_noise_budget(10)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(4), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(square).unwrap(), vec![a], &public_key)
+        .unwrap();
+
+    assert!(
+        runtime
+            .measure_noise_budget(&result[0], &private_key)
+            .unwrap()
+            >= 10
+    );
+
+    // A key from another parameter set can't measure this runtime's
+    // ciphertexts.
+    let other = Runtime::new_fhe(&Params {
+        plain_modulus: 1024,
+        ..app.params().clone()
+    })
+    .unwrap();
+
+    let (_, other_private_key) = other.generate_keys().unwrap();
+
+    assert!(matches!(
+        runtime.measure_noise_budget(&result[0], &other_private_key),
+        Err(RuntimeError::ParameterMismatch)
+    ));
+}
+
+#[test]
+fn symmetric_ciphertexts_are_smaller_and_interchangeable() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let symmetric = runtime
+        .encrypt_symmetric(Signed::from(15), &private_key)
+        .unwrap();
+    let asymmetric = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+
+    let symmetric_bytes = bincode::serialize(&symmetric).unwrap();
+
+    assert!(symmetric_bytes.len() < bincode::serialize(&asymmetric).unwrap().len());
+
+    let deserialized: Ciphertext = bincode::deserialize(&symmetric_bytes).unwrap();
+
+    for a in [symmetric, deserialized] {
+        let value: Signed = runtime.decrypt(&a, &private_key).unwrap();
+
+        assert_eq!(value, 15.into());
+
+        let result = runtime
+            .run(
+                app.get_fhe_program(add).unwrap(),
+                vec![a, asymmetric.clone()],
+                &public_key,
+            )
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(c, 30.into());
+    }
+}
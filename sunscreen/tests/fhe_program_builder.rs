@@ -0,0 +1,60 @@
+use sunscreen::{types::bfv::Signed, Compiler, Error, FheProgramBuilder, Runtime};
+
+#[test]
+fn can_run_configurable_sum_of_products() {
+    // Pairs of argument indices to multiply, as if read from a config file.
+    let terms = [(0, 1), (2, 3), (1, 2)];
+
+    let mut builder = FheProgramBuilder::<Signed>::new("sum_of_products");
+
+    let inputs = (0..4).map(|_| builder.input()).collect::<Vec<_>>();
+
+    let sum = terms
+        .iter()
+        .map(|(a, b)| inputs[*a].clone() * inputs[*b].clone())
+        .reduce(|acc, x| acc + x)
+        .unwrap();
+
+    builder.output(&sum);
+
+    let sum_of_products = builder.build().unwrap();
+
+    let app = Compiler::new()
+        .fhe_program(sum_of_products.clone())
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let values = [3i64, -4, 5, 6];
+
+    let args = values
+        .iter()
+        .map(|x| runtime.encrypt(Signed::from(*x), &public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(sum_of_products).unwrap(),
+            args,
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected: i64 = terms.iter().map(|(a, b)| values[*a] * values[*b]).sum();
+
+    assert_eq!(c, Signed::from(expected));
+}
+
+#[test]
+fn builder_without_outputs_fails() {
+    let mut builder = FheProgramBuilder::<Signed>::new("nothing");
+
+    let _ = builder.input();
+
+    assert!(matches!(builder.build(), Err(Error::BuilderError(_))));
+}
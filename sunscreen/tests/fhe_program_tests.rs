@@ -2,7 +2,7 @@ use sunscreen::{
     fhe::{FheFrontendCompilation, CURRENT_FHE_CTX},
     fhe_program,
     types::{bfv::Signed, Cipher, TypeName},
-    CallSignature, FheProgramFn, Params, SchemeType, SecurityLevel,
+    CallSignature, Error, FheProgramFn, Params, SchemeType, SecurityLevel,
 };
 
 use serde_json::json;
@@ -78,6 +78,39 @@ fn panicing_fhe_program_clears_ctx() {
     });
 }
 
+#[test]
+fn build_stops_at_max_nodes() {
+    #[fhe_program(scheme = "bfv")]
+    fn unrolled(a: Cipher<Signed>) -> Cipher<Signed> {
+        let mut x = a;
+
+        for _ in 0..100 {
+            x = x + a;
+        }
+
+        x
+    }
+
+    let result = unrolled.build_with_max_nodes(&get_params(), Some(50));
+
+    // Building stops at the first node past the limit rather than after
+    // the whole graph exists.
+    assert!(matches!(
+        result,
+        Err(Error::FheProgramTooLarge { nodes: 51, max: 50 })
+    ));
+
+    CURRENT_FHE_CTX.with(|ctx| {
+        let old = ctx.take();
+
+        assert!(old.is_none());
+    });
+
+    assert!(unrolled
+        .build_with_max_nodes(&get_params(), Some(102))
+        .is_ok());
+}
+
 #[test]
 fn capture_fhe_program_input_args() {
     #[fhe_program(scheme = "bfv")]
@@ -126,9 +159,9 @@ fn can_add() {
 
     let expected = json!({
         "nodes": [
-            { "operation": "InputCiphertext" },
-            { "operation": "InputCiphertext" },
-            { "operation": "InputCiphertext" },
+            { "operation": { "InputCiphertext": 0 } },
+            { "operation": { "InputCiphertext": 1 } },
+            { "operation": { "InputCiphertext": 2 } },
             { "operation": "Add" },
             { "operation": "Add" }
         ],
@@ -183,8 +216,8 @@ fn can_add_plaintext() {
 
     let expected = json!({
         "nodes": [
-            { "operation": "InputCiphertext" },
-            { "operation": "InputPlaintext" },
+            { "operation": { "InputCiphertext": 0 } },
+            { "operation": { "InputPlaintext": 1 } },
             { "operation": "AddPlaintext" },
         ],
         "node_holes": [],
@@ -230,9 +263,9 @@ fn can_mul() {
 
     let expected = json!({
         "nodes": [
-            { "operation": "InputCiphertext" },
-            { "operation": "InputCiphertext" },
-            { "operation": "InputCiphertext" },
+            { "operation": { "InputCiphertext": 0 } },
+            { "operation": { "InputCiphertext": 1 } },
+            { "operation": { "InputCiphertext": 2 } },
             { "operation": "Multiply" },
             { "operation": "Multiply" }
         ],
@@ -289,8 +322,8 @@ fn can_collect_output() {
 
     let expected = json!({
         "nodes": [
-          { "operation": "InputCiphertext" },
-          { "operation": "InputCiphertext" },
+          { "operation": { "InputCiphertext": 0 } },
+          { "operation": { "InputCiphertext": 1 } },
           { "operation": "Multiply" },
           { "operation": "Add" },
           { "operation": "Output" },
@@ -358,8 +391,8 @@ fn can_collect_multiple_outputs() {
 
     let expected = json!({
           "nodes": [
-            { "operation": "InputCiphertext" },
-            { "operation": "InputCiphertext"    },
+            { "operation": { "InputCiphertext": 0 } },
+            { "operation": { "InputCiphertext": 1 } },
             { "operation": "Multiply" },
             { "operation": "Add" },
             { "operation": "Output" },
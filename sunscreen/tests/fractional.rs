@@ -3,8 +3,8 @@
 use float_cmp::ApproxEq;
 use sunscreen::{
     fhe_program,
-    types::{bfv::Fractional, Cipher},
-    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+    types::{bfv::Fractional, weighted_sum, Cipher},
+    Compiler, FheProgramInput, PlainModulusConstraint, Runtime, RuntimeError,
 };
 
 use std::ops::*;
@@ -393,6 +393,37 @@ fn can_div_cipher_const() {
     test_div(4294967295.);
 }
 
+#[test]
+fn can_try_div_cipher_const() {
+    #[fhe_program(scheme = "bfv")]
+    fn halve(a: Cipher<Fractional<64>>) -> Cipher<Fractional<64>> {
+        a.try_div_const(2.0).unwrap()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(halve)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(100000))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a_c = runtime
+        .encrypt(Fractional::<64>::from(6.0), &public_key)
+        .unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(halve).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let c: Fractional<64> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert!(c.approx_eq(3.0, (0.0, 1)));
+}
+
 #[test]
 fn can_negate() {
     #[fhe_program(scheme = "bfv")]
@@ -437,7 +468,146 @@ fn can_negate() {
     test_div(4294967295.);
 }
 
+#[test]
+fn can_compute_weighted_sum() {
+    const WEIGHTS: [f64; 3] = [0.5, -2.0, 1.25];
+
+    #[fhe_program(scheme = "bfv")]
+    fn dot(a: CipherFractional, b: CipherFractional, c: CipherFractional) -> CipherFractional {
+        weighted_sum(&[a, b, c], &WEIGHTS)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(dot)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(100000))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let values = [3.0, 1.5, -4.0];
+
+    let args = values
+        .iter()
+        .map(|v| {
+            runtime
+                .encrypt(Fractional::<64>::from(*v), &public_key)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let result = runtime
+        .run(app.get_fhe_program(dot).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Fractional<64> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected: f64 = values.iter().zip(WEIGHTS).map(|(v, w)| v * w).sum();
+
+    assert_eq!(Into::<f64>::into(c), expected);
+}
+
 #[test]
 fn can_create_default() {
     assert_eq!(Into::<f64>::into(Fractional::<64>::default()), 0.0f64);
 }
+
+#[test]
+fn encrypting_non_finite_values_fails() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: CipherFractional, b: CipherFractional) -> CipherFractional {
+        a + b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    for (val, msg) in [
+        (f64::NAN, "Value is NaN."),
+        (f64::INFINITY, "Value is infinite."),
+        (f64::NEG_INFINITY, "Value is infinite."),
+    ] {
+        let err = runtime
+            .encrypt(Fractional::<64>::from(val), &public_key)
+            .unwrap_err();
+
+        assert!(matches!(&err, RuntimeError::InvalidFloatValue(m) if m.as_str() == msg));
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid floating point value: {}", msg)
+        );
+    }
+}
+
+#[test]
+fn can_scale_pow2() {
+    #[fhe_program(scheme = "bfv")]
+    fn scale(
+        a: Cipher<Fractional<64>>,
+    ) -> (
+        Cipher<Fractional<64>>,
+        Cipher<Fractional<64>>,
+        Cipher<Fractional<64>>,
+    ) {
+        (
+            a.scale_pow2(3),
+            a.scale_pow2(-3),
+            a.scale_pow2(3).scale_pow2(-3),
+        )
+    }
+
+    let app = Compiler::new()
+        .fhe_program(scale)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(100000))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a_c = runtime
+        .encrypt(Fractional::<64>::from(-3.25), &public_key)
+        .unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(scale).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let up: Fractional<64> = runtime.decrypt(&result[0], &private_key).unwrap();
+    let down: Fractional<64> = runtime.decrypt(&result[1], &private_key).unwrap();
+    let round_trip: Fractional<64> = runtime.decrypt(&result[2], &private_key).unwrap();
+
+    assert_eq!(up, Fractional::from(-26.0));
+    assert_eq!(down, Fractional::from(-0.40625));
+    assert_eq!(round_trip, Fractional::from(-3.25));
+}
+
+#[test]
+fn scale_pow2_past_int_bits_is_rejected() {
+    #[fhe_program(scheme = "bfv")]
+    fn scale(a: Cipher<Fractional<8>>) -> Cipher<Fractional<8>> {
+        assert!(matches!(
+            a.try_scale_pow2(8),
+            Err(RuntimeError::FheTypeError(_))
+        ));
+
+        a.try_scale_pow2(7).unwrap()
+    }
+
+    Compiler::new()
+        .fhe_program(scale)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(100000))
+        .compile()
+        .unwrap();
+}
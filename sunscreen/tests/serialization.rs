@@ -1,7 +1,12 @@
 use seal_fhe::{CoefficientModulus, SecurityLevel};
-use sunscreen::types::bfv::Signed;
+use sunscreen::{
+    fhe::FheFrontendCompilation,
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, FheProgramFn,
+};
 use sunscreen_fhe_program::SchemeType;
-use sunscreen_runtime::{Ciphertext, Params, Runtime};
+use sunscreen_runtime::{Ciphertext, FheProgramMetadata, Params, Runtime};
 
 #[test]
 fn can_roundtrip_ciphertexts_bincode() {
@@ -62,3 +67,185 @@ fn can_roundtrip_ciphertexts_json() {
     let actual: i64 = v.into();
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn client_can_encrypt_with_metadata_only() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+    let server_program = app.get_fhe_program(add).unwrap();
+
+    let metadata_bytes = server_program.metadata.to_bytes().unwrap();
+
+    // The client only ever sees the metadata.
+    let metadata = FheProgramMetadata::from_bytes(&metadata_bytes).unwrap();
+    assert_eq!(metadata, server_program.metadata);
+
+    let client = Runtime::new_fhe(&metadata.params).unwrap();
+    let (public_key, private_key) = client.generate_keys().unwrap();
+
+    let a = client.encrypt(Signed::from(15), &public_key).unwrap();
+    let b = client.encrypt(Signed::from(27), &public_key).unwrap();
+
+    let server = Runtime::new_fhe(app.params()).unwrap();
+    let result = server.run(server_program, vec![a, b], &public_key).unwrap();
+
+    let c: Signed = client.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(42));
+}
+
+#[test]
+fn compilation_is_deterministic() {
+    #[fhe_program(scheme = "bfv")]
+    fn simple_multiply(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b * 3 + a * 3
+    }
+
+    let compile = || {
+        let app = Compiler::new()
+            .fhe_program(simple_multiply)
+            .compile()
+            .unwrap();
+
+        bincode::serialize(app.get_fhe_program(simple_multiply).unwrap()).unwrap()
+    };
+
+    let expected = compile();
+
+    for _ in 0..4 {
+        assert_eq!(compile(), expected);
+    }
+}
+
+#[test]
+fn can_compile_deserialized_frontend() {
+    #[fhe_program(scheme = "bfv")]
+    fn mad(a: Cipher<Signed>, b: Cipher<Signed>, c: Cipher<Signed>) -> Cipher<Signed> {
+        a * b + c
+    }
+
+    let app = Compiler::new().fhe_program(mad).compile().unwrap();
+    let params = app.params();
+
+    let frontend = bincode::serialize(&mad.build(params).unwrap()).unwrap();
+    let frontend: FheFrontendCompilation = bincode::deserialize(&frontend).unwrap();
+
+    let prebuilt_app = Compiler::new()
+        .with_frontend("mad", mad.signature(), frontend)
+        .compile()
+        .unwrap();
+
+    let expected = app.get_fhe_program(mad).unwrap();
+    let actual = prebuilt_app.get_fhe_program("mad").unwrap();
+
+    assert_eq!(actual.metadata, expected.metadata);
+    assert_eq!(
+        bincode::serialize(&actual.fhe_program_fn).unwrap(),
+        bincode::serialize(&expected.fhe_program_fn).unwrap()
+    );
+
+    let runtime = Runtime::new_fhe(prebuilt_app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let args = [3, 4, 5]
+        .iter()
+        .map(|x| runtime.encrypt(Signed::from(*x), &public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let result = runtime.run(actual, args, &public_key).unwrap();
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(17));
+}
+
+#[test]
+fn can_run_on_ciphertexts_from_bytes() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(27), &public_key).unwrap();
+
+    let a = Ciphertext::from_bytes(&a.to_bytes().unwrap(), app.params()).unwrap();
+    let b = Ciphertext::from_bytes(&b.to_bytes().unwrap(), app.params()).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(add).unwrap(), vec![a, b], &public_key)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(42));
+}
+
+#[test]
+fn ciphertext_from_bytes_rejects_other_params() {
+    let params = Params {
+        lattice_dimension: 4096,
+        plain_modulus: 1024,
+        coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+            .unwrap()
+            .iter()
+            .map(|c| c.value())
+            .collect(),
+        security_level: SecurityLevel::TC128,
+        scheme_type: SchemeType::Bfv,
+    };
+
+    let runtime = Runtime::new_fhe(&params).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let bytes = runtime
+        .encrypt(Signed::from(42), &public_key)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    let other = Params {
+        plain_modulus: 2048,
+        ..params
+    };
+
+    assert!(matches!(
+        Ciphertext::from_bytes(&bytes, &other),
+        Err(sunscreen_runtime::Error::ParameterMismatch)
+    ));
+}
+
+#[test]
+fn ciphertext_from_bytes_rejects_oversized_lengths() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let mut bytes = runtime
+        .encrypt(Signed::from(42), &public_key)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    // The ciphertext starts with the length of its type's name.
+    bytes[0..8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+    assert!(matches!(
+        Ciphertext::from_bytes(&bytes, app.params()),
+        Err(sunscreen_runtime::Error::BincodeError(_))
+    ));
+}
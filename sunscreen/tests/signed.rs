@@ -1,7 +1,7 @@
 use sunscreen::{
     fhe_program,
     types::{bfv::Signed, Cipher},
-    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+    Ciphertext, Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
 };
 
 use std::ops::*;
@@ -216,6 +216,45 @@ fn can_sub_cipher_cipher() {
     assert_eq!(c, sub_fn(a, b));
 }
 
+#[test]
+fn sub_respects_operand_order() {
+    #[fhe_program(scheme = "bfv")]
+    fn sub(a: Cipher<Signed>, b: Cipher<Signed>) -> (Cipher<Signed>, Cipher<Signed>) {
+        // b - a puts the later input on the left, so a backend reading
+        // operands in insertion order rather than by edge role would
+        // swap one of these.
+        (a - b, b - a)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(sub)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a_c = runtime.encrypt(Signed::from(10), &public_key).unwrap();
+    let b_c = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(sub).unwrap(),
+            vec![a_c, b_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let a_minus_b: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+    let b_minus_a: Signed = runtime.decrypt(&result[1], &private_key).unwrap();
+
+    assert_eq!(a_minus_b, Signed::from(7));
+    assert_eq!(b_minus_a, Signed::from(-7));
+}
+
 #[test]
 fn can_sub_cipher_plain() {
     #[fhe_program(scheme = "bfv")]
@@ -344,6 +383,39 @@ fn can_sub_literal_cipher() {
     assert_eq!(c, sub_fn(-4, a));
 }
 
+#[test]
+fn can_negate_cipher() {
+    #[fhe_program(scheme = "bfv")]
+    fn neg(a: Cipher<Signed>) -> Cipher<Signed> {
+        -a
+    }
+
+    let app = Compiler::new()
+        .fhe_program(neg)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for a in [5, -5, 0] {
+        let a_c = runtime.encrypt(Signed::from(a), &public_key).unwrap();
+
+        let args: Vec<FheProgramInput> = vec![a_c.into()];
+
+        let result = runtime
+            .run(app.get_fhe_program(neg).unwrap(), args, &public_key)
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(c, Signed::from(-a));
+    }
+}
+
 fn mul_fn<T, U, R>(a: T, b: U) -> R
 where
     T: Mul<U, Output = R>,
@@ -517,3 +589,351 @@ fn can_mul_literal_cipher() {
 fn can_create_default() {
     assert_eq!(Into::<i64>::into(Signed::default()), 0);
 }
+
+#[test]
+fn can_shift_cipher_value() {
+    #[fhe_program(scheme = "bfv")]
+    fn shift(a: Cipher<Signed>) -> (Cipher<Signed>, Cipher<Signed>) {
+        (a.shl_value(3), a.shr_value(2))
+    }
+
+    let app = Compiler::new()
+        .fhe_program(shift)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for (a, shl, shr) in [(13, 104, 3), (-13, -104, -3), (3, 24, 0)] {
+        let a_c = runtime.encrypt(Signed::from(a), &public_key).unwrap();
+
+        let result = runtime
+            .run(app.get_fhe_program(shift).unwrap(), vec![a_c], &public_key)
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+        let d: Signed = runtime.decrypt(&result[1], &private_key).unwrap();
+
+        assert_eq!(c, Signed::from(shl));
+        assert_eq!(d, Signed::from(shr));
+    }
+}
+
+#[test]
+fn shr_value_drops_bits_below_shift() {
+    #[fhe_program(scheme = "bfv")]
+    fn shift(a: Cipher<Signed>) -> (Cipher<Signed>, Cipher<Signed>) {
+        (a.shr_value(3), a.shr_value(5))
+    }
+
+    let app = Compiler::new()
+        .fhe_program(shift)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    // Each value sets bits both below and above the shift amounts.
+    for (a, shr_3, shr_5) in [
+        (0b1011_0110, 0b1_0110, 0b101),
+        (-0b1011_0110, -0b1_0110, -0b101),
+        (0b1_0001_1111, 0b10_0011, 0b1000),
+    ] {
+        let a_c = runtime.encrypt(Signed::from(a), &public_key).unwrap();
+
+        let result = runtime
+            .run(app.get_fhe_program(shift).unwrap(), vec![a_c], &public_key)
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+        let d: Signed = runtime.decrypt(&result[1], &private_key).unwrap();
+
+        assert_eq!(c, Signed::from(shr_3));
+        assert_eq!(d, Signed::from(shr_5));
+    }
+}
+
+#[test]
+fn can_cond_add_cipher() {
+    #[fhe_program(scheme = "bfv")]
+    fn accumulate(
+        acc: Cipher<Signed>,
+        c_0: Cipher<Signed>,
+        d_0: Cipher<Signed>,
+        c_1: Cipher<Signed>,
+        d_1: Cipher<Signed>,
+        c_2: Cipher<Signed>,
+        d_2: Cipher<Signed>,
+    ) -> Cipher<Signed> {
+        acc.cond_add(c_0, d_0).cond_add(c_1, d_1).cond_add(c_2, d_2)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(accumulate)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let pairs = [(1, 5), (0, 100), (1, -7)];
+
+    let mut args = vec![runtime.encrypt(Signed::from(10), &public_key).unwrap()];
+
+    for (c, d) in pairs {
+        args.push(runtime.encrypt(Signed::from(c), &public_key).unwrap());
+        args.push(runtime.encrypt(Signed::from(d), &public_key).unwrap());
+    }
+
+    let result = runtime
+        .run(app.get_fhe_program(accumulate).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(10 + 5 - 7));
+}
+
+#[test]
+fn streamed_outputs_match_run() {
+    #[fhe_program(scheme = "bfv")]
+    fn stats(
+        a: Cipher<Signed>,
+        b: Cipher<Signed>,
+    ) -> (Cipher<Signed>, Cipher<Signed>, Cipher<Signed>) {
+        (a + b, a - b, a * b)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(stats)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a_c = runtime.encrypt(Signed::from(7), &public_key).unwrap();
+    let b_c = runtime.encrypt(Signed::from(-3), &public_key).unwrap();
+
+    let program = app.get_fhe_program(stats).unwrap();
+
+    let expected = runtime
+        .run(program, vec![a_c.clone(), b_c.clone()], &public_key)
+        .unwrap();
+
+    let mut streamed = runtime
+        .run_streaming(program, vec![a_c, b_c], &public_key)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    streamed.sort_by_key(|(i, _)| *i);
+
+    assert_eq!(
+        streamed.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    for ((_, actual), expected) in streamed.iter().zip(expected.iter()) {
+        let actual: Signed = runtime.decrypt(actual, &private_key).unwrap();
+        let expected: Signed = runtime.decrypt(expected, &private_key).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn run_many_matches_run() {
+    #[fhe_program(scheme = "bfv")]
+    fn mad(a: Cipher<Signed>, b: Cipher<Signed>, c: Signed) -> Cipher<Signed> {
+        a * b + c
+    }
+
+    let app = Compiler::new()
+        .fhe_program(mad)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(mad).unwrap();
+
+    let inputs = (-3..5)
+        .map(|i| {
+            (
+                runtime.encrypt(Signed::from(i), &public_key).unwrap(),
+                runtime
+                    .encrypt(Signed::from(2 * i - 1), &public_key)
+                    .unwrap(),
+                Signed::from(i + 7),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let make_args = |(a, b, c): &(Ciphertext, Ciphertext, Signed)| -> Vec<FheProgramInput> {
+        vec![a.clone().into(), b.clone().into(), (*c).into()]
+    };
+
+    let expected = inputs
+        .iter()
+        .map(|x| runtime.run(program, make_args(x), &public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let arguments = inputs.iter().map(make_args).collect::<Vec<_>>();
+
+    let results = runtime.run_many(program, arguments, &public_key).unwrap();
+
+    assert_eq!(results.len(), expected.len());
+
+    for (i, (result, expected)) in results.iter().zip(expected.iter()).enumerate() {
+        assert_eq!(result.len(), 1);
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+        let e: Signed = runtime.decrypt(&expected[0], &private_key).unwrap();
+
+        let x = i as i64 - 3;
+
+        assert_eq!(c, e);
+        assert_eq!(c, Signed::from(x * (2 * x - 1) + x + 7));
+    }
+}
+
+#[test]
+fn can_mul_mixed_signs() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(mul)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for (a, b) in [(-7, 6), (7, -6), (-7, -6), (-1, -1), (-1, 1), (0, -5)] {
+        let a_c = runtime.encrypt(Signed::from(a), &public_key).unwrap();
+        let b_c = runtime.encrypt(Signed::from(b), &public_key).unwrap();
+
+        let result = runtime
+            .run(
+                app.get_fhe_program(mul).unwrap(),
+                vec![a_c, b_c],
+                &public_key,
+            )
+            .unwrap();
+
+        let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(i64::from(c), a * b, "{} * {}", a, b);
+    }
+}
+
+#[test]
+fn can_sum_and_multiply_iterators() {
+    #[fhe_program(scheme = "bfv")]
+    fn sum_product(a: [Cipher<Signed>; 3]) -> (Cipher<Signed>, Cipher<Signed>) {
+        (a.iter().sum(), a.into_iter().product())
+    }
+
+    let app = Compiler::new()
+        .fhe_program(sum_product)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = [-2, 3, -5].map(Signed::from);
+    let a = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(sum_product).unwrap(),
+            vec![a],
+            &public_key,
+        )
+        .unwrap();
+
+    let sum: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+    let product: Signed = runtime.decrypt(&result[1], &private_key).unwrap();
+
+    assert_eq!(sum, Signed::from(-4));
+    assert_eq!(product, Signed::from(30));
+}
+
+#[test]
+fn addition_chains_are_balanced() {
+    use petgraph::stable_graph::NodeIndex;
+    use sunscreen_compiler_common::{EdgeInfo, GraphQuery, NodeInfo};
+    use sunscreen_fhe_program::Operation;
+
+    fn add_depth(query: &GraphQuery<NodeInfo<Operation>, EdgeInfo>, id: NodeIndex) -> usize {
+        if query.get_node(id).unwrap().operation != Operation::Add {
+            return 0;
+        }
+
+        let (left, right) = query.get_binary_operands(id).unwrap();
+
+        1 + add_depth(query, left).max(add_depth(query, right))
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn sum(a: [Cipher<Signed>; 8]) -> Cipher<Signed> {
+        a[0] + a[1] + a[2] + a[3] + a[4] + a[5] + a[6] + a[7]
+    }
+
+    let app = Compiler::new().fhe_program(sum).compile().unwrap();
+
+    let program = app.get_fhe_program(sum).unwrap();
+    let graph = &program.fhe_program_fn.graph;
+    let query = GraphQuery::new(&graph.0);
+
+    let out = graph
+        .node_indices()
+        .find(|id| graph[*id].operation == Operation::OutputCiphertext)
+        .unwrap();
+
+    // The frontend builds 7 additions one after another. Balanced, they
+    // take 3 steps.
+    assert_eq!(add_depth(&query, query.get_unary_operand(out).unwrap()), 3);
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let values = [1, -2, 3, -4, 5, -6, 7, 100];
+    let a = runtime
+        .encrypt(values.map(Signed::from), &public_key)
+        .unwrap();
+
+    let result = runtime.run(program, vec![a], &public_key).unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(i64::from(c), values.iter().sum::<i64>());
+}
@@ -1,7 +1,8 @@
 use sunscreen::{
     fhe_program,
-    types::{bfv::Batched, Cipher, SwapRows},
-    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+    types::{bfv::Batched, intern::FheProgramNode, Cipher, SwapRows},
+    Compiler, FheProgramInput, PlainModulusConstraint, PublicKey, RequiredKeys, Runtime,
+    RuntimeError,
 };
 
 use std::ops::*;
@@ -50,6 +51,40 @@ fn can_swap_rows_cipher() {
     assert_eq!(c, expected.try_into().unwrap());
 }
 
+#[test]
+fn swapping_rows_twice_is_identity() {
+    #[fhe_program(scheme = "bfv")]
+    fn swap_twice(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.swap_rows().swap_rows()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(swap_twice)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(swap_twice).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, a);
+}
+
 #[test]
 fn can_rotate_left_cipher() {
     fn shl_impl<T>(x: T, y: u64) -> T
@@ -301,3 +336,870 @@ fn can_neg_cipher_cipher() {
 
     assert_eq!(c, neg_impl(a));
 }
+
+#[test]
+fn batched_decrypt_reports_noise_validity() {
+    fn sum_squares<T>(a: T) -> T
+    where
+        T: Mul<T, Output = T> + Add<T, Output = T> + Shl<u64, Output = T> + Copy,
+    {
+        let x = a * a;
+        let x = x + (x << 1);
+
+        x + (x << 2)
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn reduce(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        sum_squares(a)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(reduce)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(reduce).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let budget = runtime
+        .measure_noise_budget(&result[0], &private_key)
+        .unwrap();
+
+    let (c, valid): (Batched<4>, bool) = runtime
+        .decrypt_batched_checked(&result[0], &private_key, budget)
+        .unwrap();
+
+    assert!(valid);
+    assert_eq!(c, sum_squares(a));
+
+    let (c, valid): (Batched<4>, bool) = runtime
+        .decrypt_batched_checked(&result[0], &private_key, budget + 1)
+        .unwrap();
+
+    assert!(!valid);
+    assert_eq!(c, sum_squares(a));
+}
+
+#[test]
+fn can_rotate_by_encrypted_amount() {
+    #[fhe_program(scheme = "bfv")]
+    fn rotate(a: Cipher<Batched<4>>, amount: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.rotate_by_encrypted(amount, 3)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(rotate)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![1, 2, 3, 4], vec![5, 6, 7, 8]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+    let amount_c = runtime.encrypt(Batched::<4>::from(2), &public_key).unwrap();
+
+    let args: Vec<FheProgramInput> = vec![a_c.into(), amount_c.into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(rotate).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, a << 2);
+}
+
+#[test]
+fn can_one_hot_encode_encrypted_index() {
+    #[fhe_program(scheme = "bfv")]
+    fn one_hot(index: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        index.one_hot(3)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(one_hot)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for index in 0..3 {
+        let index_c = runtime
+            .encrypt(Batched::<4>::from(index), &public_key)
+            .unwrap();
+
+        let result = runtime
+            .run(
+                app.get_fhe_program(one_hot).unwrap(),
+                vec![index_c],
+                &public_key,
+            )
+            .unwrap();
+
+        let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        let mut row = vec![0; 4];
+        row[index as usize] = 1;
+
+        let expected = Batched::<4>::try_from([row.clone(), row]).unwrap();
+
+        assert_eq!(c, expected);
+    }
+}
+
+#[test]
+fn can_one_hot_encode_single_slot() {
+    #[fhe_program(scheme = "bfv")]
+    fn one_hot(index: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        index.one_hot(1)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(one_hot)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let index_c = runtime.encrypt(Batched::<4>::from(0), &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(one_hot).unwrap(),
+            vec![index_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![1, 0, 0, 0], vec![1, 0, 0, 0]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_look_up_encrypted_index_in_plaintext_table() {
+    #[fhe_program(scheme = "bfv")]
+    fn lookup(index: Cipher<Batched<8>>, table: Batched<8>) -> Cipher<Batched<8>> {
+        index.lookup(table, 8)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(lookup)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let table = [
+        vec![10, 20, 30, 40, 50, 60, 70, 80],
+        vec![-1, -2, -3, -4, -5, -6, -7, -8],
+    ];
+
+    for index in [0usize, 3, 7] {
+        let index_c = runtime
+            .encrypt(Batched::<8>::from(index as i64), &public_key)
+            .unwrap();
+
+        let args: Vec<FheProgramInput> = vec![
+            index_c.into(),
+            Batched::<8>::try_from(table.clone()).unwrap().into(),
+        ];
+
+        let result = runtime
+            .run(app.get_fhe_program(lookup).unwrap(), args, &public_key)
+            .unwrap();
+
+        let c: Batched<8> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        let expected =
+            Batched::<8>::try_from([vec![table[0][index]; 8], vec![table[1][index]; 8]]).unwrap();
+
+        assert_eq!(c, expected);
+    }
+}
+
+#[test]
+fn can_look_up_in_single_entry_table() {
+    #[fhe_program(scheme = "bfv")]
+    fn lookup(index: Cipher<Batched<4>>, table: Batched<4>) -> Cipher<Batched<4>> {
+        index.lookup(table, 1)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(lookup)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let index_c = runtime.encrypt(Batched::<4>::from(0), &public_key).unwrap();
+
+    let args: Vec<FheProgramInput> = vec![
+        index_c.into(),
+        Batched::<4>::try_from([vec![42, 1, 2, 3], vec![-7, 4, 5, 6]])
+            .unwrap()
+            .into(),
+    ];
+
+    let result = runtime
+        .run(app.get_fhe_program(lookup).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(
+        c,
+        Batched::<4>::try_from([vec![42; 4], vec![-7; 4]]).unwrap()
+    );
+}
+
+#[test]
+fn can_prefix_product() {
+    #[fhe_program(scheme = "bfv")]
+    fn scan(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.prefix_product()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(scan)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![1, 2, 3, 4], vec![2, -1, 5, 3]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(scan).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![1, 2, 6, 24], vec![2, -2, -10, -30]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_bit_reverse() {
+    #[fhe_program(scheme = "bfv")]
+    fn bit_reverse(a: Cipher<Batched<8>>) -> Cipher<Batched<8>> {
+        a.bit_reverse(8)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(bit_reverse)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [
+        vec![0, 1, 2, 3, 4, 5, 6, 7],
+        vec![8, 9, 10, 11, 12, 13, 14, 15],
+    ];
+
+    let a = Batched::<8>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(bit_reverse).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<8> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<8>::try_from([
+        vec![0, 4, 2, 6, 1, 5, 3, 7],
+        vec![8, 12, 10, 14, 9, 13, 11, 15],
+    ])
+    .unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_compare_against_plaintext_threshold() {
+    #[fhe_program(scheme = "bfv")]
+    fn threshold(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.ge_const(1, -5..=5)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(threshold)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![-3, 0, 2, 5], vec![1, -1, 4, -5]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(threshold).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![0, 0, 1, 1], vec![1, 0, 1, 0]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_compute_signum() {
+    #[fhe_program(scheme = "bfv")]
+    fn sign(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.signum(-4..=4)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(sign)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![-3, 0, 2, 4], vec![0, -1, 1, -4]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(sign).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![-1, 0, 1, 1], vec![0, -1, 1, -1]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_clamp_cipher() {
+    #[fhe_program(scheme = "bfv")]
+    fn clamp(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.clamp(-2, 3, -5..=5)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(clamp)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    // Lanes below, within, and above [-2, 3].
+    let data = [vec![-5, -2, 0, 3], vec![5, -3, 2, 4]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(clamp).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![-2, -2, 0, 3], vec![3, -2, 2, 3]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_raise_constant_base_to_encrypted_exponent() {
+    #[fhe_program(scheme = "bfv")]
+    fn pow2(
+        b_0: Cipher<Batched<4>>,
+        b_1: Cipher<Batched<4>>,
+        b_2: Cipher<Batched<4>>,
+    ) -> Cipher<Batched<4>> {
+        type Node = FheProgramNode<Cipher<Batched<4>>>;
+
+        Node::pow_const_base(2, &[b_0, b_1, b_2])
+    }
+
+    let app = Compiler::new()
+        .fhe_program(pow2)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let exponents = [vec![5, 0, 3, 7], vec![1, 2, 4, 6]];
+
+    let bits = (0..3)
+        .map(|i| {
+            let bit = exponents
+                .iter()
+                .map(|row| row.iter().map(|e| (e >> i) & 1).collect())
+                .collect::<Vec<Vec<i64>>>();
+
+            let bit = Batched::<4>::try_from([bit[0].clone(), bit[1].clone()]).unwrap();
+
+            runtime.encrypt(bit, &public_key).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let result = runtime
+        .run(app.get_fhe_program(pow2).unwrap(), bits, &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![32, 1, 8, 128], vec![2, 4, 16, 64]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn pow_const_base_ignores_bits_past_unit_power() {
+    #[fhe_program(scheme = "bfv")]
+    fn pow_minus_one(
+        b_0: Cipher<Batched<4>>,
+        b_1: Cipher<Batched<4>>,
+        b_2: Cipher<Batched<4>>,
+    ) -> Cipher<Batched<4>> {
+        type Node = FheProgramNode<Cipher<Batched<4>>>;
+
+        // (-1)^2 = 1, so only the lowest bit matters.
+        Node::pow_const_base(Node::get_plain_modulus() - 1, &[b_0, b_1, b_2])
+    }
+
+    let app = Compiler::new()
+        .fhe_program(pow_minus_one)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let exponents = [vec![5, 0, 3, 6], vec![1, 2, 4, 7]];
+
+    let bits = (0..3)
+        .map(|i| {
+            let bit = exponents
+                .iter()
+                .map(|row| row.iter().map(|e| (e >> i) & 1).collect())
+                .collect::<Vec<Vec<i64>>>();
+
+            let bit = Batched::<4>::try_from([bit[0].clone(), bit[1].clone()]).unwrap();
+
+            runtime.encrypt(bit, &public_key).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(pow_minus_one).unwrap(),
+            bits,
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = Batched::<4>::try_from([vec![-1, 1, -1, 1], vec![-1, 1, 1, -1]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_pad_to_pow2_before_reduction() {
+    #[fhe_program(scheme = "bfv")]
+    fn padded_sum(a: Cipher<Batched<8>>) -> Cipher<Batched<8>> {
+        let mut x = a.pad_to_pow2(5, 0);
+
+        for shift in [4, 2, 1] {
+            x = x + (x << shift);
+        }
+
+        x
+    }
+
+    let app = Compiler::new()
+        .fhe_program(padded_sum)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    // Lanes 5-7 hold garbage that must not reach the sum.
+    let data = [
+        vec![1, 2, 3, 4, 5, 100, 200, 300],
+        vec![6, 7, 8, 9, 10, -100, -200, -300],
+    ];
+
+    let a = Batched::<8>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(padded_sum).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<8> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c[(0, 0)], 15);
+    assert_eq!(c[(1, 0)], 40);
+}
+
+#[test]
+fn can_extract_slot() {
+    #[fhe_program(scheme = "bfv")]
+    fn extract(a: Cipher<Batched<8>>) -> Cipher<Batched<8>> {
+        a.extract_slot(3)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(extract)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [
+        vec![1, 2, 3, 4, 5, 6, 7, 8],
+        vec![9, 10, 11, 12, 13, 14, 15, 16],
+    ];
+
+    let a = Batched::<8>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(extract).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<8> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected =
+        Batched::<8>::try_from([vec![4, 0, 0, 0, 0, 0, 0, 0], vec![12, 0, 0, 0, 0, 0, 0, 0]])
+            .unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn can_multiply_without_galois_keys() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Batched<4>>, b: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a * b
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn rotate(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a << 1
+    }
+
+    let app = Compiler::new()
+        .fhe_program(mul)
+        .fhe_program(rotate)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let public_key = PublicKey {
+        galois_key: None,
+        ..public_key
+    };
+
+    assert!(!public_key.can_rotate());
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let b = Batched::<4>::try_from([vec![2, 2, 2, 2], vec![3, 3, 3, 3]]).unwrap();
+
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+    let b_c = runtime.encrypt(b, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(mul).unwrap(),
+            vec![a_c.clone(), b_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, a * b);
+
+    let result = runtime.run(app.get_fhe_program(rotate).unwrap(), vec![a_c], &public_key);
+
+    assert!(matches!(result, Err(RuntimeError::MissingGaloisKeys)));
+}
+
+#[test]
+fn generates_only_required_keys() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul_add(a: Cipher<Batched<4>>, b: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a * b + a
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn rotate(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        (a << 1).swap_rows()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(mul_add)
+        .fhe_program(rotate)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let mul_add_program = app.get_fhe_program(mul_add).unwrap();
+    let rotate_program = app.get_fhe_program(rotate).unwrap();
+
+    assert_eq!(
+        mul_add_program.metadata.required_keys,
+        vec![RequiredKeys::Relin]
+    );
+    assert_eq!(
+        rotate_program.metadata.required_keys,
+        vec![RequiredKeys::Galois]
+    );
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys_for([mul_add_program]).unwrap();
+
+    assert!(!public_key.can_rotate());
+    assert!(public_key.can_relinearize());
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let b = Batched::<4>::try_from([vec![2, 2, 2, 2], vec![3, 3, 3, 3]]).unwrap();
+
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+    let b_c = runtime.encrypt(b, &public_key).unwrap();
+
+    let result = runtime
+        .run(mul_add_program, vec![a_c.clone(), b_c], &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, a * b + a);
+
+    let result = runtime.run(rotate_program, vec![a_c], &public_key);
+
+    assert!(matches!(result, Err(RuntimeError::MissingGaloisKeys)));
+
+    let (public_key, _) = runtime.generate_keys_for([rotate_program]).unwrap();
+
+    assert!(public_key.can_rotate());
+    assert!(!public_key.can_relinearize());
+}
+
+#[test]
+fn can_argsort() {
+    #[fhe_program(scheme = "bfv")]
+    fn argsort(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.argsort(4, 0..=3)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(argsort)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![3, 1, 2, 1], vec![0, 2, 2, 3]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(argsort).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    // Ties keep their original order.
+    let expected = Batched::<4>::try_from([vec![1, 3, 2, 0], vec![0, 1, 2, 3]]).unwrap();
+
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn signed_rotations_are_inverses() {
+    #[fhe_program(scheme = "bfv")]
+    fn rotate(
+        a: Cipher<Batched<4>>,
+    ) -> (
+        Cipher<Batched<4>>,
+        Cipher<Batched<4>>,
+        Cipher<Batched<4>>,
+        Cipher<Batched<4>>,
+    ) {
+        (
+            a.rotate(3),
+            a.rotate(-3),
+            a.rotate(3).rotate(-3),
+            a.rotate(0),
+        )
+    }
+
+    let app = Compiler::new()
+        .fhe_program(rotate)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(rotate).unwrap(), vec![a_c], &public_key)
+        .unwrap();
+
+    let decrypt = |i: usize| -> Batched<4> { runtime.decrypt(&result[i], &private_key).unwrap() };
+
+    assert_eq!(decrypt(0), a << 3);
+    assert_eq!(decrypt(1), a >> 3);
+    assert_eq!(decrypt(2), a);
+    assert_eq!(decrypt(3), a);
+}
+
+#[test]
+fn can_convert_between_slots_and_coefficients() {
+    #[fhe_program(scheme = "bfv")]
+    fn roundtrip(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.to_coeffs().to_slots()
+    }
+
+    // The transform embeds n dense literals of n coefficients each, so pin
+    // the smallest dimension with enough noise budget rather than let the
+    // search pick a larger one.
+    let app = Compiler::new()
+        .fhe_program(roundtrip)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .lattice_dimension(4096)
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, -2, 3, -4], vec![50, 60, -70, 80]]).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(roundtrip).unwrap(),
+            vec![a_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, a);
+}
@@ -0,0 +1,43 @@
+use sunscreen::{
+    fhe_program, set_thread_pool_size, thread_pool_size,
+    types::{bfv::Signed, Cipher},
+    Compiler, Runtime,
+};
+
+// This lives in its own test binary because the thread pool size is
+// process-wide state.
+#[test]
+fn single_thread_pool_gives_correct_results() {
+    #[fhe_program(scheme = "bfv")]
+    fn wide(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        // Independent branches the runtime would otherwise evaluate in
+        // parallel.
+        let x = a * b;
+        let y = a + b;
+        let z = a - b;
+
+        x + y * z
+    }
+
+    set_thread_pool_size(1).unwrap();
+    assert_eq!(thread_pool_size(), 1);
+
+    let app = Compiler::new().fhe_program(wide).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(7), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(-3), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(wide).unwrap(), vec![a, b], &public_key)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(7 * -3 + (7 + -3) * (7 - -3)));
+
+    set_thread_pool_size(0).unwrap();
+}
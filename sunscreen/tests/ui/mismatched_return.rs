@@ -0,0 +1,11 @@
+use sunscreen::fhe_program;
+use sunscreen::types::{bfv::Fractional, bfv::Signed, Cipher};
+
+#[fhe_program(scheme = "bfv")]
+fn mismatched(a: Cipher<Signed>) -> Cipher<Fractional<64>> {
+    a
+}
+
+fn main() {
+    let _ = mismatched;
+}
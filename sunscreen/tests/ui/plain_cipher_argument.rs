@@ -0,0 +1,11 @@
+use sunscreen::fhe_program;
+use sunscreen::types::{bfv::Signed, Cipher};
+
+#[fhe_program(scheme = "bfv")]
+fn scale(a: Cipher<Signed>, #[plain] w: Cipher<Signed>) -> Cipher<Signed> {
+    a * w
+}
+
+fn main() {
+    let _ = scale;
+}
@@ -5,10 +5,11 @@ use proptest::prelude::{prop::num::u64::ANY, prop_assert_eq, proptest, ProptestC
 use sunscreen::{
     fhe_program,
     types::{
-        bfv::{Unsigned, Unsigned256},
+        bfv::{Boolean, Unsigned, Unsigned256, Unsigned64},
         Cipher,
     },
-    Compiler, FheApplication, FheProgramInput, FheRuntime, PrivateKey, PublicKey, Runtime,
+    Compiler, FheApplication, FheProgramInput, FheRuntime, PlainModulusConstraint, PrivateKey,
+    PublicKey, Runtime, RuntimeError,
 };
 
 macro_rules! fhe_program {
@@ -163,3 +164,424 @@ fn carry_at_limb_boundary() {
     let b = U256::from_words([0, 1, 0, 0]);
     run_with(a, b, U256::wrapping_add, add, add_plain);
 }
+
+#[test]
+fn decrypt_checked_detects_overflow() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul_sub(
+        a: Cipher<Unsigned64>,
+        b: Cipher<Unsigned64>,
+    ) -> (Cipher<Unsigned64>, Cipher<Unsigned64>) {
+        (a * b, a - b)
+    }
+
+    let app = Compiler::new().fhe_program(mul_sub).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let run = |a: u64, b: u64| {
+        let args = [a, b]
+            .iter()
+            .map(|x| runtime.encrypt(Unsigned64::from(*x), &public_key).unwrap())
+            .collect::<Vec<_>>();
+
+        runtime
+            .run(app.get_fhe_program(mul_sub).unwrap(), args, &public_key)
+            .unwrap()
+    };
+
+    let result = run(1 << 20, 1 << 10);
+
+    let product: Unsigned64 = runtime.decrypt_checked(&result[0], &private_key).unwrap();
+    let difference: Unsigned64 = runtime.decrypt_checked(&result[1], &private_key).unwrap();
+
+    assert_eq!(product, Unsigned64::from(1u64 << 30));
+    assert_eq!(difference, Unsigned64::from((1u64 << 20) - (1 << 10)));
+
+    // 2^40 * 2^40 needs 81 bits, and 2^10 - 2^40 is negative.
+    let result = run(1 << 40, 1 << 40);
+
+    assert!(matches!(
+        runtime.decrypt_checked::<Unsigned64>(&result[0], &private_key),
+        Err(RuntimeError::Overflow)
+    ));
+
+    let result = run(1 << 10, 1 << 40);
+
+    assert!(matches!(
+        runtime.decrypt_checked::<Unsigned64>(&result[1], &private_key),
+        Err(RuntimeError::Overflow)
+    ));
+
+    // Unchecked decryption wraps.
+    let wrapped: Unsigned64 = runtime.decrypt(&result[1], &private_key).unwrap();
+
+    assert_eq!(
+        wrapped,
+        Unsigned64::from((1u64 << 10).wrapping_sub(1 << 40))
+    );
+}
+
+#[test]
+fn can_cond_add_cipher() {
+    #[fhe_program(scheme = "bfv")]
+    fn accumulate(
+        acc: Cipher<Unsigned256>,
+        c_0: Cipher<Unsigned256>,
+        d_0: Cipher<Unsigned256>,
+        c_1: Cipher<Unsigned256>,
+        d_1: Cipher<Unsigned256>,
+    ) -> Cipher<Unsigned256> {
+        acc.cond_add(c_0, d_0).cond_add(c_1, d_1)
+    }
+
+    let app = Compiler::new().fhe_program(accumulate).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let args = [10u64, 0, 42, 1, 7]
+        .iter()
+        .map(|x| runtime.encrypt(Unsigned256::from(*x), &public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let result = runtime
+        .run(app.get_fhe_program(accumulate).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Unsigned256 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Unsigned256::from(17u64));
+}
+
+#[test]
+fn literal_multiply_uses_plaintext_multiplication() {
+    use sunscreen_fhe_program::Operation;
+
+    #[fhe_program(scheme = "bfv")]
+    fn scale(a: Cipher<Unsigned256>) -> Cipher<Unsigned256> {
+        a * U256::from_u64(6)
+    }
+
+    let app = Compiler::new().fhe_program(scale).compile().unwrap();
+
+    let program = app.get_fhe_program(scale).unwrap();
+
+    let ops = program
+        .fhe_program_fn
+        .graph
+        .node_weights()
+        .map(|n| n.operation.clone())
+        .collect::<Vec<_>>();
+
+    // A public constant shouldn't cost a ciphertext multiplication (or
+    // the relinearization that follows one).
+    assert!(ops.contains(&Operation::MultiplyPlaintext));
+    assert!(!ops.contains(&Operation::Multiply));
+    assert!(!ops.contains(&Operation::Relinearize));
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt(Unsigned256::from(7u64), &public_key)
+        .unwrap();
+
+    let result = runtime.run(program, vec![a], &public_key).unwrap();
+
+    let c: Unsigned256 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Unsigned256::from(42u64));
+}
+
+#[test]
+fn can_compare_encrypted_equality() {
+    #[fhe_program(scheme = "bfv")]
+    fn eq(a: Cipher<Unsigned256>, b: Cipher<Unsigned256>) -> Cipher<Boolean> {
+        a.eq_encrypted(b)
+    }
+
+    // Keep p - 1 = 16 small so the comparison is only 4 squarings deep.
+    let app = Compiler::new()
+        .fhe_program(eq)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(17))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for (a, b) in [(0u64, 0u64), (0, 1), (1, 0), (1, 1)] {
+        let a_c = runtime.encrypt(Unsigned256::from(a), &public_key).unwrap();
+        let b_c = runtime.encrypt(Unsigned256::from(b), &public_key).unwrap();
+
+        let result = runtime
+            .run(
+                app.get_fhe_program(eq).unwrap(),
+                vec![a_c, b_c],
+                &public_key,
+            )
+            .unwrap();
+
+        let c: Boolean = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(bool::from(c), a == b);
+    }
+}
+
+#[test]
+#[should_panic(expected = "plain modulus must be prime")]
+fn encrypted_equality_requires_prime_plain_modulus() {
+    #[fhe_program(scheme = "bfv")]
+    fn eq(a: Cipher<Unsigned256>, b: Cipher<Unsigned256>) -> Cipher<Boolean> {
+        a.eq_encrypted(b)
+    }
+
+    let _ = Compiler::new()
+        .fhe_program(eq)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(16))
+        .compile();
+}
+
+#[test]
+fn adding_u64_constant_preserves_noise_budget() {
+    #[fhe_program(scheme = "bfv")]
+    fn add_const(a: Cipher<Unsigned64>) -> (Cipher<Unsigned64>, Cipher<Unsigned64>) {
+        let mut sum = a;
+
+        for _ in 0..8 {
+            sum = sum + 5;
+        }
+
+        (a + 5, sum)
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn add_cipher(a: Cipher<Unsigned64>, b: Cipher<Unsigned64>) -> Cipher<Unsigned64> {
+        let mut sum = a;
+
+        for _ in 0..8 {
+            sum = sum + b;
+        }
+
+        sum
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add_const)
+        .fhe_program(add_cipher)
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt(Unsigned64::from(10u64), &public_key)
+        .unwrap();
+    let b = runtime
+        .encrypt(Unsigned64::from(5u64), &public_key)
+        .unwrap();
+
+    let plain = runtime
+        .run(
+            app.get_fhe_program(add_const).unwrap(),
+            vec![a.clone()],
+            &public_key,
+        )
+        .unwrap();
+
+    let cipher = runtime
+        .run(
+            app.get_fhe_program(add_cipher).unwrap(),
+            vec![a, b],
+            &public_key,
+        )
+        .unwrap();
+
+    let biased: Unsigned64 = runtime.decrypt(&plain[0], &private_key).unwrap();
+    let plain_sum: Unsigned64 = runtime.decrypt(&plain[1], &private_key).unwrap();
+    let cipher_sum: Unsigned64 = runtime.decrypt(&cipher[0], &private_key).unwrap();
+
+    assert_eq!(biased, Unsigned64::from(15u64));
+    assert_eq!(plain_sum, Unsigned64::from(50u64));
+    assert_eq!(cipher_sum, Unsigned64::from(50u64));
+
+    // Eight ciphertext additions multiply b's noise by 8, about 3 bits.
+    assert!(
+        runtime
+            .measure_noise_budget(&plain[1], &private_key)
+            .unwrap()
+            > runtime
+                .measure_noise_budget(&cipher[0], &private_key)
+                .unwrap()
+    );
+}
+
+#[test]
+fn can_raise_to_constant_power() {
+    use sunscreen::{fhe::FheCompile, FheProgramFn};
+    use sunscreen_fhe_program::Operation;
+
+    #[fhe_program(scheme = "bfv")]
+    fn pow5(a: Cipher<Unsigned64>) -> Cipher<Unsigned64> {
+        a.pow(5)
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn pow_trivial(a: Cipher<Unsigned64>) -> Cipher<Unsigned64> {
+        a.pow(1)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(pow5)
+        .fhe_program(pow_trivial)
+        .compile()
+        .unwrap();
+
+    // x^5 = x * (x^2)^2: two squarings and one multiplication, at depth
+    // ceil(log2(5)) = 3.
+    let multiplications = app
+        .get_fhe_program(pow5)
+        .unwrap()
+        .fhe_program_fn
+        .graph
+        .node_weights()
+        .filter(|n| n.operation == Operation::Multiply)
+        .count();
+
+    assert_eq!(multiplications, 3);
+    assert_eq!(pow5.build(app.params()).unwrap().multiplicative_depth(), 3);
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt(Unsigned64::from(3u64), &public_key)
+        .unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(pow5).unwrap(),
+            vec![a.clone()],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Unsigned64 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Unsigned64::from(243u64));
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(pow_trivial).unwrap(),
+            vec![a],
+            &public_key,
+        )
+        .unwrap();
+
+    let one: Unsigned64 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(one, Unsigned64::from(3u64));
+}
+
+#[test]
+#[should_panic(expected = "exponent must be positive")]
+fn zero_power_is_rejected() {
+    #[fhe_program(scheme = "bfv")]
+    fn pow0(a: Cipher<Unsigned64>) -> Cipher<Unsigned64> {
+        a.pow(0)
+    }
+
+    let _ = Compiler::new().fhe_program(pow0).compile();
+}
+
+#[test]
+fn can_sum_and_multiply_iterators() {
+    use sunscreen::{fhe::FheCompile, FheProgramFn};
+
+    #[fhe_program(scheme = "bfv")]
+    fn sum(a: [Cipher<Unsigned64>; 4]) -> Cipher<Unsigned64> {
+        a.into_iter().sum()
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn product(a: [Cipher<Unsigned64>; 4]) -> Cipher<Unsigned64> {
+        a.iter().product()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(sum)
+        .fhe_program(product)
+        .compile()
+        .unwrap();
+
+    // A balanced tree of 4 values has 2 levels rather than 3.
+    assert_eq!(
+        product.build(app.params()).unwrap().multiplicative_depth(),
+        2
+    );
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = [1u64, 2, 3, 4].map(Unsigned64::from);
+    let a = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(sum).unwrap(),
+            vec![a.clone()],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Unsigned64 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Unsigned64::from(10u64));
+
+    let result = runtime
+        .run(app.get_fhe_program(product).unwrap(), vec![a], &public_key)
+        .unwrap();
+
+    let c: Unsigned64 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Unsigned64::from(24u64));
+}
+
+#[test]
+fn plain_arguments_take_plaintexts() {
+    #[fhe_program(scheme = "bfv")]
+    fn weigh(a: Cipher<Unsigned64>, #[plain] w: Unsigned64) -> Cipher<Unsigned64> {
+        a * w + a
+    }
+
+    let app = Compiler::new().fhe_program(weigh).compile().unwrap();
+    let program = app.get_fhe_program(weigh).unwrap();
+
+    assert!(program.metadata.signature.arguments[0].is_encrypted);
+    assert!(!program.metadata.signature.arguments[1].is_encrypted);
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt(Unsigned64::from(12u64), &public_key)
+        .unwrap();
+    let args: Vec<FheProgramInput> = vec![a.into(), Unsigned64::from(5u64).into()];
+
+    let result = runtime.run(program, args, &public_key).unwrap();
+
+    let c: Unsigned64 = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Unsigned64::from(72u64));
+}
@@ -16,25 +16,37 @@ mod transforms;
 pub use error::*;
 
 use sunscreen_fhe_program::FheProgram;
+use sunscreen_runtime::Params;
 
 use transforms::transform_intermediate_representation;
+pub use transforms::RelinearizationStrategy;
 
 /**
- * Clones the given [`FheProgram`] and compiles it.
+ * Clones the given [`FheProgram`] and compiles it for `params`, placing
+ * relinearizations according to `relinearization`.
  */
-pub fn compile(ir: &FheProgram) -> FheProgram {
+pub fn compile(
+    ir: &FheProgram,
+    params: &Params,
+    relinearization: RelinearizationStrategy,
+) -> FheProgram {
     let mut clone = ir.clone();
 
-    transform_intermediate_representation(&mut clone);
+    transform_intermediate_representation(&mut clone, params, relinearization);
 
     clone
 }
 
 /**
- * Consumes the given [`FheProgram`] and compiles it.
+ * Consumes the given [`FheProgram`] and compiles it for `params`, placing
+ * relinearizations according to `relinearization`.
  */
-pub fn compile_inplace(mut ir: FheProgram) -> FheProgram {
-    transform_intermediate_representation(&mut ir);
+pub fn compile_inplace(
+    mut ir: FheProgram,
+    params: &Params,
+    relinearization: RelinearizationStrategy,
+) -> FheProgram {
+    transform_intermediate_representation(&mut ir, params, relinearization);
 
     ir
 }
@@ -3,7 +3,7 @@ use sunscreen_compiler_common::GraphQuery;
 use sunscreen_fhe_program::{FheProgram, Literal, Operation::*};
 use sunscreen_runtime::traverse;
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 mod canonical_embedding_norm;
 mod measured_model;
@@ -51,7 +51,7 @@ pub fn predict_noise(model: &(dyn NoiseModel + Sync), fhe_program: &FheProgram)
         })
         .enumerate()
         .map(|(output_num, node_id)| (node_id, output_num))
-        .collect::<HashMap<usize, usize>>();
+        .collect::<BTreeMap<usize, usize>>();
 
     traverse(
         fhe_program,
@@ -121,26 +121,32 @@ pub fn predict_noise(model: &(dyn NoiseModel + Sync), fhe_program: &FheProgram)
                 ShiftLeft => {
                     let (left, right) = query.get_binary_operands(node_id).unwrap();
 
-                    let b = match fhe_program.graph[right].operation {
-                        Literal(Literal::U64(v)) => v as i32,
-                        _ => panic!(
+                    let b = match &fhe_program.graph[right].operation {
+                        Literal(x) => x.as_rotation_amount(),
+                        _ => None,
+                    }
+                    .unwrap_or_else(|| {
+                        panic!(
                             "Illegal right operand for ShiftLeft: {:#?}",
                             fhe_program.graph[right].operation
-                        ),
-                    };
+                        )
+                    });
 
                     model.shift_left(noise_levels[left.index()].load(), b)
                 }
                 ShiftRight => {
                     let (left, right) = query.get_binary_operands(node_id).unwrap();
 
-                    let b = match fhe_program.graph[right].operation {
-                        Literal(Literal::U64(v)) => v as i32,
-                        _ => panic!(
+                    let b = match &fhe_program.graph[right].operation {
+                        Literal(x) => x.as_rotation_amount(),
+                        _ => None,
+                    }
+                    .unwrap_or_else(|| {
+                        panic!(
                             "Illegal right operand for ShiftLeft: {:#?}",
                             fhe_program.graph[right].operation
-                        ),
-                    };
+                        )
+                    });
 
                     model.shift_right(noise_levels[left.index()].load(), b)
                 }
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use sunscreen_compiler_common::{
+    forward_traverse_mut,
+    transforms::{GraphTransforms, Transform},
+    EdgeInfo, NodeInfo,
+};
+use sunscreen_fhe_program::{FheProgram, Literal, Operation};
+
+use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
+
+/**
+ * Merges identical literal nodes so each distinct constant (in
+ * particular, each encoded plaintext) is stored and decoded only once,
+ * no matter how many operations consume it.
+ *
+ * # Remarks
+ * The first occurrence in traversal order becomes canonical and later
+ * duplicates forward their consumers to it. The orphaned duplicates get
+ * removed by dead code elimination.
+ */
+pub fn apply_dedup_literals(ir: &mut FheProgram) {
+    // Only used for lookups, so iteration order doesn't matter.
+    let mut canonical = HashMap::<Literal, NodeIndex>::new();
+
+    forward_traverse_mut(&mut ir.graph.0, |query, id| {
+        let mut transforms: GraphTransforms<NodeInfo<Operation>, EdgeInfo> = GraphTransforms::new();
+
+        // Id is given to us, so the node should exist. Just
+        // unwrap.
+        let literal = match &query.get_node(id).unwrap().operation {
+            Operation::Literal(x) => x,
+            _ => return Ok::<_, Infallible>(transforms),
+        };
+
+        match canonical.get(literal) {
+            Some(existing) => {
+                for e in query.edges_directed(id, Direction::Outgoing) {
+                    transforms.push(Transform::RemoveEdge(id.into(), e.target().into()));
+                    transforms.push(Transform::AddEdge(
+                        (*existing).into(),
+                        e.target().into(),
+                        *e.weight(),
+                    ));
+                }
+            }
+            None => {
+                canonical.insert(literal.clone(), id);
+            }
+        };
+
+        Ok(transforms)
+    })
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sunscreen_compiler_common::GraphQuery;
+    use sunscreen_fhe_program::{FheProgramTrait, SchemeType};
+
+    #[test]
+    fn shares_repeated_plaintext_literal() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let constant = Literal::Plaintext(vec![1, 2, 3]);
+
+        let muls = (0..3)
+            .map(|i| {
+                let ct = ir.add_input_ciphertext(i);
+                let pt = ir.add_input_literal(constant.clone());
+                let mul = ir.add_multiply_plaintext(ct, pt);
+                ir.add_output_ciphertext(mul);
+
+                mul
+            })
+            .collect::<Vec<_>>();
+
+        apply_dedup_literals(&mut ir);
+
+        let ir = ir.prune(&ir.get_outputs().collect::<Vec<_>>());
+
+        let literals = ir
+            .graph
+            .node_indices()
+            .filter(|i| matches!(ir.graph[*i].operation, Operation::Literal(_)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(literals.len(), 1);
+        assert_eq!(
+            ir.graph[literals[0]].operation,
+            Operation::Literal(constant)
+        );
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        assert_eq!(
+            query
+                .neighbors_directed(literals[0], Direction::Outgoing)
+                .count(),
+            muls.len()
+        );
+    }
+}
@@ -1,5 +1,8 @@
+use std::collections::BTreeSet;
 use std::convert::Infallible;
 
+use log::warn;
+
 use sunscreen_compiler_common::{
     forward_traverse_mut,
     transforms::{GraphTransforms, Transform},
@@ -14,7 +17,99 @@ use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
 
 type FheGraphQuery<'a> = GraphQuery<'a, NodeInfo<Operation>, EdgeInfo>;
 
-pub fn apply_insert_relinearizations(ir: &mut FheProgram) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * Where the backend inserts relinearizations after ciphertext
+ * multiplications.
+ *
+ * # Remarks
+ * Multiplying two ciphertexts of 2 polynomials yields one of 3.
+ * Relinearizing shrinks it back to 2 at the cost of some noise and a key
+ * switch, so fewer relinearizations run faster but carry larger
+ * ciphertexts through the program.
+ */
+pub enum RelinearizationStrategy {
+    /**
+     * Relinearize the result of every ciphertext multiplication.
+     */
+    EveryMultiply,
+
+    /**
+     * Relinearize a multiplication's result only if it would otherwise
+     * grow past 3 polynomials by reaching another ciphertext
+     * multiplication, or reach a rotation or row swap, which require 2.
+     * Results that only feed additions, plaintext operations, and outputs
+     * stay at 3 polynomials.
+     */
+    Minimal,
+
+    /**
+     * Never relinearize. Ciphertexts grow with every multiplication and
+     * programs that rotate a product fail at runtime. Intended for
+     * measuring the cost of relinearization, not for production.
+     */
+    None,
+}
+
+impl Default for RelinearizationStrategy {
+    fn default() -> Self {
+        Self::EveryMultiply
+    }
+}
+
+/**
+ * Whether the size-3 result of multiplication `id` reaches an operation
+ * that would grow it further or requires 2 polynomials, without passing
+ * through a relinearization first.
+ */
+fn reaches_size_sensitive_operation(query: &FheGraphQuery, id: NodeIndex) -> bool {
+    let mut visited = BTreeSet::new();
+    let mut visit = query
+        .neighbors_directed(id, Direction::Outgoing)
+        .collect::<Vec<_>>();
+
+    while let Some(n) = visit.pop() {
+        if !visited.insert(n) {
+            continue;
+        }
+
+        match query.get_node(n).unwrap().operation {
+            Multiply | ShiftLeft | ShiftRight | SwapRows => return true,
+            Relinearize => {}
+            _ => visit.extend(query.neighbors_directed(n, Direction::Outgoing)),
+        }
+    }
+
+    false
+}
+
+/**
+ * Returns every ciphertext multiplication whose size-3 result reaches
+ * another ciphertext multiplication or a rotation without passing through
+ * a relinearization first, in ascending index order.
+ */
+fn find_unrelinearized_multiplies(ir: &FheProgram) -> Vec<NodeIndex> {
+    let query = GraphQuery::new(&ir.graph.0);
+
+    ir.graph
+        .node_indices()
+        .filter(|id| matches!(ir.graph[*id].operation, Multiply))
+        .filter(|id| reaches_size_sensitive_operation(&query, *id))
+        .collect()
+}
+
+pub fn apply_insert_relinearizations(ir: &mut FheProgram, strategy: RelinearizationStrategy) {
+    if strategy == RelinearizationStrategy::None {
+        for id in find_unrelinearized_multiplies(ir) {
+            warn!(
+                "Multiplication {} feeds a multiplication or rotation without relinearization.",
+                id.index()
+            );
+        }
+
+        return;
+    }
+
     let insert_relin = |id: NodeIndex, query: FheGraphQuery| {
         let mut transforms = GraphTransforms::new();
 
@@ -51,7 +146,14 @@ pub fn apply_insert_relinearizations(ir: &mut FheProgram) {
             // the number of polynomials (see
             // multiply_plaintext_does_not_increase_polynomials) test in
             // assumptions.rs
-            Multiply => insert_relin(id, query),
+            Multiply => match strategy {
+                RelinearizationStrategy::Minimal
+                    if !reaches_size_sensitive_operation(&query, id) =>
+                {
+                    GraphTransforms::default()
+                }
+                _ => insert_relin(id, query),
+            },
             _ => GraphTransforms::default(),
         };
 
@@ -89,7 +191,7 @@ mod tests {
 
         assert_eq!(ir.graph.node_count(), 7);
 
-        apply_insert_relinearizations(&mut ir);
+        apply_insert_relinearizations(&mut ir, RelinearizationStrategy::EveryMultiply);
 
         assert_eq!(ir.graph.node_count(), 9);
 
@@ -143,4 +245,87 @@ mod tests {
             .neighbors_directed(relin_nodes[0], Direction::Outgoing)
             .all(|i| { matches!(query.get_node(i).unwrap().operation, Operation::Add) }),);
     }
+
+    fn count_relinearizations(ir: &FheProgram) -> usize {
+        ir.graph
+            .node_weights()
+            .filter(|n| matches!(n.operation, Operation::Relinearize))
+            .count()
+    }
+
+    #[test]
+    fn strategies_insert_different_relinearizations() {
+        let relins = [
+            RelinearizationStrategy::EveryMultiply,
+            RelinearizationStrategy::Minimal,
+            RelinearizationStrategy::None,
+        ]
+        .iter()
+        .map(|strategy| {
+            // (x * y) * z + w * x: only x * y feeds another multiply.
+            let mut ir = FheProgram::new(SchemeType::Bfv);
+
+            let x = ir.add_input_ciphertext(0);
+            let y = ir.add_input_ciphertext(1);
+            let z = ir.add_input_ciphertext(2);
+            let w = ir.add_input_ciphertext(3);
+            let xy = ir.add_multiply(x, y);
+            let xyz = ir.add_multiply(xy, z);
+            let wx = ir.add_multiply(w, x);
+            let sum = ir.add_add(xyz, wx);
+            ir.add_output_ciphertext(sum);
+
+            apply_insert_relinearizations(&mut ir, *strategy);
+
+            count_relinearizations(&ir)
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(relins, vec![3, 1, 0]);
+    }
+
+    #[test]
+    fn minimal_relinearizes_before_rotation() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let x = ir.add_input_ciphertext(0);
+        let xx = ir.add_multiply(x, x);
+        let amount = ir.add_input_literal(FheProgramLiteral::from(1u64));
+        let rotated = ir.add_rotate_left(xx, amount);
+        ir.add_output_ciphertext(rotated);
+
+        apply_insert_relinearizations(&mut ir, RelinearizationStrategy::Minimal);
+
+        assert_eq!(count_relinearizations(&ir), 1);
+    }
+
+    fn create_mul_chain() -> (FheProgram, NodeIndex) {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let x = ir.add_input_ciphertext(0);
+        let y = ir.add_input_ciphertext(1);
+        let z = ir.add_input_ciphertext(2);
+        let xy = ir.add_multiply(x, y);
+        let neg = ir.add_negate(xy);
+        let xyz = ir.add_multiply(neg, z);
+        ir.add_output_ciphertext(xyz);
+
+        (ir, xy)
+    }
+
+    #[test]
+    fn flags_unrelinearized_multiply_chain() {
+        let (ir, xy) = create_mul_chain();
+
+        assert_eq!(find_unrelinearized_multiplies(&ir), vec![xy]);
+    }
+
+    #[test]
+    fn relinearized_chain_is_not_flagged() {
+        let (mut ir, _) = create_mul_chain();
+
+        apply_insert_relinearizations(&mut ir, RelinearizationStrategy::Minimal);
+
+        assert!(find_unrelinearized_multiplies(&ir).is_empty());
+    }
 }
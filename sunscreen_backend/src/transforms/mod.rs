@@ -1,13 +1,36 @@
+mod dedup_literals;
 mod insert_relinearizations;
+mod rebalance;
+mod simplify;
 
 use petgraph::stable_graph::NodeIndex;
 use sunscreen_fhe_program::{FheProgram, FheProgramTrait};
+use sunscreen_runtime::Params;
 
+use dedup_literals::apply_dedup_literals;
 use insert_relinearizations::apply_insert_relinearizations;
+pub use insert_relinearizations::RelinearizationStrategy;
+use rebalance::apply_rebalance_associative;
+use simplify::apply_algebraic_simplifications;
 
-pub fn transform_intermediate_representation(ir: &mut FheProgram) {
-    apply_insert_relinearizations(ir);
+pub fn transform_intermediate_representation(
+    ir: &mut FheProgram,
+    params: &Params,
+    relinearization: RelinearizationStrategy,
+) {
+    // Capture the outputs in declaration order before transforms mutate
+    // the graph. Transforms rewire outputs' operands but never replace
+    // the outputs themselves, and pruning keeps their relative order, so
+    // `run` returns results in this order.
+    let outputs = ir.get_outputs().collect::<Vec<NodeIndex>>();
+
+    apply_dedup_literals(ir);
+    apply_algebraic_simplifications(ir, params);
+    apply_rebalance_associative(ir);
+    apply_insert_relinearizations(ir, relinearization);
+
+    debug_assert!(ir.get_outputs().eq(outputs.iter().copied()));
 
     // Dead code elimination.
-    *ir = ir.prune(&ir.get_outputs().collect::<Vec<NodeIndex>>());
+    *ir = ir.prune(&outputs);
 }
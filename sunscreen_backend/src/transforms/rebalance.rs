@@ -0,0 +1,260 @@
+use sunscreen_compiler_common::{EdgeInfo, GraphQuery};
+use sunscreen_fhe_program::{
+    FheProgram,
+    Operation::{self, *},
+};
+
+use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
+
+/**
+ * A maximal tree of a single associative operation in which every node
+ * but the root feeds only its parent.
+ */
+struct Chain {
+    /**
+     * The nodes performing the operation, root first.
+     */
+    nodes: Vec<NodeIndex>,
+
+    /**
+     * The values the chain combines, in left to right order.
+     */
+    leaves: Vec<NodeIndex>,
+
+    /**
+     * The number of operations on the longest path from a leaf to the
+     * root.
+     */
+    depth: usize,
+}
+
+/**
+ * Whether `id` performs `operation` and its only consumer is another
+ * `operation`, making it part of the same chain.
+ */
+fn is_interior(ir: &FheProgram, id: NodeIndex, operation: &Operation) -> bool {
+    if ir.graph[id].operation != *operation {
+        return false;
+    }
+
+    let mut consumers = ir.graph.edges_directed(id, Direction::Outgoing);
+
+    match (consumers.next(), consumers.next()) {
+        (Some(e), None) => ir.graph[e.target()].operation == *operation,
+        _ => false,
+    }
+}
+
+/**
+ * Returns the nodes performing `operation` that don't feed another
+ * `operation` in the same chain.
+ */
+fn chain_roots(ir: &FheProgram, operation: &Operation) -> Vec<NodeIndex> {
+    ir.graph
+        .node_indices()
+        .filter(|id| ir.graph[*id].operation == *operation && !is_interior(ir, *id, operation))
+        .collect()
+}
+
+/**
+ * Collects the chain of `operation` nodes rooted at `root`. Returns
+ * [`None`] if any node in it is malformed.
+ */
+fn collect_chain(ir: &FheProgram, root: NodeIndex, operation: &Operation) -> Option<Chain> {
+    let query = GraphQuery::new(&ir.graph.0);
+
+    let mut chain = Chain {
+        nodes: vec![],
+        leaves: vec![],
+        depth: 0,
+    };
+
+    // Visiting the left operand first yields leaves in left to right
+    // order. Iterate rather than recurse, as chains can be long. Leaves
+    // have no depth.
+    let mut visit = vec![(root, Some(1))];
+
+    while let Some((id, depth)) = visit.pop() {
+        let depth = match depth {
+            Some(x) => x,
+            None => {
+                chain.leaves.push(id);
+                continue;
+            }
+        };
+
+        let (left, right) = query.get_binary_operands(id).ok()?;
+
+        chain.nodes.push(id);
+        chain.depth = chain.depth.max(depth);
+
+        for operand in [right, left] {
+            if is_interior(ir, operand, operation) {
+                visit.push((operand, Some(depth + 1)));
+            } else {
+                visit.push((operand, None));
+            }
+        }
+    }
+
+    Some(chain)
+}
+
+/**
+ * Rewires the nodes of `chain` into a balanced tree over its leaves,
+ * keeping its root (and thus the root's consumers) in place.
+ */
+fn rebalance(ir: &mut FheProgram, chain: Chain) {
+    let graph = &mut ir.graph.0;
+
+    // Every non-root node only feeds the chain, so dropping the chain's
+    // incoming edges detaches it entirely.
+    for id in &chain.nodes {
+        let edges = graph
+            .edges_directed(*id, Direction::Incoming)
+            .map(|e| e.id())
+            .collect::<Vec<_>>();
+
+        for e in edges {
+            graph.remove_edge(e);
+        }
+    }
+
+    let root = chain.nodes[0];
+
+    // A binary tree has one more leaf than it has nodes, so this never
+    // runs out.
+    let mut free = chain.nodes[1..].to_vec();
+    let mut level = chain.leaves;
+
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity((level.len() + 1) / 2);
+
+        for pair in level.chunks(2) {
+            match pair {
+                [left, right] => {
+                    let id = if level.len() == 2 {
+                        root
+                    } else {
+                        free.pop().unwrap()
+                    };
+
+                    graph.add_edge(*left, id, EdgeInfo::Left);
+                    graph.add_edge(*right, id, EdgeInfo::Right);
+
+                    next.push(id);
+                }
+                _ => next.push(pair[0]),
+            }
+        }
+
+        level = next;
+    }
+}
+
+/**
+ * Rewrites chains of ciphertext additions (and, separately, ciphertext
+ * multiplications) into balanced trees.
+ *
+ * # Remarks
+ * `a + b + c + d` builds `((a + b) + c) + d`, whose additions must all
+ * run one after another. Rebalanced to `(a + b) + (c + d)`, the first two
+ * can run in parallel, reducing a chain over `k` values from depth
+ * `k - 1` to `ceil(log2(k))`. For multiplications, this also reduces
+ * multiplicative depth and so the noise the chain accumulates.
+ *
+ * Only nodes whose sole consumer is the next operation in the chain get
+ * rewired, so no intermediate value anything else reads changes. Chains
+ * that are already balanced are left alone.
+ */
+pub fn apply_rebalance_associative(ir: &mut FheProgram) {
+    for operation in [Add, Multiply] {
+        for root in chain_roots(ir, &operation) {
+            // Malformed nodes are left alone; validation reports them.
+            let chain = match collect_chain(ir, root, &operation) {
+                Some(x) => x,
+                None => continue,
+            };
+
+            let balanced_depth = chain.leaves.len().next_power_of_two().trailing_zeros() as usize;
+
+            if chain.depth > balanced_depth {
+                rebalance(ir, chain);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sunscreen_fhe_program::{FheProgramTrait, SchemeType};
+
+    fn depth(ir: &FheProgram, id: NodeIndex, operation: &Operation) -> usize {
+        if ir.graph[id].operation != *operation {
+            return 0;
+        }
+
+        let (left, right) = GraphQuery::new(&ir.graph.0)
+            .get_binary_operands(id)
+            .unwrap();
+
+        1 + depth(ir, left, operation).max(depth(ir, right, operation))
+    }
+
+    #[test]
+    fn balances_addition_chain() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let inputs = (0..8)
+            .map(|i| ir.add_input_ciphertext(i))
+            .collect::<Vec<_>>();
+
+        let sum = inputs[1..]
+            .iter()
+            .fold(inputs[0], |sum, x| ir.add_add(sum, *x));
+
+        let out = ir.add_output_ciphertext(sum);
+
+        assert_eq!(depth(&ir, sum, &Add), 7);
+
+        apply_rebalance_associative(&mut ir);
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        // The root stays put and still sums every input exactly once.
+        assert_eq!(query.get_unary_operand(out).unwrap(), sum);
+        assert_eq!(depth(&ir, sum, &Add), 3);
+
+        let mut leaves = collect_chain(&ir, sum, &Add).unwrap().leaves;
+        leaves.sort();
+
+        assert_eq!(leaves, inputs);
+    }
+
+    #[test]
+    fn shared_intermediates_end_chains() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_input_ciphertext(2);
+        let d = ir.add_input_ciphertext(3);
+
+        let ab = ir.add_multiply(a, b);
+        let abc = ir.add_multiply(ab, c);
+        let abcd = ir.add_multiply(abc, d);
+
+        ir.add_output_ciphertext(ab);
+        ir.add_output_ciphertext(abcd);
+
+        apply_rebalance_associative(&mut ir);
+
+        // ab is also an output, so only (ab * c) * d forms a chain, and
+        // a chain over 3 values is already balanced.
+        let query = GraphQuery::new(&ir.graph.0);
+
+        assert_eq!(query.get_binary_operands(abc).unwrap(), (ab, c));
+        assert_eq!(query.get_binary_operands(abcd).unwrap(), (abc, d));
+    }
+}
@@ -0,0 +1,342 @@
+use std::convert::Infallible;
+
+use sunscreen_compiler_common::{
+    forward_traverse_mut,
+    transforms::{GraphTransforms, Transform},
+    EdgeInfo, GraphQuery, NodeInfo,
+};
+use sunscreen_fhe_program::{
+    FheProgram, Literal,
+    Operation::{self, *},
+};
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use sunscreen_runtime::Params;
+
+type FheGraphQuery<'a> = GraphQuery<'a, NodeInfo<Operation>, EdgeInfo>;
+type FheGraphTransforms = GraphTransforms<NodeInfo<Operation>, EdgeInfo>;
+
+/**
+ * Rewrites `Negate(Negate(x))` to `x` by pointing the outer negation's
+ * consumers directly at `x`. The orphaned negations get removed by dead
+ * code elimination.
+ */
+fn remove_double_negation(id: NodeIndex, query: FheGraphQuery) -> FheGraphTransforms {
+    let mut transforms = GraphTransforms::new();
+
+    // Malformed nodes are left alone; validation reports them.
+    let inner = match query.get_unary_operand(id) {
+        Ok(x) => x,
+        Err(_) => return transforms,
+    };
+
+    if !matches!(query.get_node(inner).unwrap().operation, Negate) {
+        return transforms;
+    }
+
+    let x = match query.get_unary_operand(inner) {
+        Ok(x) => x,
+        Err(_) => return transforms,
+    };
+
+    for e in query.edges_directed(id, Direction::Outgoing) {
+        transforms.push(Transform::RemoveEdge(id.into(), e.target().into()));
+        transforms.push(Transform::AddEdge(x.into(), e.target().into(), *e.weight()));
+    }
+
+    transforms
+}
+
+/**
+ * If `id` is a rotation by a literal amount, returns its ciphertext
+ * operand, its literal node, and the amount, with right rotations
+ * negative. Returns [`None`] for amounts that don't fit in an [`i32`],
+ * which are left alone.
+ */
+fn literal_rotation(ir: &FheProgram, id: NodeIndex) -> Option<(NodeIndex, NodeIndex, i64)> {
+    let sign = match ir.graph[id].operation {
+        ShiftLeft => 1,
+        ShiftRight => -1,
+        _ => return None,
+    };
+
+    let (x, amount) = GraphQuery::new(&ir.graph.0).get_binary_operands(id).ok()?;
+
+    match &ir.graph[amount].operation {
+        Literal(v) => v.as_rotation_amount().map(|v| (x, amount, sign * v as i64)),
+        _ => None,
+    }
+}
+
+/**
+ * Rewrites `rotate(rotate(x, a), b)` into a single rotation of `x` by
+ * `a + b` when both amounts are literals, cancelling opposite directions.
+ * Rows are cyclic, so the net amount is reduced modulo `row_size` and a
+ * net rotation of zero forwards `x` to the outer rotation's consumers.
+ * Other amounts become whichever direction rotates fewer slots.
+ *
+ * # Remarks
+ * The outer rotation node is rewired in place, so this runs as a separate
+ * pass rather than under [`forward_traverse_mut`], which can't visit
+ * nodes whose operands change mid-traversal. Visiting in topological
+ * order fuses whole chains, since each rotation sees its operand already
+ * fused. The inner rotation is left for any other consumers and is
+ * otherwise removed by dead code elimination.
+ */
+fn fuse_rotations(ir: &mut FheProgram, row_size: i64) {
+    // Programs are DAGs, so this can't fail.
+    let order = toposort(&ir.graph.0, None).unwrap();
+
+    for id in order {
+        let (inner, outer_amount, b) = match literal_rotation(ir, id) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        let (x, _, a) = match literal_rotation(ir, inner) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        let graph = &mut ir.graph.0;
+
+        let net = (a + b).rem_euclid(row_size);
+
+        if net == 0 {
+            let consumers = graph
+                .edges_directed(id, Direction::Outgoing)
+                .map(|e| (e.target(), *e.weight()))
+                .collect::<Vec<_>>();
+
+            for (target, info) in consumers {
+                graph.add_edge(x, target, info);
+            }
+
+            graph.remove_node(id);
+            continue;
+        }
+
+        for source in [inner, outer_amount] {
+            while let Some(e) = graph.find_edge(source, id) {
+                graph.remove_edge(e);
+            }
+        }
+
+        let (operation, net) = if net <= row_size / 2 {
+            (ShiftLeft, net)
+        } else {
+            (ShiftRight, row_size - net)
+        };
+
+        let amount = graph.add_node(NodeInfo {
+            operation: Literal(Literal::U64(net as u64)),
+        });
+
+        graph[id].operation = operation;
+        graph.add_edge(x, id, EdgeInfo::Left);
+        graph.add_edge(amount, id, EdgeInfo::Right);
+    }
+}
+
+pub fn apply_algebraic_simplifications(ir: &mut FheProgram, params: &Params) {
+    forward_traverse_mut(&mut ir.graph.0, |query, id| {
+        // Id is given to us, so the node should exist. Just
+        // unwrap.
+        let transforms = match query.get_node(id).unwrap().operation {
+            Negate => remove_double_negation(id, query),
+            _ => GraphTransforms::default(),
+        };
+
+        Ok::<_, Infallible>(transforms)
+    })
+    .unwrap();
+
+    // BFV packs two rows of lattice_dimension / 2 slots each.
+    fuse_rotations(ir, params.lattice_dimension as i64 / 2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sunscreen_fhe_program::{FheProgramTrait, SchemeType, SecurityLevel};
+
+    fn make_params() -> Params {
+        Params {
+            lattice_dimension: 4096,
+            plain_modulus: 1024,
+            coeff_modulus: vec![],
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        }
+    }
+
+    #[test]
+    fn collapses_double_negation() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let neg_1 = ir.add_negate(ct);
+        let neg_2 = ir.add_negate(neg_1);
+        let out = ir.add_output_ciphertext(neg_2);
+
+        apply_algebraic_simplifications(&mut ir, &make_params());
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        assert_eq!(query.get_unary_operand(out).unwrap(), ct);
+        assert_eq!(
+            query.neighbors_directed(neg_2, Direction::Outgoing).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn odd_negation_chain_keeps_one_negation() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let neg_1 = ir.add_negate(ct);
+        let neg_2 = ir.add_negate(neg_1);
+        let neg_3 = ir.add_negate(neg_2);
+        let out = ir.add_output_ciphertext(neg_3);
+
+        apply_algebraic_simplifications(&mut ir, &make_params());
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        assert_eq!(query.get_unary_operand(out).unwrap(), neg_3);
+        assert_eq!(query.get_unary_operand(neg_3).unwrap(), ct);
+    }
+
+    #[test]
+    fn fuses_same_direction_rotations() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let one = ir.add_input_literal(Literal::U64(1));
+        let two = ir.add_input_literal(Literal::U64(2));
+        let rot_1 = ir.add_rotate_left(ct, one);
+        let rot_2 = ir.add_rotate_left(rot_1, two);
+        let out = ir.add_output_ciphertext(rot_2);
+
+        apply_algebraic_simplifications(&mut ir, &make_params());
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        let rot = query.get_unary_operand(out).unwrap();
+        let (x, amount) = query.get_binary_operands(rot).unwrap();
+
+        assert_eq!(ir.graph[rot].operation, ShiftLeft);
+        assert_eq!(x, ct);
+        assert_eq!(ir.graph[amount].operation, Literal(Literal::U64(3)));
+    }
+
+    #[test]
+    fn opposite_rotations_cancel() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let one = ir.add_input_literal(Literal::U64(1));
+        let three = ir.add_input_literal(Literal::U64(3));
+        let rot_1 = ir.add_rotate_left(ct, three);
+        let rot_2 = ir.append_rotate_right(rot_1, one);
+        let rot_3 = ir.append_rotate_right(rot_2, three);
+        let out = ir.add_output_ciphertext(rot_3);
+
+        apply_algebraic_simplifications(&mut ir, &make_params());
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        let rot = query.get_unary_operand(out).unwrap();
+        let (x, amount) = query.get_binary_operands(rot).unwrap();
+
+        assert_eq!(ir.graph[rot].operation, ShiftRight);
+        assert_eq!(x, ct);
+        assert_eq!(ir.graph[amount].operation, Literal(Literal::U64(1)));
+
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let two = ir.add_input_literal(Literal::U64(2));
+        let rot_1 = ir.add_rotate_left(ct, two);
+        let rot_2 = ir.append_rotate_right(rot_1, two);
+        let out = ir.add_output_ciphertext(rot_2);
+
+        apply_algebraic_simplifications(&mut ir, &make_params());
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        assert_eq!(query.get_unary_operand(out).unwrap(), ct);
+    }
+
+    #[test]
+    fn fuses_wide_literals_and_skips_oversized_ones() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let one = ir.add_input_literal(Literal::U128(1));
+        let two = ir.add_input_literal(Literal::U64(2));
+        let huge = ir.add_input_literal(Literal::U64(u64::MAX));
+        let inner = ir.add_rotate_left(ct, one);
+        let outer = ir.add_rotate_left(inner, two);
+        let oversized = ir.append_rotate_right(outer, huge);
+        ir.add_output_ciphertext(oversized);
+
+        apply_algebraic_simplifications(&mut ir, &make_params());
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        // The U128 amount fuses like a U64 one.
+        let (x, amount) = query.get_binary_operands(outer).unwrap();
+
+        assert_eq!(x, ct);
+        assert_eq!(ir.graph[amount].operation, Literal(Literal::U64(3)));
+
+        // An amount past i32 isn't wrapped into a bogus rotation.
+        assert_eq!(query.get_binary_operands(oversized).unwrap(), (outer, huge));
+        assert_eq!(ir.graph[oversized].operation, ShiftRight);
+    }
+
+    #[test]
+    fn rotations_wrap_around_rows() {
+        // With 16 slots per row, rotating left by 12 then 4 is the
+        // identity, and left by 12 then 3 is right by 1.
+        let mut params = make_params();
+        params.lattice_dimension = 32;
+
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let twelve = ir.add_input_literal(Literal::U64(12));
+        let four = ir.add_input_literal(Literal::U64(4));
+        let rot_1 = ir.add_rotate_left(ct, twelve);
+        let rot_2 = ir.add_rotate_left(rot_1, four);
+        let out = ir.add_output_ciphertext(rot_2);
+
+        apply_algebraic_simplifications(&mut ir, &params);
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        assert_eq!(query.get_unary_operand(out).unwrap(), ct);
+
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let twelve = ir.add_input_literal(Literal::U64(12));
+        let three = ir.add_input_literal(Literal::U64(3));
+        let rot_1 = ir.add_rotate_left(ct, twelve);
+        let rot_2 = ir.add_rotate_left(rot_1, three);
+        let out = ir.add_output_ciphertext(rot_2);
+
+        apply_algebraic_simplifications(&mut ir, &params);
+
+        let query = GraphQuery::new(&ir.graph.0);
+
+        let rot = query.get_unary_operand(out).unwrap();
+        let (x, amount) = query.get_binary_operands(rot).unwrap();
+
+        assert_eq!(ir.graph[rot].operation, ShiftRight);
+        assert_eq!(x, ct);
+        assert_eq!(ir.graph[amount].operation, Literal(Literal::U64(1)));
+    }
+}
@@ -1,10 +1,12 @@
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 
-use petgraph::algo::is_isomorphic_matching;
+use petgraph::algo::{is_isomorphic_matching, toposort};
 use petgraph::stable_graph::{NodeIndex, StableGraph};
 use petgraph::visit::{EdgeRef, IntoEdgeReferences, IntoNodeReferences};
-use petgraph::Graph;
+use petgraph::{Direction, Graph};
 use serde::{Deserialize, Serialize};
 
 use crate::{Operation, Render};
@@ -190,6 +192,71 @@ where
     pub fn new() -> Self {
         Self(StableGraph::new())
     }
+
+    /**
+     * Returns a hash of this graph's structure that doesn't depend on the
+     * order its nodes were added in, so graphs this type's `==` considers
+     * equal hash equal.
+     *
+     * # Remarks
+     * Each node hashes its operation with its operands' hashes, ordered
+     * by edge role, and the graph hashes the sorted multiset of its node
+     * hashes. Unlike `==`, which solves graph isomorphism, this runs in
+     * near-linear time, so it suits keying a cache of compilations.
+     * Different graphs may collide, so confirm cache hits with `==`.
+     *
+     * The hash uses [`DefaultHasher`], which is deterministic but may
+     * change between Rust releases, so don't persist it.
+     *
+     * # Panics
+     * Panics if the graph contains a cycle.
+     */
+    pub fn structural_hash(&self) -> u64 {
+        let order = toposort(&self.0, None).expect("Compilation graph contains a cycle");
+
+        let mut hashes = vec![0u64; self.node_bound()];
+
+        for id in order {
+            let mut operands = self
+                .edges_directed(id, Direction::Incoming)
+                .map(|e| (edge_order(e.weight()), hashes[e.source().index()]))
+                .collect::<Vec<_>>();
+
+            operands.sort_unstable();
+
+            let mut hasher = DefaultHasher::new();
+            self.0[id].operation.hash(&mut hasher);
+            operands.hash(&mut hasher);
+
+            hashes[id.index()] = hasher.finish();
+        }
+
+        let mut node_hashes = self
+            .node_indices()
+            .map(|id| hashes[id.index()])
+            .collect::<Vec<_>>();
+
+        node_hashes.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        node_hashes.hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
+
+/**
+ * Orders operands by their role, so operand lists compare independently of
+ * edge insertion order.
+ */
+fn edge_order(edge: &EdgeInfo) -> (u8, usize) {
+    match edge {
+        EdgeInfo::Left => (0, 0),
+        EdgeInfo::Right => (1, 0),
+        EdgeInfo::Unary => (2, 0),
+        EdgeInfo::Unordered => (3, 0),
+        EdgeInfo::Ordered(i) => (4, *i),
+    }
 }
 
 impl<O> Default for CompilationResult<O>
@@ -279,3 +346,81 @@ where
         self.graph.add_edge(from, to, edge);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Operation as OperationTrait;
+
+    #[derive(Clone, Debug, Hash, PartialEq, Eq)]
+    enum Operation {
+        Add,
+        Mul,
+        In,
+    }
+
+    impl OperationTrait for Operation {
+        fn is_binary(&self) -> bool {
+            matches!(self, Self::Add | Self::Mul)
+        }
+
+        fn is_commutative(&self) -> bool {
+            matches!(self, Self::Add | Self::Mul)
+        }
+
+        fn is_unary(&self) -> bool {
+            false
+        }
+
+        fn is_unordered(&self) -> bool {
+            false
+        }
+
+        fn is_ordered(&self) -> bool {
+            false
+        }
+    }
+
+    type TestGraph = Context<Operation, ()>;
+
+    // (in_1 + in_2) * in_3, optionally with the operations swapped.
+    fn build(first: Operation, second: Operation) -> TestGraph {
+        let mut graph = TestGraph::new(());
+
+        let in_1 = graph.add_node(Operation::In);
+        let in_2 = graph.add_node(Operation::In);
+        let x = graph.add_binary_operation(first, in_1, in_2);
+        let in_3 = graph.add_node(Operation::In);
+        graph.add_binary_operation(second, x, in_3);
+
+        graph
+    }
+
+    #[test]
+    fn structural_hash_ignores_insertion_order() {
+        let a = build(Operation::Add, Operation::Mul);
+
+        // The same graph, adding inputs in reverse and edges right first.
+        let mut b = TestGraph::new(());
+
+        let in_3 = b.add_node(Operation::In);
+        let in_2 = b.add_node(Operation::In);
+        let in_1 = b.add_node(Operation::In);
+        let add = b.add_node(Operation::Add);
+        b.add_edge(in_2, add, EdgeInfo::Right);
+        b.add_edge(in_1, add, EdgeInfo::Left);
+        b.add_binary_operation(Operation::Mul, add, in_3);
+
+        assert_eq!(a.graph, b.graph);
+        assert_eq!(a.graph.structural_hash(), b.graph.structural_hash());
+    }
+
+    #[test]
+    fn structural_hash_distinguishes_different_graphs() {
+        let a = build(Operation::Add, Operation::Mul);
+        let b = build(Operation::Mul, Operation::Add);
+
+        assert_ne!(a.graph, b.graph);
+        assert_ne!(a.graph.structural_hash(), b.graph.structural_hash());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use petgraph::{
     dot::Dot,
@@ -202,8 +202,8 @@ where
 {
     // The one unsafe line in the function...
     let graph = &mut *graph;
-    let mut ready: HashSet<NodeIndex> = HashSet::new();
-    let mut visited: HashSet<NodeIndex> = HashSet::new();
+    let mut ready: BTreeSet<NodeIndex> = BTreeSet::new();
+    let mut visited: BTreeSet<NodeIndex> = BTreeSet::new();
     let prev_direction = if forward {
         Direction::Incoming
     } else {
@@ -31,13 +31,19 @@ pub fn fhe_program_impl(
 
     let chain_count = attr_params.chain_count;
 
+    let mut plain_checks = vec![];
+
     let unwrapped_inputs = match extract_fn_arguments(inputs) {
         Ok(v) => {
             for arg in &v {
-                if !arg.0.is_empty() {
-                    return proc_macro::TokenStream::from(
-                        quote_spanned! { arg.1.span() => compile_error!("FHE program arguments do not support attributes.")},
-                    );
+                for attr in &arg.0 {
+                    if !attr.path.is_ident("plain") || !attr.tokens.is_empty() {
+                        return proc_macro::TokenStream::from(
+                            quote_spanned! { attr.span() => compile_error!("FHE program arguments only support the `#[plain]` attribute.")},
+                        );
+                    }
+
+                    plain_checks.push(emit_plain_check(arg.1));
                 }
             }
 
@@ -130,17 +136,17 @@ pub fn fhe_program_impl(
         }
 
         impl sunscreen::FheProgramFn for #fhe_program_struct_name {
-            fn build(&self, params: &sunscreen::Params) -> sunscreen::Result<sunscreen::fhe::FheFrontendCompilation> {
+            fn build_with_max_nodes(&self, params: &sunscreen::Params, max_nodes: Option<usize>) -> sunscreen::Result<sunscreen::fhe::FheFrontendCompilation> {
                 use std::cell::RefCell;
                 use std::mem::transmute;
-                use sunscreen::{fhe::{CURRENT_FHE_CTX, FheContext}, Error, INDEX_ARENA, Result, Params, SchemeType, Value, types::{intern::{FheProgramNode, Input, Output}, NumCiphertexts, Type, TypeName, SwapRows, LaneCount, TypeNameInstance}};
+                use sunscreen::{fhe::{CURRENT_FHE_CTX, FheContext, FheData, NodeLimitExceeded}, Error, INDEX_ARENA, Result, Params, SchemeType, Value, types::{intern::{FheProgramNode, Input, Output}, NumCiphertexts, Type, TypeName, SwapRows, LaneCount, TypeNameInstance}};
 
                 if SchemeType::Bfv != params.scheme_type {
                     return Err(Error::IncorrectScheme)
                 }
 
                 // TODO: Other schemes.
-                let mut context = FheContext::new(params.clone());
+                let mut context = FheContext::new(FheData::new(params.clone()).with_max_nodes(max_nodes));
 
                 CURRENT_FHE_CTX.with(|ctx| {
                     #[allow(clippy::type_complexity)]
@@ -154,30 +160,31 @@ pub fn fhe_program_impl(
                     // returns.
                     ctx.swap(&RefCell::new(Some(unsafe { transmute(&mut context) })));
 
-                    #(#var_decl)*
-
+                    // Declaring inputs and capturing outputs add nodes too, so
+                    // they run under catch_unwind in case they exceed max_nodes.
                     let panic_res = std::panic::catch_unwind(|| {
-                        internal(#(#args),*)
+                        #(#var_decl)*
+
+                        let v = internal(#(#args),*);
+
+                        #output_capture
                     });
 
                     // when panicing or not, we need to collect our indicies arena and
                     // unset the context reference.
-                    match panic_res {
-                        Ok(v) => { #output_capture },
-                        Err(err) => {
-                            INDEX_ARENA.with(|allocator| {
-                                allocator.borrow_mut().reset()
-                            });
-                            ctx.swap(&RefCell::new(None));
-                            std::panic::resume_unwind(err)
-                        }
-                    };
-
                     INDEX_ARENA.with(|allocator| {
                         allocator.borrow_mut().reset()
                     });
                     ctx.swap(&RefCell::new(None));
-                });
+
+                    match panic_res {
+                        Ok(()) => Ok(()),
+                        Err(err) => match err.downcast::<NodeLimitExceeded>() {
+                            Ok(e) => Err(Error::from(*e)),
+                            Err(err) => std::panic::resume_unwind(err),
+                        },
+                    }
+                })?;
 
                 Ok(context.graph)
             }
@@ -213,5 +220,46 @@ pub fn fhe_program_impl(
         #vis const #fhe_program_name: #fhe_program_struct_name = #fhe_program_struct_name {
             chain_count: #chain_count
         };
+
+        #(#plain_checks)*
     })
 }
+
+/**
+ * Emits a compile-time check that an argument marked `#[plain]` is
+ * passed unencrypted, i.e. its (element) type is an `FheType` rather
+ * than a `Cipher`.
+ *
+ * # Remarks
+ * A `Cipher` written out directly gets a dedicated error. Anything else
+ * (e.g. an alias of a `Cipher`) falls back to a trait bound check.
+ */
+fn emit_plain_check(arg_type: &Type) -> TokenStream {
+    let elem = match arg_type {
+        Type::Array(a) => &*a.elem,
+        t => t,
+    };
+
+    match elem {
+        Type::Array(_) => emit_plain_check(elem),
+        Type::Path(p)
+            if p.path
+                .segments
+                .last()
+                .map_or(false, |s| s.ident == "Cipher") =>
+        {
+            let cipher = &p.path.segments.last().unwrap().ident;
+
+            quote_spanned! {cipher.span() =>
+                compile_error!("`#[plain]` arguments must not be encrypted");
+            }
+        }
+        _ => quote_spanned! {elem.span() =>
+            const _: fn() = || {
+                fn assert_plain<T: sunscreen::types::FheType>() {}
+
+                assert_plain::<#elem>();
+            };
+        },
+    }
+}
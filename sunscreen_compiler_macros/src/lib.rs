@@ -30,9 +30,31 @@ pub fn derive_typename(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
  * This function gets run by the compiler to build up the [`fhe_program`](macro@fhe_program) you specify and does not
  * directly or eagerly perform homomorphic operations.
  *
+ * The declared return type determines how the runtime decodes outputs, so
+ * the body's final expression is checked against it: returning a
+ * `Cipher<Signed>` from a function declared to return
+ * `Cipher<Fractional<64>>` is a compile error rather than a garbled
+ * decryption.
+ *
  * # Parameters
  * * `scheme` (required): Designates the scheme this [`fhe_program`](macro@fhe_program) uses. Today, this must be `"bfv"`.
  *
+ * # Arguments
+ * Arguments of type `Cipher<T>` are encrypted and the rest are
+ * plaintexts, which operations consume as plaintext operands (e.g. a
+ * plaintext multiplication, which adds far less noise than a ciphertext
+ * one). Marking an argument `#[plain]` documents that it's public and
+ * makes passing it encrypted a compile error:
+ *
+ * ```rust,ignore
+ * # use sunscreen::{fhe_program, types::{bfv::Unsigned, Cipher}};
+ *
+ * #[fhe_program(scheme = "bfv")]
+ * fn weigh(a: Cipher<Unsigned>, #[plain] w: Unsigned) -> Cipher<Unsigned> {
+ *   a * w
+ * }
+ * ```
+ *
  * # Examples
  * ```rust,ignore
  * # use sunscreen::{fhe_program, types::{bfv::Signed, Cipher}, Params, Context};
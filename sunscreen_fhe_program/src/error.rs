@@ -25,6 +25,20 @@ pub enum Error {
      */
     #[error("Attempted to deserialize and unknown scheme type.")]
     InvalidSchemeType,
+
+    /**
+     * A serialized [`FheProgram`](crate::FheProgram) exceeded the limits
+     * passed to [`load_with_limits`](crate::FheProgramTrait::load_with_limits).
+     * The inner value is the program's (node, edge) count.
+     */
+    #[error("FHE program with {} nodes and {} edges exceeds limits", self.unwrap_size().0, self.unwrap_size().1)]
+    FheProgramTooLarge(Box<(usize, usize)>),
+
+    /**
+     * Failed to deserialize an [`FheProgram`](crate::FheProgram).
+     */
+    #[error("Failed to deserialize FHE program: {0}")]
+    DeserializationError(Box<String>),
 }
 
 const_assert!(std::mem::size_of::<Error>() <= 16);
@@ -36,6 +50,20 @@ impl Error {
     pub fn ir_error(inner: &[IRError]) -> Self {
         Self::IRError(Box::new(inner.to_owned()))
     }
+
+    /**
+     * Creates an [`Error::DeserializationError`].
+     */
+    pub fn deserialization_error(msg: &str) -> Self {
+        Self::DeserializationError(Box::new(msg.to_owned()))
+    }
+
+    fn unwrap_size(&self) -> &(usize, usize) {
+        match self {
+            Self::FheProgramTooLarge(d) => d,
+            _ => panic!("Not an FheProgramTooLarge"),
+        }
+    }
 }
 
 /**
@@ -5,6 +5,7 @@
 //! for Sunscreen's compiler backend.
 
 mod error;
+mod limits;
 mod literal;
 mod operation;
 
@@ -16,6 +17,7 @@ use petgraph::{
     graph::{Graph, NodeIndex},
     stable_graph::StableGraph,
     visit::IntoNeighbors,
+    Direction,
 };
 use serde::{Deserialize, Serialize};
 
@@ -26,7 +28,7 @@ pub use seal_fhe::SecurityLevel;
 
 use sunscreen_compiler_common::{CompilationResult, Context, EdgeInfo, NodeInfo};
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Clone, Copy, Serialize, Hash, Deserialize, PartialEq, Eq)]
 /**
@@ -234,6 +236,10 @@ pub trait FheProgramTrait {
 
     /**
      * Returns the node indices of output ciphertexts
+     *
+     * # Remarks
+     * Outputs are returned, and numbered when running the program, in
+     * node index order, which is the order they were declared in.
      */
     fn get_outputs(&self) -> Box<dyn Iterator<Item = NodeIndex> + '_>;
 
@@ -249,9 +255,28 @@ pub trait FheProgramTrait {
      * * `nodes`: indices specifying a set of nodes in the graph. Prune return a new
      *   [`FheProgram`] containing nodes in the transitive closure
      *   of this set.
+     *
+     * # Remarks
+     * The kept nodes are renumbered but retain their relative order, so
+     * kept outputs keep their declaration order.
      */
     fn prune(&self, nodes: &[NodeIndex]) -> Self;
 
+    /**
+     * Returns a derived FheProgram without the inputs no output depends
+     * on, along with the original argument index of each input it keeps.
+     *
+     * # Remarks
+     * The kept inputs are renumbered `0..n` in their original order, so
+     * the `i`th argument of the result is the `kept[i]`th argument of
+     * this program. Callers build the result's argument list from their
+     * original one with `kept.iter().map(|i| args[*i])`. Dead code is
+     * removed as with [`prune`](Self::prune).
+     */
+    fn prune_inputs(&self) -> (Self, Vec<usize>)
+    where
+        Self: Sized;
+
     /**
      * Validates this [`FheProgram`] for correctness.
      */
@@ -267,6 +292,36 @@ pub trait FheProgramTrait {
      * operations.
      */
     fn requires_galois_keys(&self) -> bool;
+
+    /**
+     * Returns the multiplicative depth of each output, keyed by the
+     * output's index.
+     *
+     * # Remarks
+     * A node's depth is the largest number of ciphertext-ciphertext
+     * multiplications on any path from an input to it. Plaintext
+     * multiplications, additions, and rotations don't count. The deepest
+     * output drives the scheme parameters, so this shows which output to
+     * optimize.
+     */
+    fn depth_profile(&self) -> BTreeMap<usize, usize>;
+
+    /**
+     * Deserializes a bincode-encoded [`FheProgram`] from an untrusted
+     * source.
+     *
+     * # Remarks
+     * Before building the graph, this checks the input declares at most
+     * `max_nodes` nodes and `max_edges` edges, returning
+     * [`Error::FheProgramTooLarge`] otherwise. Length prefixes in the
+     * input are never trusted beyond the input's actual size, so a
+     * malicious program can't trigger a large allocation.
+     *
+     * This does not [`validate`](Self::validate) the program.
+     */
+    fn load_with_limits(bytes: &[u8], max_nodes: usize, max_edges: usize) -> Result<Self>
+    where
+        Self: Sized;
 }
 
 impl FheProgramTrait for FheProgram {
@@ -341,7 +396,7 @@ impl FheProgramTrait for FheProgram {
         let (res, revmap) = dag_to_toposorted_adjacency_list(&compact_graph, &topo);
         let (_, closure) = dag_transitive_reduction_closure(&res);
 
-        let mut closure_set = HashSet::new();
+        let mut closure_set = BTreeSet::new();
 
         let mut visit: Vec<NodeIndex> = vec![];
 
@@ -387,6 +442,85 @@ impl FheProgramTrait for FheProgram {
         }
     }
 
+    fn prune_inputs(&self) -> (FheProgram, Vec<usize>) {
+        let outputs = self.get_outputs().collect::<Vec<_>>();
+        let pruned = self.prune(&outputs);
+
+        // prune keeps every input, but only used ones still have edges
+        // out of them.
+        let mut kept = pruned
+            .graph
+            .node_indices()
+            .filter_map(|id| match pruned.graph[id].operation {
+                Operation::InputCiphertext(i) | Operation::InputPlaintext(i)
+                    if pruned
+                        .graph
+                        .neighbors_directed(id, Direction::Outgoing)
+                        .next()
+                        .is_some() =>
+                {
+                    Some(i)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        kept.sort_unstable();
+        kept.dedup();
+
+        let graph = Graph::from(pruned.graph.0.clone()).filter_map(
+            |_, n| {
+                let operation = match n.operation {
+                    Operation::InputCiphertext(i) => {
+                        Operation::InputCiphertext(kept.binary_search(&i).ok()?)
+                    }
+                    Operation::InputPlaintext(i) => {
+                        Operation::InputPlaintext(kept.binary_search(&i).ok()?)
+                    }
+                    _ => return Some(n.clone()),
+                };
+
+                Some(NodeInfo::new(operation))
+            },
+            |_, e| Some(*e),
+        );
+
+        let program = Self {
+            data: self.data,
+            graph: CompilationResult(StableGraph::from(graph)),
+        };
+
+        (program, kept)
+    }
+
+    fn load_with_limits(bytes: &[u8], max_nodes: usize, max_edges: usize) -> Result<Self> {
+        limits::load_with_limits(bytes, max_nodes, max_edges)
+    }
+
+    fn depth_profile(&self) -> BTreeMap<usize, usize> {
+        let mut depth = vec![0; self.graph.node_bound()];
+
+        // FHE programs are DAGs, so this can't fail.
+        for n in toposort(&self.graph.0, None).unwrap() {
+            let operand_depth = self
+                .graph
+                .neighbors_directed(n, Direction::Incoming)
+                .map(|m| depth[m.index()])
+                .max()
+                .unwrap_or(0);
+
+            depth[n.index()] = match self.graph[n].operation {
+                Operation::Multiply => operand_depth + 1,
+                _ => operand_depth,
+            };
+        }
+
+        self.get_outputs()
+            .enumerate()
+            .map(|(i, n)| (i, depth[n.index()]))
+            .collect()
+    }
+
     fn validate(&self) -> Result<()> {
         let errors = validation::validate_ir(self);
 
@@ -503,6 +637,76 @@ mod tests {
         assert!(eq(&pruned, &expected_ir));
     }
 
+    #[test]
+    fn prune_inputs_drops_unused_arguments() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_input_plaintext(2);
+
+        // b only feeds dead code.
+        ir.add_negate(b);
+
+        let x = ir.add_multiply_plaintext(a, c);
+        ir.add_output_ciphertext(x);
+
+        let (pruned, kept) = ir.prune_inputs();
+
+        assert_eq!(kept, vec![0, 2]);
+
+        let mut expected = FheProgram::new(SchemeType::Bfv);
+
+        let a = expected.add_input_ciphertext(0);
+        let c = expected.add_input_plaintext(1);
+        let x = expected.add_multiply_plaintext(a, c);
+        expected.add_output_ciphertext(x);
+
+        assert!(eq(&pruned, &expected));
+    }
+
+    #[test]
+    fn pruning_preserves_output_order() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct1 = ir.add_input_ciphertext(0);
+        let ct2 = ir.add_input_ciphertext(1);
+
+        // Dead code before every output shifts their indices when pruned.
+        ir.add_negate(ct2);
+
+        let mul = ir.add_multiply(ct1, ct2);
+        let add = ir.add_add(ct1, ct2);
+        let o1 = ir.add_output_ciphertext(mul);
+        let o2 = ir.add_output_ciphertext(ct2);
+        let o3 = ir.add_output_ciphertext(add);
+
+        // Pass the outputs out of order; the result is still in node order.
+        let pruned = ir.prune(&[o3, o1, o2]);
+
+        let sources = pruned
+            .get_outputs()
+            .map(|o| {
+                let source = pruned
+                    .graph
+                    .neighbors_directed(o, Direction::Incoming)
+                    .next()
+                    .unwrap();
+
+                pruned.graph[source].operation.clone()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            sources,
+            vec![
+                Operation::Multiply,
+                Operation::InputCiphertext(1),
+                Operation::Add
+            ]
+        );
+    }
+
     #[test]
     fn pruning_empty_node_list_results_in_inputs_only() {
         let mut ir = FheProgram::new(SchemeType::Bfv);
@@ -527,6 +731,30 @@ mod tests {
         assert!(eq(&pruned, &expected_ir));
     }
 
+    #[test]
+    fn depth_profile_reports_each_output() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let l = ir.add_input_literal(Literal::from(3u64));
+
+        let ab = ir.add_multiply(a, b);
+        ir.add_output_ciphertext(ab);
+
+        let abb = ir.add_multiply(ab, b);
+        let abb = ir.add_add(abb, a);
+        let abb = ir.add_multiply_plaintext(abb, l);
+        let abbb = ir.add_multiply(abb, b);
+        ir.add_output_ciphertext(abbb);
+
+        let profile = ir.depth_profile();
+
+        assert_eq!(profile.len(), 2);
+        assert_eq!(profile[&0], 1);
+        assert_eq!(profile[&1], 3);
+    }
+
     #[test]
     fn can_roundtrip_scheme_type() {
         let schemes = [SchemeType::Bfv];
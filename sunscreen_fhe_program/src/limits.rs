@@ -0,0 +1,176 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use bincode::Options;
+use petgraph::stable_graph::NodeIndex;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use sunscreen_compiler_common::{EdgeInfo, NodeInfo};
+
+use crate::{Error, FheProgram, Operation, Result, SchemeType};
+
+/**
+ * Deserializes a sequence of `T`, discarding each element after
+ * validating it and keeping only the count.
+ */
+struct Count<T>(usize, PhantomData<T>);
+
+impl<'de, T> Deserialize<'de> for Count<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CountVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for CountVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = Count<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a sequence")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut count = 0;
+
+                while seq.next_element::<T>()?.is_some() {
+                    count += 1;
+                }
+
+                Ok(Count(count, PhantomData))
+            }
+        }
+
+        deserializer.deserialize_seq(CountVisitor(PhantomData))
+    }
+}
+
+// Mirrors petgraph's enum of the same name.
+#[derive(serde::Deserialize)]
+#[allow(unused)]
+enum EdgeProperty {
+    Undirected,
+    Directed,
+}
+
+// These mirror the serialized layout of FheProgram (i.e. Context and
+// petgraph's StableGraph) field for field, so they work with
+// non-self-describing formats like bincode.
+#[derive(serde::Deserialize)]
+#[allow(unused)]
+struct GraphShape {
+    nodes: Count<NodeInfo<Operation>>,
+    node_holes: Count<NodeIndex>,
+    edge_property: EdgeProperty,
+    edges: Count<Option<(NodeIndex, NodeIndex, EdgeInfo)>>,
+}
+
+#[derive(serde::Deserialize)]
+struct CompilationResultShape(GraphShape);
+
+#[derive(serde::Deserialize)]
+#[allow(unused)]
+struct FheProgramShape {
+    graph: CompilationResultShape,
+    data: SchemeType,
+}
+
+fn options() -> impl Options {
+    // Matches bincode::serialize, but never reads more than the input's length.
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+pub fn load_with_limits(bytes: &[u8], max_nodes: usize, max_edges: usize) -> Result<FheProgram> {
+    let options = options().with_limit(bytes.len() as u64);
+
+    // First, walk the input without building the graph to learn its size.
+    let shape: FheProgramShape = options
+        .deserialize(bytes)
+        .map_err(|e| Error::deserialization_error(&e.to_string()))?;
+
+    let graph = shape.graph.0;
+
+    // Holes occupy node slots too.
+    let nodes = graph.nodes.0 + graph.node_holes.0;
+    let edges = graph.edges.0;
+
+    if nodes > max_nodes || edges > max_edges {
+        return Err(Error::FheProgramTooLarge(Box::new((nodes, edges))));
+    }
+
+    options
+        .deserialize(bytes)
+        .map_err(|e| Error::deserialization_error(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FheProgramTrait;
+
+    fn make_program(n: usize) -> FheProgram {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let mut x = ir.add_input_ciphertext(0);
+
+        for _ in 0..n {
+            x = ir.add_negate(x);
+        }
+
+        ir.add_output_ciphertext(x);
+
+        ir
+    }
+
+    #[test]
+    fn can_load_program_within_limits() {
+        let ir = make_program(10);
+        let bytes = bincode::serialize(&ir).unwrap();
+
+        let loaded = FheProgram::load_with_limits(&bytes, 12, 11).unwrap();
+
+        assert_eq!(loaded.graph.node_count(), 12);
+        assert_eq!(loaded.graph.edge_count(), 11);
+    }
+
+    #[test]
+    fn rejects_program_over_limits() {
+        let ir = make_program(10);
+        let bytes = bincode::serialize(&ir).unwrap();
+
+        let expected = Error::FheProgramTooLarge(Box::new((12, 11)));
+
+        assert_eq!(
+            FheProgram::load_with_limits(&bytes, 11, 100).unwrap_err(),
+            expected
+        );
+        assert_eq!(
+            FheProgram::load_with_limits(&bytes, 100, 10).unwrap_err(),
+            expected
+        );
+    }
+
+    #[test]
+    fn huge_declared_node_count_fails_without_allocating() {
+        let ir = make_program(1);
+        let mut bytes = bincode::serialize(&ir).unwrap();
+
+        // The node sequence's length prefix comes first. Claim there are
+        // u64::MAX nodes; trusting this would abort on allocation.
+        bytes[0..8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        assert!(matches!(
+            FheProgram::load_with_limits(&bytes, usize::MAX, usize::MAX),
+            Err(Error::DeserializationError(_))
+        ));
+    }
+}
@@ -15,6 +15,25 @@ pub enum Literal {
      * A plaintext stored as a sequence of bytes.
      */
     Plaintext(Vec<u8>),
+
+    /**
+     * An unsigned 128-bit integer, for constants wider than 64 bits.
+     */
+    U128(u128),
+}
+
+impl Literal {
+    /**
+     * The value of this literal as a rotation amount, or [`None`] if it
+     * isn't an integer or doesn't fit in an [`i32`].
+     */
+    pub fn as_rotation_amount(&self) -> Option<i32> {
+        match self {
+            Self::U64(v) => i32::try_from(*v).ok(),
+            Self::U128(v) => i32::try_from(*v).ok(),
+            Self::Plaintext(_) => None,
+        }
+    }
 }
 
 impl From<u64> for Literal {
@@ -22,3 +41,32 @@ impl From<u64> for Literal {
         Self::U64(val)
     }
 }
+
+impl From<u128> for Literal {
+    fn from(val: u128) -> Self {
+        Self::U128(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_literals_keep_existing_encodings() {
+        // Serialized programs identify variants by index, so new ones
+        // must come last.
+        let plaintext = bincode::serialize(&Literal::Plaintext(vec![])).unwrap();
+
+        assert_eq!(plaintext[0..4], 1u32.to_le_bytes());
+    }
+
+    #[test]
+    fn rotation_amounts_must_fit_i32() {
+        assert_eq!(Literal::U64(3).as_rotation_amount(), Some(3));
+        assert_eq!(Literal::U128(3).as_rotation_amount(), Some(3));
+        assert_eq!(Literal::U64(1 << 32).as_rotation_amount(), None);
+        assert_eq!(Literal::U128(u128::MAX).as_rotation_amount(), None);
+        assert_eq!(Literal::Plaintext(vec![]).as_rotation_amount(), None);
+    }
+}
@@ -58,6 +58,47 @@ pub enum Error {
     #[error("Type mismatch, expected {:#?} found {:#?}", self.unwrap_type_mismatch_data().0, self.unwrap_type_mismatch_data().1)]
     TypeMismatch(Box<(Type, Type)>),
 
+    /**
+     * Fewer arguments were given than the FHE program's signature
+     * declares.
+     */
+    #[error("Expected {expected} arguments, got only {actual}")]
+    TooFewArguments {
+        /**
+         * The number of arguments the signature declares.
+         */
+        expected: usize,
+
+        /**
+         * The number of arguments given.
+         */
+        actual: usize,
+    },
+
+    /**
+     * More arguments were given than the FHE program's signature
+     * declares.
+     */
+    #[error("Expected {expected} arguments, got {actual}")]
+    TooManyArguments {
+        /**
+         * The number of arguments the signature declares.
+         */
+        expected: usize,
+
+        /**
+         * The number of arguments given.
+         */
+        actual: usize,
+    },
+
+    /**
+     * The argument at the contained index doesn't have the type the FHE
+     * program's signature declares for it.
+     */
+    #[error("Argument {} should be {:?}, found {:?}", self.unwrap_argument_type_mismatch_data().0, self.unwrap_argument_type_mismatch_data().1, self.unwrap_argument_type_mismatch_data().2)]
+    ArgumentTypeMismatch(Box<(usize, Type, Type)>),
+
     /**
      * The vector indicating the number of ciphertexts in the return types isn't the same length
      * as the signature's return type. Running valid FHE programs created by the Sunscreen compiler
@@ -72,6 +113,13 @@ pub enum Error {
     #[error("Too much noise")]
     TooMuchNoise,
 
+    /**
+     * The ciphertext is transparent, so it has no meaningful noise budget.
+     * This usually means an FHE program multiplied by a plaintext zero.
+     */
+    #[error("The ciphertext is transparent")]
+    TransparentCiphertext,
+
     /**
      * Executing an Fhe Program failed.
      */
@@ -85,6 +133,20 @@ pub enum Error {
     #[error("Type encoding error: {0}")]
     FheTypeError(Box<String>),
 
+    /**
+     * A floating point value to encode was NaN or infinite, which no
+     * plaintext can represent.
+     */
+    #[error("Invalid floating point value: {0}")]
+    InvalidFloatValue(Box<String>),
+
+    /**
+     * A decrypted value doesn't fit in the type it was decoded as, so
+     * decoding it would wrap.
+     */
+    #[error("Decrypted value overflows its type")]
+    Overflow,
+
     /**
      * Failed to deserialize bytes as a [`Params`](crate::Params) object.
      */
@@ -116,6 +178,91 @@ pub enum Error {
     #[error("Not a SEAL plaintext")]
     NotASealPlaintext,
 
+    /**
+     * The plaintext modulus is too small for a type's encoding.
+     */
+    #[error("Plaintext modulus {actual} is too small; the type requires at least {required}")]
+    PlainModulusTooSmall {
+        /**
+         * The minimum plaintext modulus the type requires.
+         */
+        required: u64,
+
+        /**
+         * The plaintext modulus in the given parameters.
+         */
+        actual: u64,
+    },
+
+    /**
+     * A prime in the parameters' coefficient modulus isn't congruent to
+     * 1 modulo twice the lattice dimension, so SEAL can't use it.
+     */
+    #[error("Coefficient modulus prime {prime} is not NTT-compatible with the lattice dimension")]
+    InvalidCoeffModulus {
+        /**
+         * The incompatible prime.
+         */
+        prime: u64,
+    },
+
+    /**
+     * SEAL failed to resize a plaintext to the contained number of
+     * coefficients while encoding a value.
+     */
+    #[error("Failed to resize plaintext to {} coefficients: {}", self.unwrap_plaintext_coefficient_data().0, self.unwrap_plaintext_coefficient_data().1)]
+    PlaintextResizeFailed(Box<(usize, seal_fhe::Error)>),
+
+    /**
+     * SEAL failed to set the plaintext coefficient at the contained index
+     * while encoding a value.
+     */
+    #[error("Failed to set plaintext coefficient {}: {}", self.unwrap_plaintext_coefficient_data().0, self.unwrap_plaintext_coefficient_data().1)]
+    PlaintextSetCoefficientFailed(Box<(usize, seal_fhe::Error)>),
+
+    /**
+     * A raw coefficient passed to
+     * [`Plaintext::from_coefficients`](crate::Plaintext::from_coefficients)
+     * isn't less than the plaintext modulus.
+     */
+    #[error("Coefficient {index} is not less than the plaintext modulus {plain_modulus}")]
+    CoefficientOutOfRange {
+        /**
+         * The index of the offending coefficient.
+         */
+        index: usize,
+
+        /**
+         * The plaintext modulus in the given parameters.
+         */
+        plain_modulus: u64,
+    },
+
+    /**
+     * More raw coefficients were passed to
+     * [`Plaintext::from_coefficients`](crate::Plaintext::from_coefficients)
+     * than a plaintext under the given parameters holds.
+     */
+    #[error("Got {count} coefficients, but plaintexts hold at most {lattice_dimension}")]
+    TooManyCoefficients {
+        /**
+         * The number of coefficients given.
+         */
+        count: usize,
+
+        /**
+         * The lattice dimension in the given parameters.
+         */
+        lattice_dimension: u64,
+    },
+
+    /**
+     * Failed to create the thread pool requested with
+     * [`set_thread_pool_size`](crate::set_thread_pool_size).
+     */
+    #[error("Failed to create thread pool: {0}")]
+    ThreadPoolError(Box<String>),
+
     /**
      * An error occurred when creating or verifying a proof.
      */
@@ -140,6 +287,13 @@ impl Error {
         Self::TypeMismatch(Box::new((expected.clone(), actual.clone())))
     }
 
+    /**
+     * Create an [`Error::ArgumentTypeMismatch`].
+     */
+    pub fn argument_type_mismatch(index: usize, expected: &Type, actual: &Type) -> Self {
+        Self::ArgumentTypeMismatch(Box::new((index, expected.clone(), actual.clone())))
+    }
+
     /**
      * Create an [`Error::FheTypeError`].
      */
@@ -147,6 +301,27 @@ impl Error {
         Self::FheTypeError(Box::new(msg.to_owned()))
     }
 
+    /**
+     * Create an [`Error::InvalidFloatValue`].
+     */
+    pub fn invalid_float_value(msg: &str) -> Self {
+        Self::InvalidFloatValue(Box::new(msg.to_owned()))
+    }
+
+    /**
+     * Create an [`Error::PlaintextResizeFailed`].
+     */
+    pub fn plaintext_resize_failed(len: usize, err: seal_fhe::Error) -> Self {
+        Self::PlaintextResizeFailed(Box::new((len, err)))
+    }
+
+    /**
+     * Create an [`Error::PlaintextSetCoefficientFailed`].
+     */
+    pub fn plaintext_set_coefficient_failed(index: usize, err: seal_fhe::Error) -> Self {
+        Self::PlaintextSetCoefficientFailed(Box::new((index, err)))
+    }
+
     fn unwrap_argument_mismatch_data(&self) -> &(Vec<Type>, Vec<Type>) {
         match self {
             Self::ArgumentMismatch(d) => d,
@@ -154,12 +329,27 @@ impl Error {
         }
     }
 
+    fn unwrap_plaintext_coefficient_data(&self) -> &(usize, seal_fhe::Error) {
+        match self {
+            Self::PlaintextResizeFailed(d) => d,
+            Self::PlaintextSetCoefficientFailed(d) => d,
+            _ => panic!("Not a plaintext coefficient error"),
+        }
+    }
+
     fn unwrap_type_mismatch_data(&self) -> &(Type, Type) {
         match self {
             Self::TypeMismatch(d) => d,
             _ => panic!("Not a type mismatch"),
         }
     }
+
+    fn unwrap_argument_type_mismatch_data(&self) -> &(usize, Type, Type) {
+        match self {
+            Self::ArgumentTypeMismatch(d) => d,
+            _ => panic!("Not an argument type mismatch"),
+        }
+    }
 }
 
 impl From<bincode::Error> for Error {
@@ -46,6 +46,33 @@ pub struct PublicKey {
     pub relin_key: Option<WithContext<RelinearizationKeys>>,
 }
 
+impl PublicKey {
+    /**
+     * Whether this key can run FHE programs that rotate or swap the rows
+     * of Batched ciphertexts.
+     *
+     * # Remarks
+     * Additions, subtractions, negations, and plaintext operations need
+     * only the encryption key, so a key without Galois keys (e.g. one
+     * received from a party that withheld them) can still run any
+     * program that doesn't rotate.
+     */
+    pub fn can_rotate(&self) -> bool {
+        self.galois_key.is_some()
+    }
+
+    /**
+     * Whether this key can run FHE programs that multiply ciphertexts.
+     *
+     * # Remarks
+     * The compiler relinearizes after every ciphertext multiplication,
+     * which needs relinearization keys.
+     */
+    pub fn can_relinearize(&self) -> bool {
+        self.relin_key.is_some()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 /**
  * The private key used to decrypt ciphertexts.
@@ -10,8 +10,11 @@ mod keys;
 mod metadata;
 mod run;
 mod runtime;
+mod seal_program;
 mod serialization;
+mod thread_pool;
 
+use std::marker::PhantomData;
 use std::sync::Arc;
 
 pub use crate::error::*;
@@ -19,8 +22,11 @@ pub use crate::keys::*;
 pub use crate::metadata::*;
 pub use run::*;
 pub use runtime::*;
+pub use seal_program::*;
 pub use serialization::WithContext;
+pub use thread_pool::{set_thread_pool_size, thread_pool_size};
 
+use bincode::Options;
 use seal_fhe::{Ciphertext as SealCiphertext, Plaintext as SealPlaintext};
 use serde::{Deserialize, Serialize};
 use sunscreen_zkp_backend::BigInt;
@@ -147,6 +153,63 @@ impl Plaintext {
     pub fn inner_as_seal_plaintext(&self) -> Result<&[WithContext<SealPlaintext>]> {
         self.inner.as_seal_plaintext()
     }
+
+    /**
+     * Creates a plaintext holding the given polynomial coefficients,
+     * lowest degree first, under `params`.
+     *
+     * # Remarks
+     * This bypasses every type's encoding, which is useful for testing
+     * how a type decodes arbitrary (e.g. malformed) plaintexts or for
+     * interop with other encoders. Coefficients past the end of
+     * `coeffs` are zero. The resulting [`data_type`](Self::data_type)
+     * names no Sunscreen type; set it yourself if the plaintext will be
+     * checked against one.
+     *
+     * Returns [`Error::TooManyCoefficients`] if `coeffs` is longer than
+     * the lattice dimension, and [`Error::CoefficientOutOfRange`] if any
+     * coefficient isn't less than the plaintext modulus.
+     */
+    pub fn from_coefficients(coeffs: &[u64], params: &Params) -> Result<Self> {
+        let n = params.lattice_dimension as usize;
+
+        if coeffs.len() > n {
+            return Err(Error::TooManyCoefficients {
+                count: coeffs.len(),
+                lattice_dimension: params.lattice_dimension,
+            });
+        }
+
+        if let Some(index) = coeffs.iter().position(|c| *c >= params.plain_modulus) {
+            return Err(Error::CoefficientOutOfRange {
+                index,
+                plain_modulus: params.plain_modulus,
+            });
+        }
+
+        let mut seal_plaintext = SealPlaintext::new()?;
+        seal_plaintext
+            .try_resize(n)
+            .map_err(|e| Error::plaintext_resize_failed(n, e))?;
+
+        for (i, c) in coeffs.iter().enumerate() {
+            seal_plaintext
+                .try_set_coefficient(i, *c)
+                .map_err(|e| Error::plaintext_set_coefficient_failed(i, e))?;
+        }
+
+        Ok(Self {
+            data_type: Type {
+                name: "sunscreen_runtime::RawCoefficients".to_owned(),
+                version: Version::new(0, 0, 0),
+                is_encrypted: false,
+            },
+            inner: InnerPlaintext::Seal(vec![WithContext {
+                params: params.clone(),
+                data: seal_plaintext,
+            }]),
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -178,6 +241,135 @@ pub struct Ciphertext {
     pub inner: InnerCiphertext,
 }
 
+impl Ciphertext {
+    /**
+     * Serialize this ciphertext into bytes.
+     *
+     * # Remarks
+     * Each underlying SEAL ciphertext is written with SEAL's native
+     * serialization alongside the [`Params`] it was encrypted under, so
+     * [`from_bytes`](Ciphertext::from_bytes) can tell which parameter set
+     * the bytes belong to.
+     *
+     * This function internally uses bincode for serialization.
+     */
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(&self)?)
+    }
+
+    /**
+     * Deserialize a ciphertext from bytes produced by
+     * [`to_bytes`](Ciphertext::to_bytes).
+     *
+     * # Remarks
+     * Returns [`Error::ParameterMismatch`] if the ciphertext was encrypted
+     * under parameters other than `params`, rather than deferring the
+     * failure to a later [`run`](crate::GenericRuntime::run) or
+     * [`decrypt`](crate::GenericRuntime::decrypt).
+     *
+     * Length prefixes in `data` can't claim more bytes than `data`
+     * holds, so untrusted input can't trigger oversized allocations.
+     */
+    pub fn from_bytes(data: &[u8], params: &Params) -> Result<Self> {
+        // Matches bincode::serialize, but never reads more than the input's length.
+        let ciphertext: Self = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(data.len() as u64)
+            .deserialize(data)?;
+
+        match &ciphertext.inner {
+            InnerCiphertext::Seal(c) => {
+                if c.iter().any(|c| &c.params != params) {
+                    return Err(Error::ParameterMismatch);
+                }
+            }
+        }
+
+        Ok(ciphertext)
+    }
+}
+
+/**
+ * A [`Ciphertext`] known at compile time to encrypt a `T`.
+ *
+ * # Remarks
+ * [`Runtime::decrypt_typed`](crate::GenericRuntime::decrypt_typed)
+ * infers its result type from `T`, so decrypting as the wrong type is a
+ * compile error rather than a runtime
+ * [`Error::TypeMismatch`]. Use
+ * [`Runtime::encrypt_typed`](crate::GenericRuntime::encrypt_typed) to
+ * create one, or [`TryFrom<Ciphertext>`] to check and tag an untyped
+ * ciphertext (e.g. an FHE program's output).
+ *
+ * Converting back into a [`Ciphertext`] or [`FheProgramInput`] erases the
+ * type; the runtime still checks the stored [`Type`] in that case.
+ */
+pub struct TypedCiphertext<T> {
+    inner: Ciphertext,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Clone for TypedCiphertext<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> TypedCiphertext<T> {
+    /**
+     * Returns the underlying untyped ciphertext.
+     */
+    pub fn as_untyped(&self) -> &Ciphertext {
+        &self.inner
+    }
+
+    /**
+     * Consumes this wrapper and returns the underlying untyped ciphertext.
+     */
+    pub fn into_untyped(self) -> Ciphertext {
+        self.inner
+    }
+}
+
+impl<T> TryFrom<Ciphertext> for TypedCiphertext<T>
+where
+    T: TypeName,
+{
+    type Error = Error;
+
+    fn try_from(val: Ciphertext) -> Result<Self> {
+        let expected_type = Type {
+            is_encrypted: true,
+            ..T::type_name()
+        };
+
+        if expected_type != val.data_type {
+            return Err(Error::type_mismatch(&expected_type, &val.data_type));
+        }
+
+        Ok(Self {
+            inner: val,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<T> From<TypedCiphertext<T>> for Ciphertext {
+    fn from(val: TypedCiphertext<T>) -> Self {
+        val.inner
+    }
+}
+
+impl<T> From<TypedCiphertext<T>> for FheProgramInput {
+    fn from(val: TypedCiphertext<T>) -> Self {
+        Self::Ciphertext(val.inner)
+    }
+}
+
 /**
  * A trait that denotes this type can be used as an
  * argument to an FHE program.
@@ -222,6 +414,12 @@ where
     }
 }
 
+impl TypeNameInstance for Ciphertext {
+    fn type_name_instance(&self) -> Type {
+        self.data_type.clone()
+    }
+}
+
 impl TypeNameInstance for FheProgramInput {
     fn type_name_instance(&self) -> Type {
         match self {
@@ -254,6 +452,41 @@ pub trait TryIntoPlaintext {
      * Attempts to turn this type into a [`Plaintext`].
      */
     fn try_into_plaintext(&self, params: &Params) -> Result<Plaintext>;
+
+    /**
+     * The smallest plaintext modulus under which this type's encoding is
+     * meaningful for the given params.
+     *
+     * # Remarks
+     * Encodings that represent negative digits as `plain_modulus - digit`
+     * need at least 3 to distinguish `-1` from `1`. Defaults to 2.
+     */
+    fn min_plain_modulus(_params: &Params) -> u64
+    where
+        Self: Sized,
+    {
+        2
+    }
+
+    /**
+     * Returns [`Error::PlainModulusTooSmall`] if `params` has a plaintext
+     * modulus below [`min_plain_modulus`](TryIntoPlaintext::min_plain_modulus).
+     */
+    fn check_plain_modulus(params: &Params) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let required = Self::min_plain_modulus(params);
+
+        if params.plain_modulus < required {
+            return Err(Error::PlainModulusTooSmall {
+                required,
+                actual: params.plain_modulus,
+            });
+        }
+
+        Ok(())
+    }
 }
 
 /**
@@ -291,6 +524,18 @@ where
     fn try_from_plaintext(plaintext: &Plaintext, params: &Params) -> Result<Self>;
 }
 
+/**
+ * A [`TryFromPlaintext`] type that can tell when a decrypted plaintext
+ * holds a value outside the type's range.
+ */
+pub trait TryFromPlaintextChecked: TryFromPlaintext {
+    /**
+     * Like [`TryFromPlaintext::try_from_plaintext`], but returns
+     * [`Error::Overflow`] rather than silently wrapping the value.
+     */
+    fn try_from_plaintext_checked(plaintext: &Plaintext, params: &Params) -> Result<Self>;
+}
+
 /**
  * Declare how many ciphertexts an FheType decomposes into. The runtime needs this
  * to correctly bundle return values from an Fhe Program.
@@ -127,7 +127,22 @@ impl Params {
         let (security_level, rest) = Self::read_i32(rest)?;
         let security_level: SecurityLevel = security_level.try_into()?;
 
-        let coeff_modulus: Vec<u64> = rlp::decode_list(rest);
+        let list = rlp::Rlp::new(rest);
+
+        // Decoding a list silently drops a truncated final item, so check
+        // the list spans exactly the remaining bytes first.
+        let list_len = list
+            .payload_info()
+            .map(|p| p.header_len + p.value_len)
+            .map_err(|_| Error::ParamDeserializationError)?;
+
+        if list_len != rest.len() {
+            return Err(Error::ParamDeserializationError);
+        }
+
+        let coeff_modulus: Vec<u64> = list
+            .as_list()
+            .map_err(|_| Error::ParamDeserializationError)?;
 
         Ok(Self {
             lattice_dimension,
@@ -138,7 +153,31 @@ impl Params {
         })
     }
 
+    /**
+     * Checks that every prime in `coeff_modulus` supports SEAL's number
+     * theoretic transform at this lattice dimension, i.e. is congruent to
+     * 1 modulo `2 * lattice_dimension`.
+     *
+     * # Remarks
+     * Returns [`Error::InvalidCoeffModulus`] with the first offending prime.
+     * Parameters chosen by the compiler always pass; this catches
+     * hand-written parameters before SEAL rejects them with a less
+     * helpful error.
+     */
+    pub fn validate(&self) -> Result<()> {
+        let m = 2 * self.lattice_dimension;
+
+        match self.coeff_modulus.iter().find(|q| m == 0 || **q % m != 1) {
+            Some(prime) => Err(Error::InvalidCoeffModulus { prime: *prime }),
+            None => Ok(()),
+        }
+    }
+
     fn read_u64(bytes: &[u8]) -> Result<(u64, &[u8])> {
+        if bytes.len() < std::mem::size_of::<u64>() {
+            return Err(Error::ParamDeserializationError);
+        }
+
         let (int_bytes, rest) = bytes.split_at(std::mem::size_of::<u64>());
         let val = u64::from_be_bytes(
             int_bytes
@@ -150,6 +189,10 @@ impl Params {
     }
 
     fn read_i32(bytes: &[u8]) -> Result<(i32, &[u8])> {
+        if bytes.len() < std::mem::size_of::<i32>() {
+            return Err(Error::ParamDeserializationError);
+        }
+
         let (int_bytes, rest) = bytes.split_at(std::mem::size_of::<i32>());
         let val = i32::from_be_bytes(
             int_bytes
@@ -161,6 +204,10 @@ impl Params {
     }
 
     fn read_u8(bytes: &[u8]) -> Result<(u8, &[u8])> {
+        if bytes.len() < std::mem::size_of::<u8>() {
+            return Err(Error::ParamDeserializationError);
+        }
+
         let (int_bytes, rest) = bytes.split_at(std::mem::size_of::<u8>());
         let val = u8::from_be_bytes(
             int_bytes
@@ -191,6 +238,43 @@ pub struct FheProgramMetadata {
      * The set of keys required to run the FHE program.
      */
     pub required_keys: Vec<RequiredKeys>,
+
+    /**
+     * The positions of the FHE program's inputs that no output depends
+     * on, in ascending order.
+     *
+     * # Remarks
+     * Each argument contributes one input per ciphertext (or plaintext)
+     * it encodes to. The runtime still expects a value for every
+     * argument, but any value of the right type works for these, so
+     * callers needn't encrypt real data for them.
+     */
+    pub unused_inputs: Vec<usize>,
+}
+
+impl FheProgramMetadata {
+    /**
+     * Serialize this metadata into bytes.
+     *
+     * # Remarks
+     * The metadata alone suffices for a client to construct a
+     * [`Runtime`](crate::Runtime) and encrypt arguments matching the
+     * FHE program's call signature, so servers can ship it without the
+     * (much larger) [`FheProgram`] graph.
+     *
+     * This function internally uses bincode for serialization.
+     */
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(&self)?)
+    }
+
+    /**
+     * Deserialize metadata from bytes produced by
+     * [`to_bytes`](FheProgramMetadata::to_bytes).
+     */
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(data)?)
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -229,6 +313,76 @@ mod tests {
         assert_eq!(params, params_2);
     }
 
+    #[test]
+    fn can_roundtrip_external_params() {
+        // A modulus chain SEAL wouldn't pick by default.
+        let params = Params {
+            lattice_dimension: 4096,
+            plain_modulus: 65537,
+            coeff_modulus: vec![0x800004001, 0x800008001, 0x1000002001],
+            security_level: SecurityLevel::TC128,
+            scheme_type: SchemeType::Bfv,
+        };
+
+        let bytes = params.to_bytes();
+
+        assert_eq!(Params::try_from_bytes(&bytes).unwrap(), params);
+
+        for len in 0..bytes.len() {
+            assert!(Params::try_from_bytes(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_ntt_incompatible_coeff_modulus() {
+        let mut params = Params {
+            lattice_dimension: 4096,
+            plain_modulus: 64,
+            // 68719403009 = 1 mod 8192
+            coeff_modulus: vec![68719403009],
+            security_level: SecurityLevel::TC128,
+            scheme_type: SchemeType::Bfv,
+        };
+
+        assert!(params.validate().is_ok());
+
+        // Prime, but 1 mod 4096 rather than 1 mod 8192.
+        params.coeff_modulus.push(12289);
+
+        assert_eq!(
+            params.validate(),
+            Err(Error::InvalidCoeffModulus { prime: 12289 })
+        );
+    }
+
+    #[test]
+    fn can_roundtrip_metadata() {
+        let metadata = FheProgramMetadata {
+            params: Params {
+                lattice_dimension: 4096,
+                plain_modulus: 64,
+                coeff_modulus: vec![1, 2, 3, 4],
+                security_level: SecurityLevel::TC128,
+                scheme_type: SchemeType::Bfv,
+            },
+            signature: CallSignature {
+                arguments: vec![Type {
+                    name: "foo::Bar".to_owned(),
+                    version: Version::new(1, 2, 3),
+                    is_encrypted: true,
+                }],
+                returns: vec![],
+                num_ciphertexts: vec![],
+            },
+            required_keys: vec![RequiredKeys::Relin],
+            unused_inputs: vec![0],
+        };
+
+        let metadata_2 = FheProgramMetadata::from_bytes(&metadata.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(metadata, metadata_2);
+    }
+
     #[test]
     fn can_serialize_deserialize_typename() {
         let typename = Type {
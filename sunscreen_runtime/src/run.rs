@@ -7,11 +7,11 @@ use crossbeam::atomic::AtomicCell;
 use petgraph::{stable_graph::NodeIndex, Direction};
 
 use std::borrow::Cow;
-#[cfg(target_arch = "wasm32")]
+use std::collections::HashMap;
 use std::collections::VecDeque;
 #[cfg(not(target_arch = "wasm32"))]
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use seal_fhe::{
     Ciphertext, Error as SealError, Evaluator, GaloisKeys, Plaintext, RelinearizationKeys,
@@ -60,6 +60,13 @@ pub enum FheProgramRunFailure {
     #[error("Malformed plaintext")]
     MalformedPlaintext,
 
+    /**
+     * The FHE program's graph is malformed (e.g. it contains a cycle or
+     * an operand of the wrong kind).
+     */
+    #[error("The FHE program is malformed")]
+    MalformedProgram,
+
     /**
      * Internal error: no data found for a parent node.
      */
@@ -104,6 +111,225 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
     relin_keys: &Option<&RelinearizationKeys>,
     galois_keys: &Option<&GaloisKeys>,
 ) -> Result<Vec<Ciphertext>, FheProgramRunFailure> {
+    run_program_unchecked_with_context(
+        ir,
+        inputs,
+        evaluator,
+        relin_keys,
+        galois_keys,
+        &RunContext::new(),
+    )
+}
+
+/**
+ * Buffers for intermediate ciphertexts, recycled across FHE program runs.
+ *
+ * # Remarks
+ * Every operation in an FHE program produces a new ciphertext. Rather than
+ * allocating these afresh on every run, pass the same [`RunContext`] to
+ * [`Runtime::run_with_context`](crate::Runtime::run_with_context) (or
+ * [`run_program_unchecked_with_context`]). When a run finishes, its
+ * intermediate ciphertexts go back into the context and subsequent runs
+ * write their results into them, reusing their memory when it's large
+ * enough. The context also caches each plaintext literal the first time
+ * a run decodes it (see [`cached_literals`](Self::cached_literals)).
+ *
+ * A [`RunContext`] may be shared between threads, but only makes sense
+ * to reuse for programs compiled with the same parameters.
+ */
+#[derive(Default)]
+pub struct RunContext {
+    pool: Mutex<Vec<Ciphertext>>,
+    literals: Arc<LiteralCache>,
+    sequential: bool,
+}
+
+impl RunContext {
+    /**
+     * Creates an empty [`RunContext`].
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Creates an empty [`RunContext`] that shares decoded literals with
+     * `literals`.
+     */
+    pub(crate) fn with_literal_cache(literals: Arc<LiteralCache>) -> Self {
+        Self {
+            pool: Mutex::default(),
+            literals,
+            sequential: false,
+        }
+    }
+
+    /**
+     * Makes runs using this context evaluate one operation at a time, on
+     * the calling thread, in a fixed order.
+     *
+     * # Remarks
+     * By default, runs evaluate every operation as soon as its operands
+     * are ready, so independent branches of a program proceed in parallel
+     * on Sunscreen's thread pool. Results are the same either way, but a
+     * sequential run is reproducible step for step, which helps when
+     * debugging or profiling a program.
+     */
+    pub fn sequential(mut self) -> Self {
+        self.sequential = true;
+        self
+    }
+
+    /**
+     * The number of ciphertext buffers currently available for reuse.
+     */
+    pub fn pooled_ciphertexts(&self) -> usize {
+        self.pool.lock().unwrap().len()
+    }
+
+    /**
+     * The number of distinct plaintext literals decoded so far.
+     *
+     * # Remarks
+     * FHE programs embed plaintext literals in serialized form. The first
+     * run to reach each literal decodes it, and later runs sharing this
+     * context reuse the decoded plaintext.
+     */
+    pub fn cached_literals(&self) -> usize {
+        self.literals.plaintexts.lock().unwrap().len()
+    }
+
+    fn take(&self) -> Result<Ciphertext, FheProgramRunFailure> {
+        match self.pool.lock().unwrap().pop() {
+            Some(c) => Ok(c),
+            None => Ok(Ciphertext::new()?),
+        }
+    }
+
+    fn recycle(&self, c: Ciphertext) {
+        self.pool.lock().unwrap().push(c);
+    }
+}
+
+/**
+ * The most decoded plaintext literals a [`LiteralCache`] holds by default.
+ */
+const MAX_CACHED_LITERALS: usize = 1024;
+
+/**
+ * Decoded plaintext literals, keyed by their serialized form.
+ *
+ * # Remarks
+ * A runtime shares one cache between every program it runs, so the cache
+ * holds at most `capacity` literals. Once full, further literals get
+ * decoded on every run rather than evicting those already cached.
+ */
+pub(crate) struct LiteralCache {
+    plaintexts: Mutex<HashMap<Vec<u8>, Arc<SealData>>>,
+    capacity: usize,
+}
+
+impl Default for LiteralCache {
+    fn default() -> Self {
+        Self::with_capacity(MAX_CACHED_LITERALS)
+    }
+}
+
+impl LiteralCache {
+    /**
+     * Creates an empty cache holding at most `capacity` literals.
+     */
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            plaintexts: Mutex::default(),
+            capacity,
+        }
+    }
+
+    fn get_or_decode(&self, bytes: &[u8]) -> Result<Arc<SealData>, FheProgramRunFailure> {
+        if let Some(p) = self.plaintexts.lock().unwrap().get(bytes) {
+            return Ok(p.clone());
+        }
+
+        // Decode without holding the lock; if another thread races us to
+        // the same literal, keep whichever finishes first.
+        let p = InnerPlaintext::from_bytes(bytes)
+            .map_err(|_| FheProgramRunFailure::MalformedPlaintext)?;
+
+        let p = match p {
+            InnerPlaintext::Seal(p) => {
+                // Plaintext literals should always have exactly one plaintext.
+                if p.len() != 1 {
+                    return Err(FheProgramRunFailure::MalformedPlaintext);
+                }
+
+                Arc::new(SealData::from(p[0].data.clone()))
+            }
+        };
+
+        let mut plaintexts = self.plaintexts.lock().unwrap();
+
+        if plaintexts.len() >= self.capacity && !plaintexts.contains_key(bytes) {
+            return Ok(p);
+        }
+
+        Ok(plaintexts.entry(bytes.to_owned()).or_insert(p).clone())
+    }
+}
+
+/**
+ * Identical to [`run_program_unchecked`], except intermediate ciphertexts
+ * are drawn from and returned to `run_context`.
+ *
+ * # Safety
+ * Calling this method on a malformed [`FheProgram`] may
+ * result in panics, non-termination, or undefined behavior.
+ */
+pub unsafe fn run_program_unchecked_with_context<E: Evaluator + Sync + Send>(
+    ir: &FheProgram,
+    inputs: &[SealData],
+    evaluator: &E,
+    relin_keys: &Option<&RelinearizationKeys>,
+    galois_keys: &Option<&GaloisKeys>,
+    run_context: &RunContext,
+) -> Result<Vec<Ciphertext>, FheProgramRunFailure> {
+    run_program_unchecked_streaming(
+        ir,
+        inputs,
+        evaluator,
+        relin_keys,
+        galois_keys,
+        run_context,
+        |_, _| {},
+    )
+}
+
+/**
+ * Identical to [`run_program_unchecked_with_context`], except `on_output`
+ * is called with each output's index and ciphertext as soon as it's
+ * computed, while other nodes may still be running.
+ *
+ * # Remarks
+ * Outputs complete in whatever order the scheduler finishes them, so
+ * `on_output` may be called from several threads and out of order.
+ *
+ * # Safety
+ * Calling this method on a malformed [`FheProgram`] may
+ * result in panics, non-termination, or undefined behavior.
+ */
+pub unsafe fn run_program_unchecked_streaming<E, F>(
+    ir: &FheProgram,
+    inputs: &[SealData],
+    evaluator: &E,
+    relin_keys: &Option<&RelinearizationKeys>,
+    galois_keys: &Option<&GaloisKeys>,
+    run_context: &RunContext,
+    on_output: F,
+) -> Result<Vec<Ciphertext>, FheProgramRunFailure>
+where
+    E: Evaluator + Sync + Send,
+    F: Fn(usize, &Ciphertext) + Sync + Send,
+{
     fn get_data(
         data: &[AtomicCell<Option<Arc<SealData>>>],
         index: usize,
@@ -157,188 +383,211 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
         data.push(AtomicCell::new(None));
     }
 
-    traverse(
-        ir,
-        |index| {
-            let node = &ir.graph[index];
-            let query = GraphQuery::new(&ir.graph.0);
+    // Outputs are numbered in node index order.
+    let mut output_ids = vec![None; ir.graph.node_bound()];
 
-            match &node.operation {
-                InputCiphertext(id) => {
-                    data[index.index()].store(Some(inputs[*id].clone()));
-                }
-                InputPlaintext(id) => {
-                    data[index.index()].store(Some(inputs[*id].clone()));
-                }
-                ShiftLeft => {
-                    let (left, right) = query.get_binary_operands(index)?;
-
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = match ir.graph[right].operation {
-                        Literal(Literal::U64(v)) => v as i32,
-                        _ => panic!(
-                            "Illegal right operand for ShiftLeft: {:#?}",
-                            ir.graph[right].operation
-                        ),
-                    };
-
-                    let c = evaluator.rotate_rows(
-                        a,
-                        b,
-                        galois_keys
-                            .as_ref()
-                            .ok_or(FheProgramRunFailure::MissingGaloisKeys)?,
-                    )?;
-
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                ShiftRight => {
-                    let (left, right) = query.get_binary_operands(index)?;
-
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = match ir.graph[right].operation {
-                        Literal(Literal::U64(v)) => v as i32,
-                        _ => panic!(
-                            "Illegal right operand for ShiftLeft: {:#?}",
-                            ir.graph[right].operation
-                        ),
-                    };
-
-                    let c = evaluator.rotate_rows(
-                        a,
-                        -b,
-                        galois_keys
-                            .as_ref()
-                            .ok_or(FheProgramRunFailure::MissingGaloisKeys)?,
-                    )?;
-
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                Add => {
-                    let (left, right) = query.get_binary_operands(index)?;
+    for (i, id) in ir
+        .graph
+        .node_indices()
+        .filter(|id| matches!(ir.graph[*id].operation, OutputCiphertext))
+        .enumerate()
+    {
+        output_ids[id.index()] = Some(i);
+    }
 
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = get_ciphertext(&data, right.index())?;
+    let callback = |index: NodeIndex| -> Result<(), FheProgramRunFailure> {
+        let node = &ir.graph[index];
+        let query = GraphQuery::new(&ir.graph.0);
 
-                    let c = evaluator.add(a, b)?;
+        match &node.operation {
+            InputCiphertext(id) => {
+                data[index.index()].store(Some(inputs[*id].clone()));
+            }
+            InputPlaintext(id) => {
+                data[index.index()].store(Some(inputs[*id].clone()));
+            }
+            ShiftLeft => {
+                let (left, right) = query.get_binary_operands(index)?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
+                let a = get_ciphertext(&data, left.index())?;
+                let b = match &ir.graph[right].operation {
+                    Literal(x) => x.as_rotation_amount(),
+                    _ => None,
                 }
-                AddPlaintext => {
-                    let (left, right) = query.get_binary_operands(index)?;
+                .ok_or(FheProgramRunFailure::MalformedProgram)?;
 
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = get_plaintext(&data, right.index())?;
+                let mut c = run_context.take()?;
 
-                    let c = evaluator.add_plain(a, b)?;
+                evaluator.rotate_rows_into(
+                    a,
+                    b,
+                    galois_keys
+                        .as_ref()
+                        .ok_or(FheProgramRunFailure::MissingGaloisKeys)?,
+                    &mut c,
+                )?;
+
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            ShiftRight => {
+                let (left, right) = query.get_binary_operands(index)?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
+                let a = get_ciphertext(&data, left.index())?;
+                let b = match &ir.graph[right].operation {
+                    Literal(x) => x.as_rotation_amount(),
+                    _ => None,
                 }
-                Multiply => {
-                    let (left, right) = query.get_binary_operands(index)?;
+                .ok_or(FheProgramRunFailure::MalformedProgram)?;
 
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = get_ciphertext(&data, right.index())?;
+                let mut c = run_context.take()?;
 
-                    let c = evaluator.multiply(a, b)?;
+                evaluator.rotate_rows_into(
+                    a,
+                    -b,
+                    galois_keys
+                        .as_ref()
+                        .ok_or(FheProgramRunFailure::MissingGaloisKeys)?,
+                    &mut c,
+                )?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                MultiplyPlaintext => {
-                    let (left, right) = query.get_binary_operands(index)?;
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            Add => {
+                let (left, right) = query.get_binary_operands(index)?;
 
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = get_plaintext(&data, right.index())?;
+                let a = get_ciphertext(&data, left.index())?;
+                let b = get_ciphertext(&data, right.index())?;
+                let (a, b) = align_levels(evaluator, a, b)?;
 
-                    let c = evaluator.multiply_plain(a, b)?;
+                let mut c = run_context.take()?;
+                evaluator.add_into(&a, &b, &mut c)?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                SwapRows => {
-                    let galois_keys = galois_keys
-                        .as_ref()
-                        .ok_or(FheProgramRunFailure::MissingGaloisKeys)?;
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            AddPlaintext => {
+                let (left, right) = query.get_binary_operands(index)?;
+
+                let a = get_ciphertext(&data, left.index())?;
+                let b = get_plaintext(&data, right.index())?;
 
-                    let input = query.get_unary_operand(index)?;
+                let mut c = run_context.take()?;
+                evaluator.add_plain_into(a, b, &mut c)?;
 
-                    let x = get_ciphertext(&data, input.index())?;
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            Multiply => {
+                let (left, right) = query.get_binary_operands(index)?;
 
-                    let y = evaluator.rotate_columns(x, galois_keys)?;
+                let a = get_ciphertext(&data, left.index())?;
+                let b = get_ciphertext(&data, right.index())?;
+                let (a, b) = align_levels(evaluator, a, b)?;
 
-                    data[index.index()].store(Some(Arc::new(y.into())));
-                }
-                Relinearize => {
-                    let relin_keys = relin_keys
-                        .as_ref()
-                        .ok_or(FheProgramRunFailure::MissingRelinearizationKeys)?;
+                let mut c = run_context.take()?;
+                evaluator.multiply_into(&a, &b, &mut c)?;
 
-                    let input = query.get_unary_operand(index)?;
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            MultiplyPlaintext => {
+                let (left, right) = query.get_binary_operands(index)?;
 
-                    let a = get_ciphertext(&data, input.index())?;
+                let a = get_ciphertext(&data, left.index())?;
+                let b = get_plaintext(&data, right.index())?;
 
-                    let c = evaluator.relinearize(a, relin_keys)?;
+                let mut c = run_context.take()?;
+                evaluator.multiply_plain_into(a, b, &mut c)?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                Negate => {
-                    let x_id = query.get_unary_operand(index)?;
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            SwapRows => {
+                let galois_keys = galois_keys
+                    .as_ref()
+                    .ok_or(FheProgramRunFailure::MissingGaloisKeys)?;
 
-                    let x = get_ciphertext(&data, x_id.index())?;
+                let input = query.get_unary_operand(index)?;
 
-                    let y = evaluator.negate(x)?;
+                let x = get_ciphertext(&data, input.index())?;
 
-                    data[index.index()].store(Some(Arc::new(y.into())));
-                }
-                Sub => {
-                    let (left, right) = query.get_binary_operands(index)?;
+                let mut y = run_context.take()?;
+                evaluator.rotate_columns_into(x, galois_keys, &mut y)?;
 
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = get_ciphertext(&data, right.index())?;
+                data[index.index()].store(Some(Arc::new(y.into())));
+            }
+            Relinearize => {
+                let relin_keys = relin_keys
+                    .as_ref()
+                    .ok_or(FheProgramRunFailure::MissingRelinearizationKeys)?;
 
-                    let c = evaluator.sub(a, b)?;
+                let input = query.get_unary_operand(index)?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                SubPlaintext => {
-                    let (left, right) = query.get_binary_operands(index)?;
+                let a = get_ciphertext(&data, input.index())?;
 
-                    let a = get_ciphertext(&data, left.index())?;
-                    let b = get_plaintext(&data, right.index())?;
+                let mut c = run_context.take()?;
+                evaluator.relinearize_into(a, relin_keys, &mut c)?;
 
-                    let c = evaluator.sub_plain(a, b)?;
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            Negate => {
+                let x_id = query.get_unary_operand(index)?;
 
-                    data[index.index()].store(Some(Arc::new(c.into())));
-                }
-                Literal(x) => {
-                    if let Literal::Plaintext(p) = x {
-                        let p = InnerPlaintext::from_bytes(p)
-                            .map_err(|_| FheProgramRunFailure::MalformedPlaintext)?;
-
-                        match p {
-                            InnerPlaintext::Seal(p) => {
-                                // Plaintext literals should always have exactly one plaintext.
-                                if p.len() != 1 {
-                                    return Err(FheProgramRunFailure::MalformedPlaintext);
-                                }
-
-                                data[index.index()].store(Some(Arc::new(p[0].data.clone().into())))
-                            }
-                        };
-                    }
+                let x = get_ciphertext(&data, x_id.index())?;
+
+                let mut y = run_context.take()?;
+                evaluator.negate_into(x, &mut y)?;
+
+                data[index.index()].store(Some(Arc::new(y.into())));
+            }
+            Sub => {
+                let (left, right) = query.get_binary_operands(index)?;
+
+                let a = get_ciphertext(&data, left.index())?;
+                let b = get_ciphertext(&data, right.index())?;
+                let (a, b) = align_levels(evaluator, a, b)?;
+
+                let mut c = run_context.take()?;
+                evaluator.sub_into(&a, &b, &mut c)?;
+
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            SubPlaintext => {
+                let (left, right) = query.get_binary_operands(index)?;
+
+                let a = get_ciphertext(&data, left.index())?;
+                let b = get_plaintext(&data, right.index())?;
+
+                let mut c = run_context.take()?;
+                evaluator.sub_plain_into(a, b, &mut c)?;
+
+                data[index.index()].store(Some(Arc::new(c.into())));
+            }
+            Literal(x) => {
+                if let Literal::Plaintext(p) = x {
+                    let p = run_context.literals.get_or_decode(p)?;
+
+                    data[index.index()].store(Some(p));
                 }
-                OutputCiphertext => {
-                    let input = query.get_unary_operand(index)?;
+            }
+            OutputCiphertext => {
+                let input = query.get_unary_operand(index)?;
+
+                let a = get_data(&data, input.index())?;
 
-                    let a = get_data(&data, input.index())?;
+                data[index.index()].store(Some(a.clone()));
 
-                    data[index.index()].store(Some(a.clone()));
+                match (output_ids[index.index()], a.as_ref()) {
+                    (Some(i), SealData::Ciphertext(c)) => on_output(i, c),
+                    _ => return Err(FheProgramRunFailure::ExpectedCiphertext),
                 }
-            };
+            }
+        };
 
-            Ok(())
-        },
-        None,
-    )?;
+        Ok(())
+    };
+
+    if run_context.sequential {
+        traverse_sequential(ir, callback, None)?;
+    } else {
+        traverse(ir, callback, None)?;
+    }
 
     // Attempt to copy ciphertexts to our output vector.
     let output = ir
@@ -353,9 +602,73 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
         .map(|c| c.to_owned())
         .collect();
 
+    // Hand every ciphertext we took from the pool back to it. Input nodes
+    // hold clones of the caller's ciphertexts and outputs share their
+    // operand's, so drop those references first; recycling them would grow
+    // the pool on every run.
+    drop(inputs);
+
+    let taken = data
+        .drain(0..)
+        .enumerate()
+        .filter(|(i, _)| {
+            !matches!(
+                ir.graph
+                    .node_weight(NodeIndex::new(*i))
+                    .map(|n| &n.operation),
+                None | Some(InputCiphertext(_) | InputPlaintext(_) | OutputCiphertext)
+            )
+        })
+        .filter_map(|(_, d)| d.into_inner())
+        .collect::<Vec<_>>();
+
+    for d in taken {
+        if let Ok(SealData::Ciphertext(c)) = Arc::try_unwrap(d) {
+            run_context.recycle(c);
+        }
+    }
+
     Ok(output)
 }
 
+/**
+ * Brings two ciphertexts to the same level in the modulus switching chain.
+ *
+ * # Remarks
+ * SEAL refuses to combine ciphertexts encrypted under different coefficient
+ * moduli. When `a` and `b` sit at different levels (e.g. one was loaded after
+ * being modulus switched), this repeatedly switches the higher-level operand down
+ * until both have the same number of primes. Operands already at the same level
+ * are returned borrowed without copying.
+ */
+pub fn align_levels<'a, E: Evaluator>(
+    evaluator: &E,
+    a: &'a Ciphertext,
+    b: &'a Ciphertext,
+) -> Result<(Cow<'a, Ciphertext>, Cow<'a, Ciphertext>), FheProgramRunFailure> {
+    fn switch_down<'a, E: Evaluator>(
+        evaluator: &E,
+        c: &'a Ciphertext,
+        target: u64,
+    ) -> Result<Cow<'a, Ciphertext>, FheProgramRunFailure> {
+        let mut c = Cow::Borrowed(c);
+
+        while c.coeff_modulus_size() > target {
+            c = Cow::Owned(evaluator.mod_switch_to_next(&c)?);
+        }
+
+        Ok(c)
+    }
+
+    let a_level = a.coeff_modulus_size();
+    let b_level = b.coeff_modulus_size();
+
+    Ok((
+        switch_down(evaluator, a, b_level)?,
+        switch_down(evaluator, b, a_level)?,
+    ))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 /**
  * Traverses the FheProgram's nodes in topological order, executing
@@ -414,66 +727,73 @@ where
 
     let returned_result = AtomicCell::new(Ok(()));
 
-    rayon::scope(|s| {
-        for node_id in initial_ready {
-            fn run_internal<F>(
-                node_id: NodeIndex,
-                ir: &FheProgram,
-                deps: &[AtomicUsize],
-                returned_result: &AtomicCell<Result<(), FheProgramRunFailure>>,
-                callback: &F,
-            ) where
-                F: Fn(NodeIndex) -> Result<(), FheProgramRunFailure> + Sync + Send,
-            {
-                log::trace!("parallel_traverse: Running node {}", node_id.index());
-
-                if returned_result.load().is_err() {
-                    return;
-                }
+    crate::thread_pool::install(|| {
+        rayon::scope(|s| {
+            for node_id in initial_ready {
+                fn run_internal<F>(
+                    node_id: NodeIndex,
+                    ir: &FheProgram,
+                    deps: &[AtomicUsize],
+                    returned_result: &AtomicCell<Result<(), FheProgramRunFailure>>,
+                    callback: &F,
+                ) where
+                    F: Fn(NodeIndex) -> Result<(), FheProgramRunFailure> + Sync + Send,
+                {
+                    log::trace!("parallel_traverse: Running node {}", node_id.index());
+
+                    if returned_result.load().is_err() {
+                        return;
+                    }
 
-                let result = callback(node_id);
+                    let result = callback(node_id);
 
-                if result.is_err() {
-                    returned_result.store(result);
-                    return;
-                }
+                    if result.is_err() {
+                        returned_result.store(result);
+                        return;
+                    }
 
-                rayon::scope(|s| {
-                    // Check each child's dependency count and mark it as ready if 0.
-                    for e in ir.graph.neighbors_directed(node_id, Direction::Outgoing) {
-                        let old_val = deps[e.index()].fetch_sub(1, Ordering::Relaxed);
-
-                        // Note is the value prior to atomic subtraction.
-                        if old_val == 1 {
-                            s.spawn(move |_| {
-                                log::trace!("Node {} ready", e.index());
-                                run_internal(e, ir, deps, returned_result, callback);
-                            });
+                    rayon::scope(|s| {
+                        // Check each child's dependency count and mark it as ready if 0.
+                        for e in ir.graph.neighbors_directed(node_id, Direction::Outgoing) {
+                            let old_val = deps[e.index()].fetch_sub(1, Ordering::Relaxed);
+
+                            // Note is the value prior to atomic subtraction.
+                            if old_val == 1 {
+                                s.spawn(move |_| {
+                                    log::trace!("Node {} ready", e.index());
+                                    run_internal(e, ir, deps, returned_result, callback);
+                                });
+                            }
                         }
-                    }
+                    });
+                }
+
+                let deps = &deps;
+                let returned_result = &returned_result;
+                let callback = &callback;
+
+                s.spawn(move |_| {
+                    run_internal(
+                        NodeIndex::from(node_id as u32),
+                        ir,
+                        deps,
+                        returned_result,
+                        callback,
+                    );
                 });
             }
-
-            let deps = &deps;
-            let returned_result = &returned_result;
-            let callback = &callback;
-
-            s.spawn(move |_| {
-                run_internal(
-                    NodeIndex::from(node_id as u32),
-                    ir,
-                    deps,
-                    returned_result,
-                    callback,
-                );
-            });
-        }
+        });
     });
 
     returned_result.load()
 }
 
 #[cfg(target_arch = "wasm32")]
+/**
+ * Traverses the FheProgram's nodes in topological order, executing
+ * callback on each node. WebAssembly has no threads, so this is
+ * [`traverse_sequential`].
+ */
 pub fn traverse<F>(
     ir: &FheProgram,
     callback: F,
@@ -482,8 +802,33 @@ pub fn traverse<F>(
 where
     F: Fn(NodeIndex) -> Result<(), FheProgramRunFailure> + Sync + Send,
 {
+    traverse_sequential(ir, callback, run_to)
+}
+
+/**
+ * Traverses the FheProgram's nodes in topological order, executing
+ * callback on each node one at a time on the calling thread.
+ *
+ * # Remarks
+ * Nodes run in breadth-first order from the inputs, so repeated
+ * traversals of the same program visit nodes in the same order.
+ *
+ * The optional `run_to` specifies to only run the given node and
+ * its ancestors, topologically. If not specified, every node in the
+ * program gets visited.
+ */
+pub fn traverse_sequential<F>(
+    ir: &FheProgram,
+    callback: F,
+    run_to: Option<NodeIndex>,
+) -> Result<(), FheProgramRunFailure>
+where
+    F: Fn(NodeIndex) -> Result<(), FheProgramRunFailure>,
+{
+    use sunscreen_fhe_program::FheProgramTrait;
+
     let ir = if let Some(x) = run_to {
-        Cow::Owned(ir.prune(&vec![x]))
+        Cow::Owned(ir.prune(&[x]))
     } else {
         Cow::Borrowed(ir)
     };
@@ -495,30 +840,17 @@ where
         .map(|n| ir.graph.neighbors_directed(n, Direction::Incoming).count())
         .collect::<Vec<usize>>();
 
-    let initial_ready = deps.iter().enumerate().filter_map(|(id, count)| {
-        if *count == 0 {
+    let mut ready_nodes = deps
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| {
             log::trace!("traverse: Initial node {}", id);
-            Some(NodeIndex::from(id as u32))
-        } else {
-            None
-        }
-    });
-
-    let mut ready_nodes = VecDeque::new();
-
-    for i in initial_ready {
-        ready_nodes.push_back(i);
-    }
-
-    loop {
-        let node_id = ready_nodes.pop_front();
-        let node_id = match node_id {
-            Some(i) => i,
-            None => {
-                break;
-            }
-        };
+            NodeIndex::from(id as u32)
+        })
+        .collect::<VecDeque<_>>();
 
+    while let Some(node_id) = ready_nodes.pop_front() {
         callback(node_id)?;
 
         for e in ir.graph.neighbors_directed(node_id, Direction::Outgoing) {
@@ -622,6 +954,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pruned_inputs_run_with_fewer_arguments() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let _b = ir.add_input_ciphertext(1);
+        let c = ir.add_input_ciphertext(2);
+        let d = ir.add_sub(a, c);
+        ir.add_output_ciphertext(d);
+
+        let (ir, kept) = ir.prune_inputs();
+
+        assert_eq!(kept, vec![0, 2]);
+        assert_eq!(ir.num_inputs(), 2);
+
+        let degree = 8192;
+
+        let (_keygen, context, _public_key, _private_key, encryptor, decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let args = [42, 7, -24]
+            .iter()
+            .map(|x| {
+                let pt = encoder.encode_signed(&vec![*x; degree as usize]).unwrap();
+
+                SealData::from(encryptor.encrypt(&pt).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        let args = kept.iter().map(|i| args[*i].clone()).collect::<Vec<_>>();
+
+        let output =
+            unsafe { run_program_unchecked(&ir, &args, &evaluator, &None, &None).unwrap() };
+
+        let o_p = decryptor.decrypt(&output[0]).unwrap();
+
+        assert_eq!(
+            encoder.decode_signed(&o_p).unwrap(),
+            vec![42 + 24; degree as usize]
+        );
+    }
+
     #[test]
     fn simple_mul() {
         let mut ir = FheProgram::new(SchemeType::Bfv);
@@ -780,6 +1156,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_add_ciphertexts_at_different_levels() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_add(a, b);
+        ir.add_output_ciphertext(c);
+
+        let degree = 8192;
+
+        let (_keygen, context, _public_key, _private_key, encryptor, decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let a = vec![42; degree as usize];
+        let b = vec![-24; degree as usize];
+
+        let pt_0 = encoder.encode_signed(&a).unwrap();
+        let pt_1 = encoder.encode_signed(&b).unwrap();
+
+        let ct_0 = encryptor.encrypt(&pt_0).unwrap();
+        let ct_1 = evaluator
+            .mod_switch_to_next(&encryptor.encrypt(&pt_1).unwrap())
+            .unwrap();
+
+        assert!(ct_0.coeff_modulus_size() > ct_1.coeff_modulus_size());
+
+        let output = unsafe {
+            run_program_unchecked(&ir, &[ct_0.into(), ct_1.into()], &evaluator, &None, &None)
+                .unwrap()
+        };
+
+        assert_eq!(output.len(), 1);
+
+        let o_p = decryptor.decrypt(&output[0]).unwrap();
+
+        assert_eq!(
+            encoder.decode_signed(&o_p).unwrap(),
+            vec![42 - 24; degree as usize]
+        );
+    }
+
     #[test]
     fn rotate_left() {
         let mut ir = FheProgram::new(SchemeType::Bfv);
@@ -869,4 +1289,286 @@ mod tests {
 
         assert_eq!(encoder.decode_unsigned(&o_p).unwrap(), expected);
     }
+
+    #[test]
+    fn rejects_rotation_amounts_past_i32() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let l = ir.add_input_literal(Literal::U128(1 << 40));
+
+        let res = ir.append_rotate_right(a, l);
+
+        ir.add_output_ciphertext(res);
+
+        let degree = 4096;
+
+        let (keygen, context, _public_key, _private_key, encryptor, _decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+        let galois_keys = keygen.create_galois_keys().unwrap();
+
+        let a: Vec<u64> = (0..degree).collect();
+
+        let ct_0 = encryptor
+            .encrypt(&encoder.encode_unsigned(&a).unwrap())
+            .unwrap();
+
+        let result = unsafe {
+            run_program_unchecked(&ir, &[ct_0.into()], &evaluator, &None, &Some(&galois_keys))
+        };
+
+        assert!(matches!(
+            result,
+            Err(FheProgramRunFailure::MalformedProgram)
+        ));
+    }
+
+    #[test]
+    fn run_context_recycles_intermediates() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_add(a, b);
+        let d = ir.add_negate(c);
+        ir.add_output_ciphertext(d);
+
+        let degree = 8192;
+
+        let (_keygen, context, _public_key, _private_key, encryptor, decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let a = vec![42; degree as usize];
+        let b = vec![-24; degree as usize];
+
+        let pt_0 = encoder.encode_signed(&a).unwrap();
+        let pt_1 = encoder.encode_signed(&b).unwrap();
+
+        let ct_0 = encryptor.encrypt(&pt_0).unwrap();
+        let ct_1 = encryptor.encrypt(&pt_1).unwrap();
+
+        let run_context = RunContext::new();
+
+        for _ in 0..3 {
+            let output = unsafe {
+                run_program_unchecked_with_context(
+                    &ir,
+                    &[ct_0.clone().into(), ct_1.clone().into()],
+                    &evaluator,
+                    &None,
+                    &None,
+                    &run_context,
+                )
+                .unwrap()
+            };
+
+            let o_p = decryptor.decrypt(&output[0]).unwrap();
+
+            assert_eq!(
+                encoder.decode_signed(&o_p).unwrap(),
+                vec![24 - 42; degree as usize]
+            );
+
+            // Every run should reuse the previous run's buffers rather than
+            // growing the pool. Only the add and negate allocate; the
+            // inputs belong to the caller.
+            assert_eq!(run_context.pooled_ciphertexts(), 2);
+        }
+    }
+
+    #[test]
+    fn run_context_caches_literals() {
+        let degree = 8192;
+
+        let (_keygen, context, _public_key, _private_key, encryptor, decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let params = crate::Params {
+            lattice_dimension: degree,
+            plain_modulus: PlainModulus::batching(degree, 17).unwrap().value(),
+            coeff_modulus: CoefficientModulus::bfv_default(degree, SecurityLevel::default())
+                .unwrap()
+                .iter()
+                .map(|m| m.value())
+                .collect(),
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::default(),
+        };
+
+        let literal = InnerPlaintext::Seal(vec![crate::serialization::WithContext {
+            params,
+            data: encoder.encode_signed(&vec![5; degree as usize]).unwrap(),
+        }])
+        .to_bytes()
+        .unwrap();
+
+        // Two distinct nodes holding the same literal.
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let l_0 = ir.add_input_literal(Literal::Plaintext(literal.clone()));
+        let l_1 = ir.add_input_literal(Literal::Plaintext(literal));
+        let b = ir.add_multiply_plaintext(a, l_0);
+        let c = ir.add_multiply_plaintext(b, l_1);
+        ir.add_output_ciphertext(c);
+
+        let pt_0 = encoder.encode_signed(&vec![3; degree as usize]).unwrap();
+        let ct_0 = encryptor.encrypt(&pt_0).unwrap();
+
+        let run_context = RunContext::new();
+
+        for _ in 0..2 {
+            let output = unsafe {
+                run_program_unchecked_with_context(
+                    &ir,
+                    &[ct_0.clone().into()],
+                    &evaluator,
+                    &None,
+                    &None,
+                    &run_context,
+                )
+                .unwrap()
+            };
+
+            let o_p = decryptor.decrypt(&output[0]).unwrap();
+
+            assert_eq!(
+                encoder.decode_signed(&o_p).unwrap(),
+                vec![75; degree as usize]
+            );
+            assert_eq!(run_context.cached_literals(), 1);
+        }
+    }
+
+    #[test]
+    fn literal_cache_respects_capacity() {
+        let degree = 8192;
+
+        let (_keygen, context, _public_key, _private_key, encryptor, decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let params = crate::Params {
+            lattice_dimension: degree,
+            plain_modulus: PlainModulus::batching(degree, 17).unwrap().value(),
+            coeff_modulus: CoefficientModulus::bfv_default(degree, SecurityLevel::default())
+                .unwrap()
+                .iter()
+                .map(|m| m.value())
+                .collect(),
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::default(),
+        };
+
+        let literal = |x: i64| {
+            InnerPlaintext::Seal(vec![crate::serialization::WithContext {
+                params: params.clone(),
+                data: encoder.encode_signed(&vec![x; degree as usize]).unwrap(),
+            }])
+            .to_bytes()
+            .unwrap()
+        };
+
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let l_0 = ir.add_input_literal(Literal::Plaintext(literal(5)));
+        let l_1 = ir.add_input_literal(Literal::Plaintext(literal(2)));
+        let b = ir.add_multiply_plaintext(a, l_0);
+        let c = ir.add_multiply_plaintext(b, l_1);
+        ir.add_output_ciphertext(c);
+
+        let pt_0 = encoder.encode_signed(&vec![3; degree as usize]).unwrap();
+        let ct_0 = encryptor.encrypt(&pt_0).unwrap();
+
+        let run_context = RunContext::with_literal_cache(Arc::new(LiteralCache::with_capacity(1)));
+
+        for _ in 0..2 {
+            let output = unsafe {
+                run_program_unchecked_with_context(
+                    &ir,
+                    &[ct_0.clone().into()],
+                    &evaluator,
+                    &None,
+                    &None,
+                    &run_context,
+                )
+                .unwrap()
+            };
+
+            let o_p = decryptor.decrypt(&output[0]).unwrap();
+
+            // The second literal doesn't fit, but still gets decoded.
+            assert_eq!(
+                encoder.decode_signed(&o_p).unwrap(),
+                vec![30; degree as usize]
+            );
+            assert_eq!(run_context.cached_literals(), 1);
+        }
+    }
+
+    #[test]
+    fn streaming_reports_each_output() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_add(a, b);
+        ir.add_output_ciphertext(c);
+        let d = ir.add_negate(a);
+        ir.add_output_ciphertext(d);
+
+        let degree = 8192;
+
+        let (_keygen, context, _public_key, _private_key, encryptor, decryptor, evaluator) =
+            setup_scheme(degree);
+
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let pt_0 = encoder.encode_signed(&vec![42; degree as usize]).unwrap();
+        let pt_1 = encoder.encode_signed(&vec![-24; degree as usize]).unwrap();
+
+        let ct_0 = encryptor.encrypt(&pt_0).unwrap();
+        let ct_1 = encryptor.encrypt(&pt_1).unwrap();
+
+        let streamed = Mutex::new(vec![]);
+
+        let output = unsafe {
+            run_program_unchecked_streaming(
+                &ir,
+                &[ct_0.into(), ct_1.into()],
+                &evaluator,
+                &None,
+                &None,
+                &RunContext::new(),
+                |i, c| streamed.lock().unwrap().push((i, c.clone())),
+            )
+            .unwrap()
+        };
+
+        let mut streamed = streamed.into_inner().unwrap();
+        streamed.sort_by_key(|(i, _)| *i);
+
+        assert_eq!(streamed.len(), output.len());
+
+        for (j, ((i, c), expected)) in streamed.iter().zip(output.iter()).enumerate() {
+            assert_eq!(*i, j);
+
+            let actual = encoder
+                .decode_signed(&decryptor.decrypt(c).unwrap())
+                .unwrap();
+            let expected = encoder
+                .decode_signed(&decryptor.decrypt(expected).unwrap())
+                .unwrap();
+
+            assert_eq!(actual, expected, "output {}", i);
+        }
+    }
 }
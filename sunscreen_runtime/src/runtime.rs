@@ -1,22 +1,26 @@
 use std::marker::PhantomData;
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::Instant;
 
 use crate::error::*;
 use crate::metadata::*;
 use crate::ZkpProgramInput;
 use crate::{
-    run_program_unchecked, serialization::WithContext, Ciphertext, FheProgramInput,
-    InnerCiphertext, InnerPlaintext, Plaintext, PrivateKey, PublicKey, SealCiphertext, SealData,
-    SealPlaintext, TryFromPlaintext, TryIntoPlaintext, TypeNameInstance,
+    run_program_unchecked_streaming, run_program_unchecked_with_context,
+    serialization::WithContext, Ciphertext, FheProgramInput, InnerCiphertext, InnerPlaintext,
+    LiteralCache, Plaintext, PrivateKey, PublicKey, RunContext, SealCiphertext, SealData,
+    SealPlaintext, TryFromPlaintext, TryFromPlaintextChecked, TryIntoPlaintext, TypeNameInstance,
+    TypedCiphertext,
 };
 
 use log::trace;
+use rayon::prelude::*;
 use sunscreen_fhe_program::FheProgramTrait;
 use sunscreen_fhe_program::SchemeType;
 
 use seal_fhe::{
     BFVEvaluator, BfvEncryptionParametersBuilder, Context as SealContext, Decryptor, Encryptor,
-    KeyGenerator, Modulus,
+    Evaluator, FromBytes, KeyGenerator, Modulus, ToBytes,
 };
 
 pub use sunscreen_compiler_common::{Type, TypeName};
@@ -29,6 +33,22 @@ enum Context {
     Seal(SealContext),
 }
 
+/**
+ * Returns the full form of `c` if it's a seeded ciphertext from
+ * [`encrypt_symmetric`](GenericRuntime::encrypt_symmetric), which isn't
+ * valid for evaluation or decryption until its seed is expanded.
+ */
+fn expand_seed(context: &SealContext, c: &SealCiphertext) -> Result<Option<SealCiphertext>> {
+    if c.is_valid_for(context) {
+        return Ok(None);
+    }
+
+    // Loading a saved seeded ciphertext regenerates its second polynomial.
+    let expanded = SealCiphertext::from_bytes(context, &c.as_bytes()?)?;
+
+    Ok(Some(expanded))
+}
+
 /**
  * Marker traits.
  */
@@ -68,6 +88,7 @@ impl marker::Zkp for FheZkp {}
 struct FheRuntimeData {
     params: Params,
     context: Context,
+    literals: Arc<LiteralCache>,
 }
 
 struct ZkpRuntimeData;
@@ -118,6 +139,117 @@ where
     pub fn decrypt<P>(&self, ciphertext: &Ciphertext, private_key: &PrivateKey) -> Result<P>
     where
         P: TryFromPlaintext + TypeName,
+    {
+        Ok(self.decrypt_with_budget(ciphertext, private_key)?.0)
+    }
+
+    /**
+     * Decrypts the given [`TypedCiphertext`] into the type it encrypts.
+     */
+    pub fn decrypt_typed<P>(
+        &self,
+        ciphertext: &TypedCiphertext<P>,
+        private_key: &PrivateKey,
+    ) -> Result<P>
+    where
+        P: TryFromPlaintext + TypeName,
+    {
+        self.decrypt(ciphertext.as_untyped(), private_key)
+    }
+
+    /**
+     * Decrypts the given ciphertext into the type P and also returns its
+     * remaining noise budget in bits.
+     *
+     * # Remarks
+     * This is equivalent to calling [`decrypt`](Self::decrypt) and
+     * [`measure_noise_budget`](Self::measure_noise_budget), but only
+     * creates one decryptor and measures each ciphertext once. As with
+     * the latter, the budget is the minimum over all the ciphertexts
+     * `ciphertext` contains.
+     */
+    pub fn decrypt_with_budget<P>(
+        &self,
+        ciphertext: &Ciphertext,
+        private_key: &PrivateKey,
+    ) -> Result<(P, u32)>
+    where
+        P: TryFromPlaintext + TypeName,
+    {
+        let (plaintext, budget) = self.decrypt_plaintext::<P>(ciphertext, private_key, false)?;
+
+        let val = P::try_from_plaintext(&plaintext, &self.runtime_data.unwrap_fhe().params)?;
+
+        Ok((val, budget))
+    }
+
+    /**
+     * Decrypts the given ciphertext into the type P (typically a
+     * batched type) along with whether its noise budget is at least
+     * `min_budget` bits.
+     *
+     * # Remarks
+     * Unlike [`decrypt`](Self::decrypt), this decodes the slots even when
+     * the budget is exhausted rather than returning
+     * [`Error::TooMuchNoise`], so long reductions can inspect a suspect
+     * result. The flag is `false` in that case, whatever `min_budget` is.
+     *
+     * BFV noise is a property of the whole ciphertext, not of individual
+     * slots: either every slot decodes correctly or none is guaranteed
+     * to. So the flag applies to all the slots at once, and checking it
+     * with a margin tells you to refresh (e.g. re-encrypt) a value before
+     * further operations destroy it.
+     */
+    pub fn decrypt_batched_checked<P>(
+        &self,
+        ciphertext: &Ciphertext,
+        private_key: &PrivateKey,
+        min_budget: u32,
+    ) -> Result<(P, bool)>
+    where
+        P: TryFromPlaintext + TypeName,
+    {
+        let (plaintext, budget) = self.decrypt_plaintext::<P>(ciphertext, private_key, true)?;
+
+        let val = P::try_from_plaintext(&plaintext, &self.runtime_data.unwrap_fhe().params)?;
+
+        Ok((val, budget > 0 && budget >= min_budget))
+    }
+
+    /**
+     * Decrypts the given ciphertext into the type P, returning
+     * [`Error::Overflow`] if the value doesn't fit in P.
+     *
+     * # Remarks
+     * [`decrypt`](Self::decrypt) decodes out-of-range values by wrapping
+     * them, which makes an FHE program whose result outgrew its type
+     * look like it computed the wrong thing. This instead reports the
+     * overflow. See the [`TryFromPlaintextChecked`] implementation of
+     * each type for which overflows it can detect.
+     */
+    pub fn decrypt_checked<P>(&self, ciphertext: &Ciphertext, private_key: &PrivateKey) -> Result<P>
+    where
+        P: TryFromPlaintextChecked + TypeName,
+    {
+        let (plaintext, _) = self.decrypt_plaintext::<P>(ciphertext, private_key, false)?;
+
+        P::try_from_plaintext_checked(&plaintext, &self.runtime_data.unwrap_fhe().params)
+    }
+
+    /**
+     * Checks `ciphertext` encrypts a P and decrypts it, returning the
+     * plaintext and the minimum remaining noise budget. Fails with
+     * [`Error::TooMuchNoise`] if any ciphertext has no budget left, unless
+     * `allow_exhausted` is set.
+     */
+    fn decrypt_plaintext<P>(
+        &self,
+        ciphertext: &Ciphertext,
+        private_key: &PrivateKey,
+        allow_exhausted: bool,
+    ) -> Result<(Plaintext, u32)>
+    where
+        P: TypeName,
     {
         let expected_type = Type {
             is_encrypted: true,
@@ -134,17 +266,24 @@ where
             (Context::Seal(context), InnerCiphertext::Seal(ciphertexts)) => {
                 let decryptor = Decryptor::new(context, &private_key.0)?;
 
+                let mut budget = u32::MAX;
+
                 let plaintexts = ciphertexts
                     .iter()
                     .map(|c| {
-                        if decryptor
+                        let expanded = expand_seed(context, c)?;
+                        let c = expanded.as_ref().unwrap_or(&c.data);
+
+                        let noise_budget = decryptor
                             .invariant_noise_budget(c)
-                            .map_err(Error::SealError)?
-                            == 0
-                        {
+                            .map_err(Error::SealError)?;
+
+                        if noise_budget == 0 && !allow_exhausted {
                             return Err(Error::TooMuchNoise);
                         }
 
+                        budget = u32::min(budget, noise_budget);
+
                         decryptor.decrypt(c).map_err(Error::SealError)
                     })
                     .collect::<Result<Vec<SealPlaintext>>>()?
@@ -155,13 +294,12 @@ where
                     })
                     .collect();
 
-                P::try_from_plaintext(
-                    &Plaintext {
-                        data_type: P::type_name(),
-                        inner: InnerPlaintext::Seal(plaintexts),
-                    },
-                    &fhe_data.params,
-                )?
+                let plaintext = Plaintext {
+                    data_type: P::type_name(),
+                    inner: InnerPlaintext::Seal(plaintexts),
+                };
+
+                (plaintext, budget)
             }
         };
 
@@ -177,19 +315,130 @@ where
      * than one ciphertext. This function returns the
      * *minimum* noise budget remaining of all the enclosed
      * ciphertexts.
+     *
+     * Use this to check that an FHE program's outputs keep the margin
+     * requested from the compiler's `additional_noise_budget`, or to tell
+     * whether a garbled result ran out of budget.
+     *
+     * Returns [`Error::ParameterMismatch`] if `c` or `private_key` belong
+     * to a different parameter set than this runtime and
+     * [`Error::TransparentCiphertext`] if any enclosed ciphertext is
+     * transparent.
      */
     pub fn measure_noise_budget(&self, c: &Ciphertext, private_key: &PrivateKey) -> Result<u32> {
         let fhe_data = self.runtime_data.unwrap_fhe();
 
+        if private_key.0.params != fhe_data.params {
+            return Err(Error::ParameterMismatch);
+        }
+
         match (&fhe_data.context, &c.inner) {
             (Context::Seal(ctx), InnerCiphertext::Seal(ciphertexts)) => {
                 let decryptor = Decryptor::new(ctx, &private_key.0)?;
 
-                Ok(ciphertexts
+                ciphertexts.iter().try_fold(u32::MAX, |min, c| {
+                    if c.params != fhe_data.params {
+                        return Err(Error::ParameterMismatch);
+                    }
+
+                    if c.data.is_transparent() {
+                        return Err(Error::TransparentCiphertext);
+                    }
+
+                    let expanded = expand_seed(ctx, &c.data)?;
+                    let c = expanded.as_ref().unwrap_or(&c.data);
+
+                    Ok(u32::min(min, decryptor.invariant_noise_budget(c)?))
+                })
+            }
+        }
+    }
+
+    /**
+     * Relinearizes each ciphertext in `c`, shrinking the 3 polynomials a
+     * multiplication leaves back to 2.
+     *
+     * # Remarks
+     * FHE programs relinearize automatically, so this is only useful for
+     * experimenting with ciphertexts outside of one (e.g. comparing
+     * [`measure_noise_budget`](Self::measure_noise_budget) before and
+     * after). Relinearizing a ciphertext that already has 2 polynomials
+     * returns it unchanged.
+     *
+     * Returns [`Error::MissingRelinearizationKeys`] if `public_key` has
+     * none and [`Error::ParameterMismatch`] if `c` or `public_key` belong
+     * to a different parameter set than this runtime.
+     */
+    pub fn relinearize(&self, c: &Ciphertext, public_key: &PublicKey) -> Result<Ciphertext> {
+        let relin_keys = public_key
+            .relin_key
+            .as_ref()
+            .ok_or(Error::MissingRelinearizationKeys)?;
+
+        if relin_keys.params != self.runtime_data.unwrap_fhe().params {
+            return Err(Error::ParameterMismatch);
+        }
+
+        self.map_ciphertexts(c, |evaluator, c| {
+            if c.num_polynomials() <= 2 {
+                return Ok(c.clone());
+            }
+
+            Ok(evaluator.relinearize(c, &relin_keys.data)?)
+        })
+    }
+
+    /**
+     * Switches each ciphertext in `c` to the next modulus in the
+     * coefficient modulus chain, dropping one prime.
+     *
+     * # Remarks
+     * Smaller moduli make later operations faster and ciphertexts
+     * smaller to send, at the cost of noise budget. FHE programs don't
+     * modulus switch on their own, so this lets you study the trade-off
+     * with [`measure_noise_budget`](Self::measure_noise_budget).
+     *
+     * Returns [`Error::ParameterMismatch`] if `c` belongs to a different
+     * parameter set than this runtime, and an [`Error::SealError`] if `c`
+     * is already at the last modulus in the chain.
+     */
+    pub fn mod_switch_to_next(&self, c: &Ciphertext) -> Result<Ciphertext> {
+        self.map_ciphertexts(c, |evaluator, c| Ok(evaluator.mod_switch_to_next(c)?))
+    }
+
+    /**
+     * Applies `f` to each (seed-expanded) SEAL ciphertext in `c`.
+     */
+    fn map_ciphertexts<F>(&self, c: &Ciphertext, f: F) -> Result<Ciphertext>
+    where
+        F: Fn(&BFVEvaluator, &SealCiphertext) -> Result<SealCiphertext>,
+    {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match (&fhe_data.context, &c.inner) {
+            (Context::Seal(ctx), InnerCiphertext::Seal(ciphertexts)) => {
+                let evaluator = BFVEvaluator::new(ctx)?;
+
+                let ciphertexts = ciphertexts
                     .iter()
-                    .fold(Ok(u32::MAX), |min: Result<u32>, c| {
-                        Ok(u32::min(min?, decryptor.invariant_noise_budget(&c.data)?))
-                    })?)
+                    .map(|c| {
+                        if c.params != fhe_data.params {
+                            return Err(Error::ParameterMismatch);
+                        }
+
+                        let expanded = expand_seed(ctx, &c.data)?;
+
+                        Ok(WithContext {
+                            params: fhe_data.params.clone(),
+                            data: f(&evaluator, expanded.as_ref().unwrap_or(&c.data))?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(Ciphertext {
+                    data_type: c.data_type.clone(),
+                    inner: InnerCiphertext::Seal(ciphertexts),
+                })
             }
         }
     }
@@ -206,24 +455,68 @@ where
      * See [`PublicKey`] for more information.
      */
     pub fn generate_keys(&self) -> Result<(PublicKey, PrivateKey)> {
+        self.generate_keys_with(true, true)
+    }
+
+    /**
+     * Like [`generate_keys`](Self::generate_keys), but only generates the
+     * Galois and relinearization keys some program in `fhe_programs`
+     * requires, as listed in its [`FheProgramMetadata::required_keys`].
+     *
+     * # Remarks
+     * Galois keys are by far the slowest keys to generate and the largest
+     * to send, and only programs that rotate or swap rows use them. The
+     * returned public key can't run programs that need a key it lacks; see
+     * [`PublicKey::can_rotate`] and [`PublicKey::can_relinearize`].
+     */
+    pub fn generate_keys_for<'a, I>(&self, fhe_programs: I) -> Result<(PublicKey, PrivateKey)>
+    where
+        I: IntoIterator<Item = &'a CompiledFheProgram>,
+    {
+        let mut galois = false;
+        let mut relin = false;
+
+        for program in fhe_programs {
+            galois |= program
+                .metadata
+                .required_keys
+                .contains(&RequiredKeys::Galois);
+            relin |= program
+                .metadata
+                .required_keys
+                .contains(&RequiredKeys::Relin);
+        }
+
+        self.generate_keys_with(galois, relin)
+    }
+
+    fn generate_keys_with(&self, galois: bool, relin: bool) -> Result<(PublicKey, PrivateKey)> {
         let fhe_data = self.runtime_data.unwrap_fhe();
 
         let keys = match &fhe_data.context {
             Context::Seal(context) => {
                 let keygen = KeyGenerator::new(context)?;
 
-                let galois_keys = keygen.create_galois_keys().ok().map(|v| WithContext {
-                    params: fhe_data.params.clone(),
-                    data: v,
-                });
-
-                let relin_keys = keygen
-                    .create_relinearization_keys()
-                    .ok()
-                    .map(|v| WithContext {
+                let galois_keys = if galois {
+                    keygen.create_galois_keys().ok().map(|v| WithContext {
                         params: fhe_data.params.clone(),
                         data: v,
-                    });
+                    })
+                } else {
+                    None
+                };
+
+                let relin_keys = if relin {
+                    keygen
+                        .create_relinearization_keys()
+                        .ok()
+                        .map(|v| WithContext {
+                            params: fhe_data.params.clone(),
+                            data: v,
+                        })
+                } else {
+                    None
+                };
 
                 let public_keys = PublicKey {
                     public_key: WithContext {
@@ -256,17 +549,321 @@ where
 
     /**
      * Validates and runs the given FHE program. Unless you can guarantee your FHE program is valid,
-     * you should use this method rather than [`run_program_unchecked`].
+     * you should use this method rather than
+     * [`run_program_unchecked`](crate::run_program_unchecked).
+     *
+     * # Remarks
+     * `public_key` need only hold the keys `fhe_program` uses. Without
+     * Galois keys (see [`PublicKey::can_rotate`]), programs that don't
+     * rotate still run and those that do return
+     * [`Error::MissingGaloisKeys`] before evaluating anything. Likewise,
+     * programs that multiply ciphertexts need relinearization keys and
+     * otherwise return [`Error::MissingRelinearizationKeys`].
+     *
+     * The runtime decodes each plaintext literal in `fhe_program` on the
+     * first run to use it and reuses the result on later runs, so
+     * repeatedly running the same program only pays for this once.
      */
     pub fn run<I>(
         &self,
         fhe_program: &CompiledFheProgram,
-        mut arguments: Vec<I>,
+        arguments: Vec<I>,
         public_key: &PublicKey,
     ) -> Result<Vec<Ciphertext>>
     where
         I: Into<FheProgramInput>,
     {
+        let literals = self.runtime_data.unwrap_fhe().literals.clone();
+
+        self.run_with_context(
+            fhe_program,
+            arguments,
+            public_key,
+            &RunContext::with_literal_cache(literals),
+        )
+    }
+
+    /**
+     * Like [`run`](Self::run), but draws intermediate ciphertexts from
+     * `run_context` and returns them to it afterwards. Reusing one
+     * [`RunContext`] when repeatedly running programs avoids reallocating
+     * these on every call.
+     */
+    pub fn run_with_context<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+        run_context: &RunContext,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        self.validate_program(fhe_program, public_key)?;
+
+        let inputs = self.unpack_arguments(fhe_program, arguments)?;
+
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                let evaluator = BFVEvaluator::new(context)?;
+
+                let relin_key = public_key.relin_key.as_ref().map(|p| &p.data);
+                let galois_key = public_key.galois_key.as_ref().map(|p| &p.data);
+
+                let raw_ciphertexts = unsafe {
+                    run_program_unchecked_with_context(
+                        &fhe_program.fhe_program_fn,
+                        &inputs,
+                        &evaluator,
+                        &relin_key,
+                        &galois_key,
+                        run_context,
+                    )
+                }?;
+
+                Ok(Self::pack_outputs(
+                    fhe_program,
+                    &fhe_data.params,
+                    raw_ciphertexts,
+                ))
+            }
+        }
+    }
+
+    /**
+     * Runs `fhe_program` once for each set of arguments in `arguments`,
+     * returning each run's outputs in the same order.
+     *
+     * # Remarks
+     * The program is validated and the evaluator created once for the
+     * whole batch rather than once per call, then the runs proceed in
+     * parallel on Sunscreen's thread pool (see
+     * [`set_thread_pool_size`](crate::set_thread_pool_size)) while sharing
+     * one [`RunContext`]. For small programs, where that setup and the
+     * scheduler's per-run overhead dominate, this is substantially faster
+     * than calling [`run`](Self::run) in a loop. Large programs already
+     * spread a single run across threads, so they gain less.
+     *
+     * Every argument set is checked against the program's signature
+     * before anything runs. If any run fails, this returns that error.
+     */
+    pub fn run_many<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<Vec<I>>,
+        public_key: &PublicKey,
+    ) -> Result<Vec<Vec<Ciphertext>>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        self.validate_program(fhe_program, public_key)?;
+
+        let inputs = arguments
+            .into_iter()
+            .map(|a| self.unpack_arguments(fhe_program, a))
+            .collect::<Result<Vec<_>>>()?;
+
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                let evaluator = BFVEvaluator::new(context)?;
+
+                let relin_key = public_key.relin_key.as_ref().map(|p| &p.data);
+                let galois_key = public_key.galois_key.as_ref().map(|p| &p.data);
+
+                let run_context = RunContext::with_literal_cache(fhe_data.literals.clone());
+
+                crate::thread_pool::install(|| {
+                    inputs
+                        .par_iter()
+                        .map(|inputs| -> Result<Vec<Ciphertext>> {
+                            let raw_ciphertexts = unsafe {
+                                run_program_unchecked_with_context(
+                                    &fhe_program.fhe_program_fn,
+                                    inputs,
+                                    &evaluator,
+                                    &relin_key,
+                                    &galois_key,
+                                    &run_context,
+                                )
+                            }?;
+
+                            Ok(Self::pack_outputs(
+                                fhe_program,
+                                &fhe_data.params,
+                                raw_ciphertexts,
+                            ))
+                        })
+                        .collect()
+                })
+            }
+        }
+    }
+
+    /**
+     * Groups the raw ciphertexts from running `fhe_program` into its
+     * return values.
+     */
+    fn pack_outputs(
+        fhe_program: &CompiledFheProgram,
+        params: &Params,
+        mut raw_ciphertexts: Vec<SealCiphertext>,
+    ) -> Vec<Ciphertext> {
+        let mut packed_ciphertexts = vec![];
+
+        for (i, ciphertext_count) in fhe_program
+            .metadata
+            .signature
+            .num_ciphertexts
+            .iter()
+            .enumerate()
+        {
+            packed_ciphertexts.push(Ciphertext {
+                data_type: fhe_program.metadata.signature.returns[i].clone(),
+                inner: InnerCiphertext::Seal(
+                    raw_ciphertexts
+                        .drain(0..*ciphertext_count)
+                        .map(|c| WithContext {
+                            params: params.clone(),
+                            data: c,
+                        })
+                        .collect(),
+                ),
+            });
+        }
+
+        packed_ciphertexts
+    }
+
+    /**
+     * Like [`run`](Self::run), but returns an iterator yielding each
+     * `(output_index, ciphertext)` pair as soon as that output is computed
+     * rather than waiting for the whole program to finish.
+     *
+     * # Remarks
+     * The program runs on a background thread, so outputs arrive in
+     * completion order rather than return order; `output_index` is the
+     * output's position in the program's return values. Should the run
+     * fail partway through, the iterator yields the error and ends.
+     *
+     * Validation happens before anything runs, so malformed programs and
+     * arguments are reported through the outer [`Result`]. The background
+     * thread owns copies of the program and keys, which costs a clone of
+     * each per call.
+     */
+    pub fn run_streaming<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+    ) -> Result<impl Iterator<Item = Result<(usize, Ciphertext)>>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        self.validate_program(fhe_program, public_key)?;
+
+        let inputs = self.unpack_arguments(fhe_program, arguments)?;
+
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                let evaluator = BFVEvaluator::new(context)?;
+
+                let ir = fhe_program.fhe_program_fn.clone();
+                let signature = fhe_program.metadata.signature.clone();
+                let params = fhe_data.params.clone();
+                let relin_key = public_key.relin_key.as_ref().map(|p| p.data.clone());
+                let galois_key = public_key.galois_key.as_ref().map(|p| p.data.clone());
+                let literals = fhe_data.literals.clone();
+
+                // The return value each raw output ciphertext belongs to and
+                // its position within that value.
+                let owners = signature
+                    .num_ciphertexts
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, n)| (0..*n).map(move |j| (i, j)))
+                    .collect::<Vec<_>>();
+
+                let (sender, receiver) = mpsc::channel();
+
+                std::thread::spawn(move || {
+                    let pending = signature
+                        .num_ciphertexts
+                        .iter()
+                        .map(|n| {
+                            (0..*n)
+                                .map(|_| None)
+                                .collect::<Vec<Option<SealCiphertext>>>()
+                        })
+                        .collect::<Vec<_>>();
+
+                    let state = Mutex::new((pending, sender));
+
+                    let on_output = |i: usize, c: &SealCiphertext| {
+                        let (output, j) = owners[i];
+                        let mut state = state.lock().unwrap();
+                        let (pending, sender) = &mut *state;
+
+                        pending[output][j] = Some(c.clone());
+
+                        if pending[output].iter().all(Option::is_some) {
+                            let ciphertext = Ciphertext {
+                                data_type: signature.returns[output].clone(),
+                                inner: InnerCiphertext::Seal(
+                                    pending[output]
+                                        .drain(0..)
+                                        .flatten()
+                                        .map(|c| WithContext {
+                                            params: params.clone(),
+                                            data: c,
+                                        })
+                                        .collect(),
+                                ),
+                            };
+
+                            // The caller may have dropped the iterator, in
+                            // which case nobody wants this output.
+                            let _ = sender.send(Ok((output, ciphertext)));
+                        }
+                    };
+
+                    let result = unsafe {
+                        run_program_unchecked_streaming(
+                            &ir,
+                            &inputs,
+                            &evaluator,
+                            &relin_key.as_ref(),
+                            &galois_key.as_ref(),
+                            &RunContext::with_literal_cache(literals),
+                            on_output,
+                        )
+                    };
+
+                    if let Err(e) = result {
+                        let (_, sender) = state.into_inner().unwrap();
+                        let _ = sender.send(Err(e.into()));
+                    }
+                });
+
+                Ok(receiver.into_iter())
+            }
+        }
+    }
+
+    /**
+     * Checks `fhe_program` is well formed and that `public_key` holds the
+     * keys it needs.
+     */
+    fn validate_program(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        public_key: &PublicKey,
+    ) -> Result<()> {
         // We're going to call run_program_unchecked, which
         // can result in undefined behavior, non-termination,
         // or panics on malformed programs. Since this method is safe,
@@ -275,114 +872,113 @@ where
         fhe_program.fhe_program_fn.validate()?;
 
         // Aside from FHE program correctness, check that the required keys are given.
-        if public_key.relin_key.is_none() && fhe_program.fhe_program_fn.requires_relin_keys() {
+        if !public_key.can_relinearize() && fhe_program.fhe_program_fn.requires_relin_keys() {
             return Err(Error::MissingRelinearizationKeys);
         }
 
-        if public_key.galois_key.is_none() && fhe_program.fhe_program_fn.requires_galois_keys() {
+        if !public_key.can_rotate() && fhe_program.fhe_program_fn.requires_galois_keys() {
             return Err(Error::MissingGaloisKeys);
         }
 
-        let mut arguments: Vec<FheProgramInput> = arguments.drain(0..).map(|a| a.into()).collect();
+        if fhe_program.metadata.signature.num_ciphertexts.len()
+            != fhe_program.metadata.signature.returns.len()
+        {
+            return Err(Error::ReturnTypeMetadataError);
+        }
 
-        let expected_args = &fhe_program.metadata.signature.arguments;
+        Ok(())
+    }
 
-        // Check the arguments match the signature.
-        if expected_args.len() != arguments.len() {
-            return Err(Error::IncorrectCiphertextCount);
+    /**
+     * Checks the number and types of `arguments` against
+     * `fhe_program`'s [`CallSignature`](crate::CallSignature).
+     *
+     * # Remarks
+     * Returns [`Error::TooFewArguments`] or [`Error::TooManyArguments`]
+     * if the count is wrong, and otherwise
+     * [`Error::ArgumentTypeMismatch`] naming the first argument whose
+     * type differs from the signature's. [`run`](Self::run) and its
+     * variants perform this check before evaluating anything, so calling
+     * this is only needed to check arguments ahead of time.
+     */
+    pub fn validate<I>(&self, fhe_program: &CompiledFheProgram, arguments: &[I]) -> Result<()>
+    where
+        I: TypeNameInstance,
+    {
+        let expected = &fhe_program.metadata.signature.arguments;
+
+        if arguments.len() < expected.len() {
+            return Err(Error::TooFewArguments {
+                expected: expected.len(),
+                actual: arguments.len(),
+            });
         }
 
-        // Check the passed arguments' types match the signature.
-        if arguments
-            .iter()
-            .enumerate()
-            .any(|(i, a)| a.type_name_instance() != expected_args[i])
-        {
-            return Err(Error::argument_mismatch(
-                expected_args,
-                &arguments
-                    .iter()
-                    .map(|a| a.type_name_instance())
-                    .collect::<Vec<Type>>(),
-            ));
+        if arguments.len() > expected.len() {
+            return Err(Error::TooManyArguments {
+                expected: expected.len(),
+                actual: arguments.len(),
+            });
         }
 
-        if fhe_program.metadata.signature.num_ciphertexts.len()
-            != fhe_program.metadata.signature.returns.len()
-        {
-            return Err(Error::ReturnTypeMetadataError);
+        for (i, (expected, argument)) in expected.iter().zip(arguments).enumerate() {
+            let actual = argument.type_name_instance();
+
+            if actual != *expected {
+                return Err(Error::argument_type_mismatch(i, expected, &actual));
+            }
         }
 
+        Ok(())
+    }
+
+    /**
+     * Checks `arguments` match `fhe_program`'s signature, then flattens
+     * them into the SEAL values
+     * [`run_program_unchecked`](crate::run_program_unchecked) expects.
+     */
+    fn unpack_arguments<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        mut arguments: Vec<I>,
+    ) -> Result<Vec<SealData>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        let mut arguments: Vec<FheProgramInput> = arguments.drain(0..).map(|a| a.into()).collect();
+
+        self.validate(fhe_program, &arguments)?;
+
         let fhe_data = self.runtime_data.unwrap_fhe();
 
-        match &fhe_data.context {
-            Context::Seal(context) => {
-                let evaluator = BFVEvaluator::new(context)?;
+        let mut inputs: Vec<SealData> = vec![];
 
-                let mut inputs: Vec<SealData> = vec![];
+        for i in arguments.drain(0..) {
+            match i {
+                FheProgramInput::Ciphertext(c) => match (&fhe_data.context, c.inner) {
+                    (Context::Seal(context), InnerCiphertext::Seal(mut c)) => {
+                        for j in c.drain(0..) {
+                            let data = expand_seed(context, &j.data)?.unwrap_or(j.data);
 
-                for i in arguments.drain(0..) {
-                    match i {
-                        FheProgramInput::Ciphertext(c) => match c.inner {
-                            InnerCiphertext::Seal(mut c) => {
-                                for j in c.drain(0..) {
-                                    inputs.push(SealData::Ciphertext(j.data));
-                                }
-                            }
-                        },
-                        FheProgramInput::Plaintext(p) => {
-                            let p = p.try_into_plaintext(&fhe_data.params)?;
-
-                            match p.inner {
-                                InnerPlaintext::Seal(mut p) => {
-                                    for j in p.drain(0..) {
-                                        inputs.push(SealData::Plaintext(j.data));
-                                    }
-                                }
+                            inputs.push(SealData::Ciphertext(data));
+                        }
+                    }
+                },
+                FheProgramInput::Plaintext(p) => {
+                    let p = p.try_into_plaintext(&fhe_data.params)?;
+
+                    match p.inner {
+                        InnerPlaintext::Seal(mut p) => {
+                            for j in p.drain(0..) {
+                                inputs.push(SealData::Plaintext(j.data));
                             }
                         }
                     }
                 }
-
-                let relin_key = public_key.relin_key.as_ref().map(|p| &p.data);
-                let galois_key = public_key.galois_key.as_ref().map(|p| &p.data);
-
-                let mut raw_ciphertexts = unsafe {
-                    run_program_unchecked(
-                        &fhe_program.fhe_program_fn,
-                        &inputs,
-                        &evaluator,
-                        &relin_key,
-                        &galois_key,
-                    )
-                }?;
-
-                let mut packed_ciphertexts = vec![];
-
-                for (i, ciphertext_count) in fhe_program
-                    .metadata
-                    .signature
-                    .num_ciphertexts
-                    .iter()
-                    .enumerate()
-                {
-                    packed_ciphertexts.push(Ciphertext {
-                        data_type: fhe_program.metadata.signature.returns[i].clone(),
-                        inner: InnerCiphertext::Seal(
-                            raw_ciphertexts
-                                .drain(0..*ciphertext_count)
-                                .map(|c| WithContext {
-                                    params: fhe_data.params.clone(),
-                                    data: c,
-                                })
-                                .collect(),
-                        ),
-                    });
-                }
-
-                Ok(packed_ciphertexts)
             }
         }
+
+        Ok(inputs)
     }
 
     /**
@@ -394,6 +990,45 @@ where
     pub fn encrypt<P>(&self, val: P, public_key: &PublicKey) -> Result<Ciphertext>
     where
         P: TryIntoPlaintext + TypeName,
+    {
+        self.encrypt_with(
+            val,
+            |context| Encryptor::with_public_key(context, &public_key.public_key.data),
+            Encryptor::encrypt,
+        )
+    }
+
+    /**
+     * Encrypts the given [`FheType`](crate::FheType) using the given
+     * private key rather than a public key.
+     *
+     * # Remarks
+     * Symmetric encryption is useful when whoever encrypts the data also
+     * decrypts the results. Its ciphertexts store a seed in place of half
+     * their data, so they serialize to about half the size of those from
+     * [`encrypt`](Self::encrypt). They otherwise behave identically:
+     * [`run`](Self::run) and [`decrypt`](Self::decrypt) accept them
+     * directly or after deserialization.
+     *
+     * Returns [`Error::ParameterMismatch`] if the plaintext is incompatible with this runtime's
+     * scheme.
+     */
+    pub fn encrypt_symmetric<P>(&self, val: P, private_key: &PrivateKey) -> Result<Ciphertext>
+    where
+        P: TryIntoPlaintext + TypeName,
+    {
+        self.encrypt_with(
+            val,
+            |context| Encryptor::with_secret_key(context, &private_key.0.data),
+            Encryptor::encrypt_symmetric,
+        )
+    }
+
+    fn encrypt_with<P, M, E>(&self, val: P, make_encryptor: M, encrypt: E) -> Result<Ciphertext>
+    where
+        P: TryIntoPlaintext + TypeName,
+        M: FnOnce(&SealContext) -> seal_fhe::Result<Encryptor>,
+        E: Fn(&Encryptor, &SealPlaintext) -> seal_fhe::Result<SealCiphertext>,
     {
         let fhe_data = self.runtime_data.unwrap_fhe();
 
@@ -401,11 +1036,11 @@ where
 
         let ciphertext = match (&fhe_data.context, plaintext.inner) {
             (Context::Seal(context), InnerPlaintext::Seal(inner_plain)) => {
-                let encryptor = Encryptor::with_public_key(context, &public_key.public_key.data)?;
+                let encryptor = make_encryptor(context)?;
 
                 let ciphertexts = inner_plain
                     .iter()
-                    .map(|p| encryptor.encrypt(p).map_err(Error::SealError))
+                    .map(|p| encrypt(&encryptor, p).map_err(Error::SealError))
                     .collect::<Result<Vec<SealCiphertext>>>()?
                     .drain(0..)
                     .map(|c| WithContext {
@@ -426,6 +1061,17 @@ where
 
         Ok(ciphertext)
     }
+
+    /**
+     * Encrypts the given value like [`encrypt`](Self::encrypt), but
+     * returns a [`TypedCiphertext`] that remembers `P`.
+     */
+    pub fn encrypt_typed<P>(&self, val: P, public_key: &PublicKey) -> Result<TypedCiphertext<P>>
+    where
+        P: TryIntoPlaintext + TypeName,
+    {
+        self.encrypt(val, public_key)?.try_into()
+    }
 }
 
 impl<T, B> GenericRuntime<T, B>
@@ -527,6 +1173,8 @@ impl GenericRuntime<(), ()> {
     }
 
     fn make_fhe_runtime_data(params: &Params) -> Result<FheRuntimeData> {
+        params.validate()?;
+
         match params.scheme_type {
             SchemeType::Bfv => {
                 let bfv_params = BfvEncryptionParametersBuilder::new()
@@ -536,8 +1184,8 @@ impl GenericRuntime<(), ()> {
                         params
                             .coeff_modulus
                             .iter()
-                            .map(|v| Modulus::new(*v).unwrap())
-                            .collect::<Vec<Modulus>>(),
+                            .map(|v| Modulus::new(*v))
+                            .collect::<std::result::Result<Vec<Modulus>, _>>()?,
                     )
                     .build()?;
 
@@ -546,6 +1194,7 @@ impl GenericRuntime<(), ()> {
                 Ok(FheRuntimeData {
                     params: params.clone(),
                     context: Context::Seal(context),
+                    literals: Arc::default(),
                 })
             }
         }
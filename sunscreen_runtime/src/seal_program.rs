@@ -0,0 +1,596 @@
+use crate::{run::FheProgramRunFailure, InnerPlaintext};
+use sunscreen_compiler_common::GraphQuery;
+use sunscreen_fhe_program::{FheProgram, Literal, Operation::*};
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex};
+use seal_fhe::Plaintext;
+
+/**
+ * A register holding an intermediate value in a [`SealProgram`]. Registers
+ * correspond to node indices in the [`FheProgram`] the program was lowered from.
+ */
+pub type Register = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+/**
+ * A single SEAL evaluator call in a [`SealProgram`].
+ *
+ * # Remarks
+ * Each instruction reads its operands from and writes its result to
+ * [`Register`]s. Every register is written exactly once before any
+ * instruction reads it.
+ */
+pub enum SealInstruction {
+    /**
+     * Copy the `input`th argument (ciphertext or plaintext) into `dst`.
+     */
+    LoadInput {
+        /**
+         * The argument index.
+         */
+        input: usize,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * Place a plaintext constant into `dst`.
+     */
+    LoadPlaintext {
+        /**
+         * The constant.
+         */
+        plaintext: Plaintext,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::add`
+     */
+    Add {
+        /**
+         * The left ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The right ciphertext operand.
+         */
+        b: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::add_plain`
+     */
+    AddPlain {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The plaintext operand.
+         */
+        b: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::sub`
+     */
+    Sub {
+        /**
+         * The left ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The right ciphertext operand.
+         */
+        b: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::sub_plain`
+     */
+    SubPlain {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The plaintext operand.
+         */
+        b: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::multiply`
+     */
+    Multiply {
+        /**
+         * The left ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The right ciphertext operand.
+         */
+        b: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::multiply_plain`
+     */
+    MultiplyPlain {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The plaintext operand.
+         */
+        b: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::negate`
+     */
+    Negate {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::relinearize`. Requires relinearization keys.
+     */
+    Relinearize {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::rotate_rows`. Requires Galois keys. Positive `steps`
+     * rotate left, negative rotate right. Lowering rejects rotation
+     * amounts past [`i32::MAX`] rather than truncating them, and SEAL
+     * rejects any amount not less than the row size.
+     */
+    RotateRows {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The number of steps to rotate.
+         */
+        steps: i32,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * `Evaluator::rotate_columns`. Requires Galois keys.
+     */
+    RotateColumns {
+        /**
+         * The ciphertext operand.
+         */
+        a: Register,
+
+        /**
+         * The destination register.
+         */
+        dst: Register,
+    },
+
+    /**
+     * Append the ciphertext in `src` to the program's outputs.
+     */
+    Output {
+        /**
+         * The register holding the output ciphertext.
+         */
+        src: Register,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/**
+ * An [`FheProgram`] lowered into a straight-line sequence of SEAL evaluator calls.
+ *
+ * # Remarks
+ * This describes exactly what [`run_program_unchecked`](crate::run_program_unchecked)
+ * does, but in a form you can execute yourself with a raw SEAL evaluator.
+ * Executing [`instructions`](SealProgram::instructions) in order produces
+ * outputs in the same order as [`Runtime::run`](crate::Runtime::run).
+ */
+pub struct SealProgram {
+    /**
+     * The evaluator calls to make, in order.
+     */
+    pub instructions: Vec<SealInstruction>,
+
+    /**
+     * The number of registers needed to execute the instructions. Every
+     * [`Register`] in `instructions` is less than this value.
+     */
+    pub num_registers: usize,
+}
+
+/**
+ * Lowers the given [`FheProgram`] into a [`SealProgram`].
+ *
+ * # Remarks
+ * The program should be valid (see
+ * [`validate()`](sunscreen_fhe_program::FheProgramTrait::validate())).
+ * Malformed programs, including those with rotation amounts that don't
+ * fit in an [`i32`], return an error rather than panicking.
+ */
+pub fn to_seal_program(ir: &FheProgram) -> Result<SealProgram, FheProgramRunFailure> {
+    let query = GraphQuery::new(&ir.graph.0);
+
+    let order = toposort(&ir.graph.0, None).map_err(|_| FheProgramRunFailure::MalformedProgram)?;
+
+    let rotation_amount = |index: NodeIndex| match &ir.graph[index].operation {
+        Literal(x) => x
+            .as_rotation_amount()
+            .ok_or(FheProgramRunFailure::MalformedProgram),
+        _ => Err(FheProgramRunFailure::MalformedProgram),
+    };
+
+    let mut instructions = vec![];
+
+    for index in order {
+        let dst = index.index();
+
+        let instruction = match &ir.graph[index].operation {
+            InputCiphertext(input) | InputPlaintext(input) => {
+                SealInstruction::LoadInput { input: *input, dst }
+            }
+            Literal(Literal::U64(_) | Literal::U128(_)) => {
+                // Rotation amounts get folded into the rotation itself.
+                continue;
+            }
+            Literal(Literal::Plaintext(p)) => {
+                let p = InnerPlaintext::from_bytes(p)
+                    .map_err(|_| FheProgramRunFailure::MalformedPlaintext)?;
+
+                let plaintext = match p {
+                    InnerPlaintext::Seal(mut p) => {
+                        if p.len() != 1 {
+                            return Err(FheProgramRunFailure::MalformedPlaintext);
+                        }
+
+                        p.remove(0).data
+                    }
+                };
+
+                SealInstruction::LoadPlaintext { plaintext, dst }
+            }
+            ShiftLeft => {
+                let (left, right) = query.get_binary_operands(index)?;
+
+                SealInstruction::RotateRows {
+                    a: left.index(),
+                    steps: rotation_amount(right)?,
+                    dst,
+                }
+            }
+            ShiftRight => {
+                let (left, right) = query.get_binary_operands(index)?;
+
+                SealInstruction::RotateRows {
+                    a: left.index(),
+                    steps: -rotation_amount(right)?,
+                    dst,
+                }
+            }
+            Add | AddPlaintext | Sub | SubPlaintext | Multiply | MultiplyPlaintext => {
+                let (left, right) = query.get_binary_operands(index)?;
+                let (a, b) = (left.index(), right.index());
+
+                match &ir.graph[index].operation {
+                    Add => SealInstruction::Add { a, b, dst },
+                    AddPlaintext => SealInstruction::AddPlain { a, b, dst },
+                    Sub => SealInstruction::Sub { a, b, dst },
+                    SubPlaintext => SealInstruction::SubPlain { a, b, dst },
+                    Multiply => SealInstruction::Multiply { a, b, dst },
+                    _ => SealInstruction::MultiplyPlain { a, b, dst },
+                }
+            }
+            Negate => SealInstruction::Negate {
+                a: query.get_unary_operand(index)?.index(),
+                dst,
+            },
+            Relinearize => SealInstruction::Relinearize {
+                a: query.get_unary_operand(index)?.index(),
+                dst,
+            },
+            SwapRows => SealInstruction::RotateColumns {
+                a: query.get_unary_operand(index)?.index(),
+                dst,
+            },
+            OutputCiphertext => {
+                // Outputs are appended at the end in node order to match `run`.
+                continue;
+            }
+        };
+
+        instructions.push(instruction);
+    }
+
+    for index in ir.graph.node_indices() {
+        if let OutputCiphertext = ir.graph[index].operation {
+            instructions.push(SealInstruction::Output {
+                src: query.get_unary_operand(index)?.index(),
+            });
+        }
+    }
+
+    Ok(SealProgram {
+        instructions,
+        num_registers: ir.graph.node_bound(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{run_program_unchecked, SealData};
+    use seal_fhe::*;
+    use sunscreen_fhe_program::{FheProgramTrait, SchemeType};
+
+    fn execute(
+        program: &SealProgram,
+        inputs: &[SealData],
+        evaluator: &BFVEvaluator,
+        relin_keys: &RelinearizationKeys,
+        galois_keys: &GaloisKeys,
+    ) -> Vec<Ciphertext> {
+        let mut registers: Vec<Option<SealData>> = vec![None; program.num_registers];
+        let mut outputs = vec![];
+
+        let ct = |r: &[Option<SealData>], i: Register| match &r[i] {
+            Some(SealData::Ciphertext(c)) => c.clone(),
+            _ => panic!("Expected ciphertext in register {}", i),
+        };
+        let pt = |r: &[Option<SealData>], i: Register| match &r[i] {
+            Some(SealData::Plaintext(p)) => p.clone(),
+            _ => panic!("Expected plaintext in register {}", i),
+        };
+
+        for i in &program.instructions {
+            let (dst, val): (Register, SealData) = match i {
+                SealInstruction::LoadInput { input, dst } => (*dst, inputs[*input].clone()),
+                SealInstruction::LoadPlaintext { plaintext, dst } => {
+                    (*dst, plaintext.clone().into())
+                }
+                SealInstruction::Add { a, b, dst } => (
+                    *dst,
+                    evaluator
+                        .add(&ct(&registers, *a), &ct(&registers, *b))
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::AddPlain { a, b, dst } => (
+                    *dst,
+                    evaluator
+                        .add_plain(&ct(&registers, *a), &pt(&registers, *b))
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::Sub { a, b, dst } => (
+                    *dst,
+                    evaluator
+                        .sub(&ct(&registers, *a), &ct(&registers, *b))
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::SubPlain { a, b, dst } => (
+                    *dst,
+                    evaluator
+                        .sub_plain(&ct(&registers, *a), &pt(&registers, *b))
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::Multiply { a, b, dst } => (
+                    *dst,
+                    evaluator
+                        .multiply(&ct(&registers, *a), &ct(&registers, *b))
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::MultiplyPlain { a, b, dst } => (
+                    *dst,
+                    evaluator
+                        .multiply_plain(&ct(&registers, *a), &pt(&registers, *b))
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::Negate { a, dst } => {
+                    (*dst, evaluator.negate(&ct(&registers, *a)).unwrap().into())
+                }
+                SealInstruction::Relinearize { a, dst } => (
+                    *dst,
+                    evaluator
+                        .relinearize(&ct(&registers, *a), relin_keys)
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::RotateRows { a, steps, dst } => (
+                    *dst,
+                    evaluator
+                        .rotate_rows(&ct(&registers, *a), *steps, galois_keys)
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::RotateColumns { a, dst } => (
+                    *dst,
+                    evaluator
+                        .rotate_columns(&ct(&registers, *a), galois_keys)
+                        .unwrap()
+                        .into(),
+                ),
+                SealInstruction::Output { src } => {
+                    outputs.push(ct(&registers, *src));
+                    continue;
+                }
+            };
+
+            registers[dst] = Some(val);
+        }
+
+        outputs
+    }
+
+    #[test]
+    fn rejects_rotation_amounts_past_i32() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let l = ir.add_input_literal(Literal::U64(1 << 32));
+        let b = ir.add_rotate_left(a, l);
+        ir.add_output_ciphertext(b);
+
+        assert!(matches!(
+            to_seal_program(&ir),
+            Err(FheProgramRunFailure::MalformedProgram)
+        ));
+    }
+
+    #[test]
+    fn lowered_program_matches_run() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_multiply(a, b);
+        let c = ir.add_relinearize(c);
+        let l = ir.add_input_literal(Literal::U64(2));
+        let d = ir.add_rotate_left(c, l);
+        let e = ir.add_sub(d, a);
+        let f = ir.add_negate(b);
+        ir.add_output_ciphertext(e);
+        ir.add_output_ciphertext(f);
+
+        let degree = 4096;
+
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(degree)
+            .set_plain_modulus(PlainModulus::batching(degree, 17).unwrap())
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(degree, SecurityLevel::default()).unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let context = Context::new(&params, true, SecurityLevel::default()).unwrap();
+        let keygen = KeyGenerator::new(&context).unwrap();
+        let public_key = keygen.create_public_key();
+        let private_key = keygen.secret_key();
+        let relin_keys = keygen.create_relinearization_keys().unwrap();
+        let galois_keys = keygen.create_galois_keys().unwrap();
+        let encryptor = Encryptor::with_public_key(&context, &public_key).unwrap();
+        let decryptor = Decryptor::new(&context, &private_key).unwrap();
+        let evaluator = BFVEvaluator::new(&context).unwrap();
+        let encoder = BFVEncoder::new(&context).unwrap();
+
+        let a: Vec<i64> = (0..degree as i64).collect();
+        let b = vec![-3; degree as usize];
+
+        let inputs: Vec<SealData> = vec![
+            encryptor
+                .encrypt(&encoder.encode_signed(&a).unwrap())
+                .unwrap()
+                .into(),
+            encryptor
+                .encrypt(&encoder.encode_signed(&b).unwrap())
+                .unwrap()
+                .into(),
+        ];
+
+        let program = to_seal_program(&ir).unwrap();
+        let lowered = execute(&program, &inputs, &evaluator, &relin_keys, &galois_keys);
+
+        let expected = unsafe {
+            run_program_unchecked(
+                &ir,
+                &inputs,
+                &evaluator,
+                &Some(&relin_keys),
+                &Some(&galois_keys),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(lowered.len(), 2);
+        assert_eq!(lowered.len(), expected.len());
+
+        for (l, e) in lowered.iter().zip(expected.iter()) {
+            let l = encoder
+                .decode_signed(&decryptor.decrypt(l).unwrap())
+                .unwrap();
+            let e = encoder
+                .decode_signed(&decryptor.decrypt(e).unwrap())
+                .unwrap();
+
+            assert_eq!(l, e);
+        }
+    }
+}
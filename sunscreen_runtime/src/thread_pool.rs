@@ -0,0 +1,65 @@
+use std::sync::{Arc, RwLock};
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::{Error, Result};
+
+static THREAD_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+/**
+ * Caps the number of threads Sunscreen uses to run FHE programs.
+ *
+ * # Remarks
+ * By default, Sunscreen runs on rayon's global thread pool, which has one
+ * thread per logical CPU. Calling this replaces that with a dedicated pool
+ * of `n` threads for all subsequent runs, which is useful when embedding
+ * Sunscreen in a larger system that manages its own parallelism. Passing
+ * `0` reverts to rayon's global pool.
+ *
+ * Runs already in progress finish on the pool they started on.
+ */
+pub fn set_thread_pool_size(n: usize) -> Result<()> {
+    let pool = if n == 0 {
+        None
+    } else {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(n)
+            .thread_name(|i| format!("sunscreen-{}", i))
+            .build()
+            .map_err(|e| Error::ThreadPoolError(Box::new(e.to_string())))?;
+
+        Some(Arc::new(pool))
+    };
+
+    *THREAD_POOL.write().unwrap() = pool;
+
+    Ok(())
+}
+
+/**
+ * Returns the number of threads Sunscreen will use to run FHE programs.
+ */
+pub fn thread_pool_size() -> usize {
+    match THREAD_POOL.read().unwrap().as_ref() {
+        Some(pool) => pool.current_num_threads(),
+        None => rayon::current_num_threads(),
+    }
+}
+
+/**
+ * Runs `f` in the thread pool configured with [`set_thread_pool_size`],
+ * or directly (i.e. in rayon's global pool) if none is set.
+ */
+pub(crate) fn install<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    // Clone the pool out so we don't hold the lock while running.
+    let pool = THREAD_POOL.read().unwrap().clone();
+
+    match pool {
+        Some(pool) => pool.install(f),
+        None => f(),
+    }
+}